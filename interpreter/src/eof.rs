@@ -0,0 +1,1182 @@
+//! EVM Object Format containers, see
+//! [EIP-3540](https://eips.ethereum.org/EIPS/eip-3540) for the format and
+//! [EIP-3670](https://eips.ethereum.org/EIPS/eip-3670) for code validation.
+
+use alloc::{vec, vec::Vec};
+use core::ops::Range;
+
+use crate::opcode::Opcode;
+
+/// Magic prefix of EOF containers.
+pub const MAGIC: [u8; 2] = [0xef, 0x00];
+/// Supported EOF version.
+pub const VERSION: u8 = 0x01;
+
+const KIND_TYPES: u8 = 0x01;
+const KIND_CODE: u8 = 0x02;
+const KIND_CONTAINER: u8 = 0x03;
+const KIND_DATA: u8 = 0xff;
+const TERMINATOR: u8 = 0x00;
+
+const TYPE_SIZE: usize = 4;
+const MAX_CODE_SECTIONS: usize = 1024;
+const MAX_CONTAINER_SECTIONS: usize = 256;
+const MAX_INPUTS: u8 = 0x7f;
+const MAX_OUTPUTS: u8 = 0x7f;
+const NON_RETURNING: u8 = 0x80;
+const MAX_STACK_HEIGHT: u16 = 0x03ff;
+const STACK_LIMIT: usize = 1024;
+
+/// Reason for a container to be invalid.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EofError {
+	/// Code does not start with [MAGIC].
+	InvalidMagic,
+	/// Version other than [VERSION].
+	UnsupportedVersion(u8),
+	/// Header ends before the terminator.
+	TruncatedHeader,
+	/// Section header of another kind than expected.
+	InvalidSectionKind {
+		/// Expected kind.
+		expected: u8,
+		/// Kind found in the header.
+		found: u8,
+	},
+	/// No code or container sections, or too many of them.
+	InvalidSectionCount,
+	/// Code or container section of size zero.
+	EmptySection,
+	/// Size of the types section does not match the number of code sections.
+	InvalidTypesSize,
+	/// Body is shorter or longer than the sizes in the header.
+	InvalidBodySize,
+	/// First code section takes inputs or returns.
+	InvalidFirstSectionType,
+	/// Inputs, outputs or maximum stack height out of bounds.
+	InvalidSectionType {
+		/// Index of the code section.
+		section: usize,
+	},
+	/// Undefined or deprecated opcode in a code section.
+	InvalidOpcode {
+		/// Index of the code section.
+		section: usize,
+		/// Offset of the opcode in the code section.
+		offset: usize,
+		/// The offending opcode.
+		opcode: Opcode,
+	},
+	/// Immediate of the last instruction of a code section runs past its end.
+	TruncatedImmediate {
+		/// Index of the code section.
+		section: usize,
+		/// Offset of the instruction in the code section.
+		offset: usize,
+	},
+	/// Code section not ending with a terminating instruction.
+	NoTerminatingInstruction {
+		/// Index of the code section.
+		section: usize,
+	},
+	/// Relative jump out of its code section or into an immediate.
+	InvalidJumpDestination {
+		/// Index of the code section.
+		section: usize,
+		/// Offset of the jump in the code section.
+		offset: usize,
+	},
+	/// `DATALOADN` reading past the end of the data section.
+	InvalidDataOffset {
+		/// Index of the code section.
+		section: usize,
+		/// Offset of the instruction in the code section.
+		offset: usize,
+	},
+	/// `EOFCREATE` or `RETURNCONTRACT` referring to a missing nested
+	/// container.
+	InvalidContainerIndex {
+		/// Index of the code section.
+		section: usize,
+		/// Offset of the instruction in the code section.
+		offset: usize,
+	},
+	/// `CALLF` or `JUMPF` to a missing code section, or to a section whose
+	/// outputs cannot be returned.
+	InvalidFunctionTarget {
+		/// Index of the code section.
+		section: usize,
+		/// Offset of the instruction in the code section.
+		offset: usize,
+	},
+	/// Instruction taking more stack items than the stack holds.
+	StackUnderflow {
+		/// Index of the code section.
+		section: usize,
+		/// Offset of the instruction in the code section.
+		offset: usize,
+	},
+	/// `CALLF` or `JUMPF` whose target may overflow the stack.
+	StackOverflow {
+		/// Index of the code section.
+		section: usize,
+		/// Offset of the instruction in the code section.
+		offset: usize,
+	},
+	/// Instruction reached with different stack heights, or returning with
+	/// another number of outputs than its section type.
+	InconsistentStackHeight {
+		/// Index of the code section.
+		section: usize,
+		/// Offset of the instruction in the code section.
+		offset: usize,
+	},
+	/// Maximum stack height of the code other than in its section type.
+	InvalidMaxStackHeight {
+		/// Index of the code section.
+		section: usize,
+	},
+	/// Instruction not reachable from the start of its code section.
+	UnreachableCode {
+		/// Index of the code section.
+		section: usize,
+		/// Offset of the instruction in the code section.
+		offset: usize,
+	},
+}
+
+/// Type of a code section.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CodeType {
+	/// Number of stack items the section takes.
+	pub inputs: u8,
+	/// Number of stack items the section returns, or `0x80` if it does not
+	/// return.
+	pub outputs: u8,
+	/// Maximum stack height reached by the section.
+	pub max_stack_height: u16,
+}
+
+/// Sections of a parsed container.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EofContainer<'a> {
+	/// Types of the code sections.
+	pub types: Vec<CodeType>,
+	/// Code sections.
+	pub code_sections: Vec<&'a [u8]>,
+	/// Nested containers.
+	pub container_sections: Vec<&'a [u8]>,
+	/// Data section.
+	pub data: &'a [u8],
+}
+
+/// Position of the code sections of a container in its code, used to execute
+/// it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EofLayout {
+	/// Range of each code section in the container.
+	pub code_sections: Vec<Range<usize>>,
+	/// Types of the code sections.
+	pub types: Vec<CodeType>,
+	/// Range of each nested container in the container.
+	pub container_sections: Vec<Range<usize>>,
+	/// Range of the data section in the container.
+	pub data: Range<usize>,
+}
+
+struct Reader<'a> {
+	code: &'a [u8],
+	position: usize,
+}
+
+impl<'a> Reader<'a> {
+	fn bytes(&mut self, len: usize, err: EofError) -> Result<&'a [u8], EofError> {
+		let end = self.position.checked_add(len).ok_or(err)?;
+		let bytes = self.code.get(self.position..end).ok_or(err)?;
+		self.position = end;
+		Ok(bytes)
+	}
+
+	fn u8(&mut self) -> Result<u8, EofError> {
+		Ok(self.bytes(1, EofError::TruncatedHeader)?[0])
+	}
+
+	fn u16(&mut self) -> Result<usize, EofError> {
+		let bytes = self.bytes(2, EofError::TruncatedHeader)?;
+		Ok(u16::from_be_bytes([bytes[0], bytes[1]]) as usize)
+	}
+
+	fn u32(&mut self) -> Result<usize, EofError> {
+		let bytes = self.bytes(4, EofError::TruncatedHeader)?;
+		Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+	}
+
+	fn kind(&mut self, expected: u8) -> Result<(), EofError> {
+		let found = self.u8()?;
+		if found != expected {
+			return Err(EofError::InvalidSectionKind { expected, found });
+		}
+		Ok(())
+	}
+
+	fn sizes(
+		&mut self,
+		max: usize,
+		read: fn(&mut Self) -> Result<usize, EofError>,
+	) -> Result<Vec<usize>, EofError> {
+		let count = self.u16()?;
+		if count == 0 || count > max {
+			return Err(EofError::InvalidSectionCount);
+		}
+
+		let mut sizes = Vec::with_capacity(count);
+		for _ in 0..count {
+			let size = read(self)?;
+			if size == 0 {
+				return Err(EofError::EmptySection);
+			}
+			sizes.push(size);
+		}
+		Ok(sizes)
+	}
+}
+
+/// Whether the code starts with [MAGIC] and is meant as an EOF container.
+#[must_use]
+pub fn is_eof(code: &[u8]) -> bool {
+	code.starts_with(&MAGIC)
+}
+
+/// Parse the sections of a container, checking its header and section
+/// sizes. The code is not validated, see [validate].
+pub fn parse(code: &[u8]) -> Result<EofContainer<'_>, EofError> {
+	let mut reader = Reader { code, position: 0 };
+
+	if reader.bytes(MAGIC.len(), EofError::InvalidMagic)? != MAGIC {
+		return Err(EofError::InvalidMagic);
+	}
+	let version = reader.u8()?;
+	if version != VERSION {
+		return Err(EofError::UnsupportedVersion(version));
+	}
+
+	reader.kind(KIND_TYPES)?;
+	let types_size = reader.u16()?;
+	reader.kind(KIND_CODE)?;
+	let code_sizes = reader.sizes(MAX_CODE_SECTIONS, Reader::u16)?;
+	if types_size != code_sizes.len() * TYPE_SIZE {
+		return Err(EofError::InvalidTypesSize);
+	}
+
+	let mut kind = reader.u8()?;
+	let container_sizes = if kind == KIND_CONTAINER {
+		let sizes = reader.sizes(MAX_CONTAINER_SECTIONS, Reader::u32)?;
+		kind = reader.u8()?;
+		sizes
+	} else {
+		Vec::new()
+	};
+	if kind != KIND_DATA {
+		return Err(EofError::InvalidSectionKind {
+			expected: KIND_DATA,
+			found: kind,
+		});
+	}
+	let data_size = reader.u16()?;
+	reader.kind(TERMINATOR)?;
+
+	let types = reader
+		.bytes(types_size, EofError::InvalidBodySize)?
+		.chunks(TYPE_SIZE)
+		.map(|ty| CodeType {
+			inputs: ty[0],
+			outputs: ty[1],
+			max_stack_height: u16::from_be_bytes([ty[2], ty[3]]),
+		})
+		.collect();
+	let code_sections = code_sizes
+		.into_iter()
+		.map(|size| reader.bytes(size, EofError::InvalidBodySize))
+		.collect::<Result<_, _>>()?;
+	let container_sections = container_sizes
+		.into_iter()
+		.map(|size| reader.bytes(size, EofError::InvalidBodySize))
+		.collect::<Result<_, _>>()?;
+	let data = reader.bytes(data_size, EofError::InvalidBodySize)?;
+	if reader.position != code.len() {
+		return Err(EofError::InvalidBodySize);
+	}
+
+	Ok(EofContainer {
+		types,
+		code_sections,
+		container_sections,
+		data,
+	})
+}
+
+/// Layout of a container, see [EofLayout]. The container is parsed but not
+/// validated, so instructions can only be executed on it after [validate].
+pub fn layout(code: &[u8]) -> Result<EofLayout, EofError> {
+	let container = parse(code)?;
+
+	let types_size = container.types.len() * TYPE_SIZE;
+	let body_size = types_size
+		+ container
+			.code_sections
+			.iter()
+			.map(|section| section.len())
+			.sum::<usize>()
+		+ container
+			.container_sections
+			.iter()
+			.map(|section| section.len())
+			.sum::<usize>()
+		+ container.data.len();
+
+	let mut start = code.len() - body_size + types_size;
+	let mut ranges = |sections: &[&[u8]]| {
+		sections
+			.iter()
+			.map(|section| {
+				let range = start..(start + section.len());
+				start = range.end;
+				range
+			})
+			.collect::<Vec<_>>()
+	};
+	let code_sections = ranges(&container.code_sections);
+	let container_sections = ranges(&container.container_sections);
+
+	Ok(EofLayout {
+		code_sections,
+		types: container.types,
+		container_sections,
+		data: start..code.len(),
+	})
+}
+
+/// Container deployed by `RETURNCONTRACT`, that is `container` with `aux`
+/// appended to its data section, see
+/// [EIP-7620](https://eips.ethereum.org/EIPS/eip-7620).
+pub fn append_data(container: &[u8], aux: &[u8]) -> Result<Vec<u8>, EofError> {
+	let layout = layout(container)?;
+	let data_size =
+		u16::try_from(layout.data.len() + aux.len()).map_err(|_| EofError::InvalidBodySize)?;
+	// The header ends with the data size and the terminator.
+	let header_size = layout.code_sections[0].start - layout.types.len() * TYPE_SIZE;
+
+	let mut deployed = container.to_vec();
+	deployed[(header_size - 3)..(header_size - 1)].copy_from_slice(&data_size.to_be_bytes());
+	deployed.extend_from_slice(aux);
+	Ok(deployed)
+}
+
+/// Parse a container and validate its section types and code, as done
+/// before deploying it. Nested containers are validated as well.
+pub fn validate(code: &[u8]) -> Result<EofContainer<'_>, EofError> {
+	let container = parse(code)?;
+
+	for (section, ty) in container.types.iter().enumerate() {
+		if section == 0 && (ty.inputs != 0 || ty.outputs != NON_RETURNING) {
+			return Err(EofError::InvalidFirstSectionType);
+		}
+		if ty.inputs > MAX_INPUTS
+			|| (ty.outputs > MAX_OUTPUTS && ty.outputs != NON_RETURNING)
+			|| ty.max_stack_height > MAX_STACK_HEIGHT
+		{
+			return Err(EofError::InvalidSectionType { section });
+		}
+	}
+
+	for (section, code) in container.code_sections.iter().enumerate() {
+		validate_code(section, code, &container)?;
+	}
+
+	for nested in &container.container_sections {
+		validate(nested)?;
+	}
+
+	Ok(container)
+}
+
+/// Size of the immediate of the instruction at `offset`.
+pub(crate) fn immediate_size(code: &[u8], offset: usize) -> usize {
+	let opcode = Opcode(code[offset]);
+	match opcode {
+		Opcode::DATALOADN | Opcode::RJUMP | Opcode::RJUMPI | Opcode::CALLF | Opcode::JUMPF => 2,
+		Opcode::DUPN
+		| Opcode::SWAPN
+		| Opcode::EXCHANGE
+		| Opcode::EOFCREATE
+		| Opcode::RETURNCONTRACT => 1,
+		Opcode::RJUMPV => code
+			.get(offset + 1)
+			.map_or(1, |max_index| 1 + (*max_index as usize + 1) * 2),
+		_ => opcode.is_push().unwrap_or(0) as usize,
+	}
+}
+
+fn is_terminating(opcode: Opcode) -> bool {
+	matches!(
+		opcode,
+		Opcode::STOP
+			| Opcode::RETURN
+			| Opcode::REVERT
+			| Opcode::INVALID
+			| Opcode::RJUMP
+			| Opcode::RETF
+			| Opcode::JUMPF
+			| Opcode::RETURNCONTRACT
+	)
+}
+
+/// Opcodes rejected in EOF code, as they observe code or gas, or have EOF
+/// replacements.
+fn is_deprecated(opcode: Opcode) -> bool {
+	matches!(
+		opcode,
+		Opcode::CODESIZE
+			| Opcode::CODECOPY
+			| Opcode::EXTCODESIZE
+			| Opcode::EXTCODECOPY
+			| Opcode::EXTCODEHASH
+			| Opcode::JUMP
+			| Opcode::JUMPI
+			| Opcode::PC
+			| Opcode::GAS
+			| Opcode::CREATE
+			| Opcode::CALL
+			| Opcode::CALLCODE
+			| Opcode::DELEGATECALL
+			| Opcode::CREATE2
+			| Opcode::STATICCALL
+			| Opcode::SUICIDE
+	)
+}
+
+/// Destinations of the relative jumps of the instruction at `offset`, ending
+/// at `end`.
+fn relative_destinations(
+	code: &[u8],
+	offset: usize,
+	end: usize,
+) -> impl Iterator<Item = isize> + '_ {
+	let relative = match Opcode(code[offset]) {
+		Opcode::RJUMP | Opcode::RJUMPI => &code[(offset + 1)..end],
+		Opcode::RJUMPV => &code[(offset + 2)..end],
+		_ => &[],
+	};
+	relative
+		.chunks(2)
+		.map(move |relative| end as isize + i16::from_be_bytes([relative[0], relative[1]]) as isize)
+}
+
+/// Code section called by the `CALLF` or `JUMPF` at `offset`.
+fn function_index(code: &[u8], offset: usize) -> usize {
+	u16::from_be_bytes([code[offset + 1], code[offset + 2]]) as usize
+}
+
+/// Stack items taken and returned by the instruction at `offset`, apart from
+/// `CALLF` and `JUMPF` whose stack effect comes from the type of their target.
+/// Opcodes rejected in EOF code have their legacy stack effect.
+pub(crate) fn stack_io(code: &[u8], offset: usize) -> (usize, usize) {
+	let opcode = Opcode(code[offset]);
+	if opcode.is_push().is_some() {
+		return (0, 1);
+	}
+
+	match opcode {
+		Opcode::DUPN => {
+			let n = code[offset + 1] as usize + 1;
+			(n, n + 1)
+		}
+		Opcode::SWAPN => {
+			let n = code[offset + 1] as usize + 2;
+			(n, n)
+		}
+		Opcode::EXCHANGE => {
+			let n = (code[offset + 1] >> 4) as usize + 1;
+			let m = (code[offset + 1] & 0x0f) as usize + 1;
+			(n + m + 1, n + m + 1)
+		}
+		Opcode::ADDMOD | Opcode::MULMOD => (3, 1),
+		Opcode::ADD
+		| Opcode::MUL
+		| Opcode::SUB
+		| Opcode::DIV
+		| Opcode::SDIV
+		| Opcode::MOD
+		| Opcode::SMOD
+		| Opcode::EXP
+		| Opcode::SIGNEXTEND
+		| Opcode::LT
+		| Opcode::GT
+		| Opcode::SLT
+		| Opcode::SGT
+		| Opcode::EQ
+		| Opcode::AND
+		| Opcode::OR
+		| Opcode::XOR
+		| Opcode::BYTE
+		| Opcode::SHL
+		| Opcode::SHR
+		| Opcode::SAR
+		| Opcode::SHA3 => (2, 1),
+		Opcode::ISZERO
+		| Opcode::NOT
+		| Opcode::BALANCE
+		| Opcode::CALLDATALOAD
+		| Opcode::EXTCODESIZE
+		| Opcode::EXTCODEHASH
+		| Opcode::BLOCKHASH
+		| Opcode::BLOBHASH
+		| Opcode::MLOAD
+		| Opcode::SLOAD
+		| Opcode::TLOAD
+		| Opcode::DATALOAD => (1, 1),
+		Opcode::ADDRESS
+		| Opcode::ORIGIN
+		| Opcode::CALLER
+		| Opcode::CALLVALUE
+		| Opcode::CALLDATASIZE
+		| Opcode::CODESIZE
+		| Opcode::GASPRICE
+		| Opcode::RETURNDATASIZE
+		| Opcode::COINBASE
+		| Opcode::TIMESTAMP
+		| Opcode::NUMBER
+		| Opcode::DIFFICULTY
+		| Opcode::GASLIMIT
+		| Opcode::CHAINID
+		| Opcode::SELFBALANCE
+		| Opcode::BASEFEE
+		| Opcode::BLOBBASEFEE
+		| Opcode::MSIZE
+		| Opcode::GAS
+		| Opcode::PC
+		| Opcode::PUSH0
+		| Opcode::DATALOADN
+		| Opcode::DATASIZE => (0, 1),
+		Opcode::CALLDATACOPY
+		| Opcode::CODECOPY
+		| Opcode::RETURNDATACOPY
+		| Opcode::MCOPY
+		| Opcode::DATACOPY => (3, 0),
+		Opcode::EXTCODECOPY => (4, 0),
+		Opcode::POP | Opcode::JUMP | Opcode::SUICIDE | Opcode::RJUMPI | Opcode::RJUMPV => (1, 0),
+		Opcode::MSTORE
+		| Opcode::MSTORE8
+		| Opcode::JUMPI
+		| Opcode::SSTORE
+		| Opcode::TSTORE
+		| Opcode::RETURN
+		| Opcode::REVERT
+		| Opcode::RETURNCONTRACT => (2, 0),
+		Opcode::CREATE | Opcode::AUTH => (3, 1),
+		Opcode::CREATE2 | Opcode::EOFCREATE => (4, 1),
+		Opcode::DELEGATECALL | Opcode::STATICCALL => (6, 1),
+		Opcode::CALL | Opcode::CALLCODE | Opcode::AUTHCALL => (7, 1),
+		_ => {
+			let op = opcode.as_usize();
+			if (Opcode::DUP1.as_usize()..=Opcode::DUP16.as_usize()).contains(&op) {
+				let n = op - Opcode::DUP1.as_usize() + 1;
+				(n, n + 1)
+			} else if (Opcode::SWAP1.as_usize()..=Opcode::SWAP16.as_usize()).contains(&op) {
+				let n = op - Opcode::SWAP1.as_usize() + 2;
+				(n, n)
+			} else if (Opcode::LOG0.as_usize()..=Opcode::LOG4.as_usize()).contains(&op) {
+				(op - Opcode::LOG0.as_usize() + 2, 0)
+			} else {
+				(0, 0)
+			}
+		}
+	}
+}
+
+/// Reject undefined and deprecated opcodes, truncated immediates, code not
+/// ending with a terminating instruction, relative jumps out of the code or
+/// into an immediate, references to missing sections and data loads past the
+/// data section, then validate the stack heights of the code.
+fn validate_code(section: usize, code: &[u8], container: &EofContainer) -> Result<(), EofError> {
+	let types = &container.types;
+	let ty = types[section];
+	let mut starts = vec![false; code.len()];
+	let mut jumps = Vec::new();
+	let mut last = Opcode::STOP;
+
+	let mut offset = 0;
+	while offset < code.len() {
+		let opcode = Opcode(code[offset]);
+		if opcode.name().is_none()
+			|| is_deprecated(opcode)
+			|| (opcode == Opcode::RETF && ty.outputs == NON_RETURNING)
+		{
+			return Err(EofError::InvalidOpcode {
+				section,
+				offset,
+				opcode,
+			});
+		}
+
+		let immediate = immediate_size(code, offset);
+		if offset + immediate >= code.len() {
+			return Err(EofError::TruncatedImmediate { section, offset });
+		}
+		let end = offset + 1 + immediate;
+
+		if opcode == Opcode::CALLF || opcode == Opcode::JUMPF {
+			let valid = match types.get(function_index(code, offset)) {
+				Some(target) if opcode == Opcode::CALLF => target.outputs != NON_RETURNING,
+				Some(target) => {
+					target.outputs == NON_RETURNING
+						|| (ty.outputs != NON_RETURNING && target.outputs <= ty.outputs)
+				}
+				None => false,
+			};
+			if !valid {
+				return Err(EofError::InvalidFunctionTarget { section, offset });
+			}
+		}
+
+		if (opcode == Opcode::EOFCREATE || opcode == Opcode::RETURNCONTRACT)
+			&& code[offset + 1] as usize >= container.container_sections.len()
+		{
+			return Err(EofError::InvalidContainerIndex { section, offset });
+		}
+
+		if opcode == Opcode::DATALOADN
+			&& u16::from_be_bytes([code[offset + 1], code[offset + 2]]) as usize + 32
+				> container.data.len()
+		{
+			return Err(EofError::InvalidDataOffset { section, offset });
+		}
+
+		jumps.extend(
+			relative_destinations(code, offset, end).map(|destination| (offset, destination)),
+		);
+		starts[offset] = true;
+		last = opcode;
+		offset = end;
+	}
+
+	if !is_terminating(last) {
+		return Err(EofError::NoTerminatingInstruction { section });
+	}
+
+	for (offset, destination) in jumps {
+		if destination < 0 || destination as usize >= code.len() || !starts[destination as usize] {
+			return Err(EofError::InvalidJumpDestination { section, offset });
+		}
+	}
+
+	validate_stack(section, code, types, &starts)
+}
+
+/// Check that each instruction is reached with a single stack height, that
+/// the stack does not underflow, that functions return their outputs and
+/// that the maximum stack height is the one of the section type, see
+/// [EIP-5450](https://eips.ethereum.org/EIPS/eip-5450).
+fn validate_stack(
+	section: usize,
+	code: &[u8],
+	types: &[CodeType],
+	starts: &[bool],
+) -> Result<(), EofError> {
+	let ty = types[section];
+	let mut heights = vec![None; code.len()];
+	let mut pending = vec![(0, ty.inputs as usize)];
+	let mut max_height = ty.inputs as usize;
+
+	while let Some((offset, height)) = pending.pop() {
+		match heights[offset] {
+			Some(known) if known == height => continue,
+			Some(_) => return Err(EofError::InconsistentStackHeight { section, offset }),
+			None => heights[offset] = Some(height),
+		}
+
+		let opcode = Opcode(code[offset]);
+		let end = offset + 1 + immediate_size(code, offset);
+		let target = match opcode {
+			Opcode::CALLF | Opcode::JUMPF => Some(&types[function_index(code, offset)]),
+			_ => None,
+		};
+		let (inputs, outputs) = match target {
+			Some(target) if opcode == Opcode::CALLF => {
+				(target.inputs as usize, target.outputs as usize)
+			}
+			Some(target) => (target.inputs as usize, 0),
+			None => stack_io(code, offset),
+		};
+		if height < inputs {
+			return Err(EofError::StackUnderflow { section, offset });
+		}
+		if let Some(target) = target {
+			if height - inputs + target.max_stack_height as usize > STACK_LIMIT {
+				return Err(EofError::StackOverflow { section, offset });
+			}
+		}
+		let next = height - inputs + outputs;
+		max_height = max_height.max(next);
+
+		let returns = match (opcode, target) {
+			(Opcode::RETF, _) => height == ty.outputs as usize,
+			(Opcode::JUMPF, Some(target)) => {
+				target.outputs == NON_RETURNING
+					|| height + target.outputs as usize == ty.outputs as usize + inputs
+			}
+			_ => true,
+		};
+		if !returns {
+			return Err(EofError::InconsistentStackHeight { section, offset });
+		}
+
+		if !is_terminating(opcode) {
+			pending.push((end, next));
+		}
+		pending.extend(
+			relative_destinations(code, offset, end)
+				.map(|destination| (destination as usize, next)),
+		);
+	}
+
+	if let Some(offset) =
+		(0..code.len()).find(|offset| starts[*offset] && heights[*offset].is_none())
+	{
+		return Err(EofError::UnreachableCode { section, offset });
+	}
+	if max_height != ty.max_stack_height as usize {
+		return Err(EofError::InvalidMaxStackHeight { section });
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Container with the given types and code sections, and `data`.
+	fn functions(sections: &[(&str, &str)], data: &str) -> Vec<u8> {
+		let data = hex::decode(data).unwrap();
+		let mut out = MAGIC.to_vec();
+		out.push(VERSION);
+		out.push(KIND_TYPES);
+		out.extend_from_slice(&((sections.len() * TYPE_SIZE) as u16).to_be_bytes());
+		out.push(KIND_CODE);
+		out.extend_from_slice(&(sections.len() as u16).to_be_bytes());
+		for (_, code) in sections {
+			out.extend_from_slice(&((code.len() / 2) as u16).to_be_bytes());
+		}
+		out.push(KIND_DATA);
+		out.extend_from_slice(&(data.len() as u16).to_be_bytes());
+		out.push(TERMINATOR);
+		for (ty, _) in sections {
+			out.extend(hex::decode(ty).unwrap());
+		}
+		for (_, code) in sections {
+			out.extend(hex::decode(code).unwrap());
+		}
+		out.extend(data);
+		out
+	}
+
+	/// Container with a single `code` section and `data`.
+	fn container(ty: &str, code: &str, data: &str) -> Vec<u8> {
+		functions(&[(ty, code)], data)
+	}
+
+	/// Container with a single `code` section, the `nested` container and no
+	/// data.
+	fn with_nested(ty: &str, code: &str, nested: &[u8]) -> Vec<u8> {
+		let mut out = MAGIC.to_vec();
+		out.push(VERSION);
+		out.push(KIND_TYPES);
+		out.extend_from_slice(&(TYPE_SIZE as u16).to_be_bytes());
+		out.push(KIND_CODE);
+		out.extend_from_slice(&1u16.to_be_bytes());
+		out.extend_from_slice(&((code.len() / 2) as u16).to_be_bytes());
+		out.push(KIND_CONTAINER);
+		out.extend_from_slice(&1u16.to_be_bytes());
+		out.extend_from_slice(&(nested.len() as u32).to_be_bytes());
+		out.push(KIND_DATA);
+		out.extend_from_slice(&0u16.to_be_bytes());
+		out.push(TERMINATOR);
+		out.extend(hex::decode(ty).unwrap());
+		out.extend(hex::decode(code).unwrap());
+		out.extend_from_slice(nested);
+		out
+	}
+
+	#[test]
+	fn rejects_legacy_only_opcodes() {
+		for opcode in [
+			Opcode::CODESIZE,
+			Opcode::CODECOPY,
+			Opcode::EXTCODESIZE,
+			Opcode::EXTCODECOPY,
+			Opcode::EXTCODEHASH,
+			Opcode::JUMP,
+			Opcode::JUMPI,
+			Opcode::PC,
+			Opcode::GAS,
+			Opcode::CREATE,
+			Opcode::CALL,
+			Opcode::CALLCODE,
+			Opcode::DELEGATECALL,
+			Opcode::CREATE2,
+			Opcode::STATICCALL,
+			Opcode::SUICIDE,
+		] {
+			let code = hex::encode([opcode.as_u8(), Opcode::STOP.as_u8()]);
+			assert_eq!(
+				validate(&container("00800000", &code, "")).map(|_| ()),
+				Err(EofError::InvalidOpcode {
+					section: 0,
+					offset: 0,
+					opcode,
+				})
+			);
+		}
+	}
+
+	#[test]
+	fn validation_vectors() {
+		let minimal = container("00800000", "00", "");
+		assert_eq!(minimal.len(), 20);
+		let with_container = |nested: &[u8]| with_nested("00800000", "00", nested);
+
+		let vectors: &[(Vec<u8>, Result<(), EofError>)] = &[
+			(minimal.clone(), Ok(())),
+			(container("00800002", "600160015500", "aabb"), Ok(())),
+			(container("00800000", "fe", ""), Ok(())),
+			(with_container(&minimal), Ok(())),
+			(hex::decode("ef01").unwrap(), Err(EofError::InvalidMagic)),
+			(hex::decode("ef").unwrap(), Err(EofError::InvalidMagic)),
+			(
+				hex::decode("ef0002").unwrap(),
+				Err(EofError::UnsupportedVersion(2)),
+			),
+			(
+				hex::decode("ef00010100040200").unwrap(),
+				Err(EofError::TruncatedHeader),
+			),
+			(
+				hex::decode("ef0001020004").unwrap(),
+				Err(EofError::InvalidSectionKind {
+					expected: KIND_TYPES,
+					found: KIND_CODE,
+				}),
+			),
+			(
+				hex::decode("ef0001010000020000").unwrap(),
+				Err(EofError::InvalidSectionCount),
+			),
+			(
+				hex::decode("ef0001010004020001000000").unwrap(),
+				Err(EofError::EmptySection),
+			),
+			(
+				hex::decode("ef00010100080200010001ff000000").unwrap(),
+				Err(EofError::InvalidTypesSize),
+			),
+			(
+				hex::decode("ef00010100040200010001ff000001").unwrap(),
+				Err(EofError::InvalidSectionKind {
+					expected: TERMINATOR,
+					found: 0x01,
+				}),
+			),
+			(
+				hex::decode("ef00010100040200010001040000000080000000").unwrap(),
+				Err(EofError::InvalidSectionKind {
+					expected: KIND_DATA,
+					found: 0x04,
+				}),
+			),
+			(
+				[&minimal[..], &[0x00]].concat(),
+				Err(EofError::InvalidBodySize),
+			),
+			(
+				minimal[..minimal.len() - 1].to_vec(),
+				Err(EofError::InvalidBodySize),
+			),
+			(
+				container("01800001", "00", ""),
+				Err(EofError::InvalidFirstSectionType),
+			),
+			(
+				container("00800400", "00", ""),
+				Err(EofError::InvalidSectionType { section: 0 }),
+			),
+			(
+				container("00800000", "0c", ""),
+				Err(EofError::InvalidOpcode {
+					section: 0,
+					offset: 0,
+					opcode: Opcode(0x0c),
+				}),
+			),
+			(
+				container("00800000", "6000ff", ""),
+				Err(EofError::InvalidOpcode {
+					section: 0,
+					offset: 2,
+					opcode: Opcode::SUICIDE,
+				}),
+			),
+			(
+				container("00800000", "f2", ""),
+				Err(EofError::InvalidOpcode {
+					section: 0,
+					offset: 0,
+					opcode: Opcode::CALLCODE,
+				}),
+			),
+			(
+				container("00800000", "ef", ""),
+				Err(EofError::InvalidOpcode {
+					section: 0,
+					offset: 0,
+					opcode: Opcode::EOFMAGIC,
+				}),
+			),
+			(
+				container("00800000", "006100", ""),
+				Err(EofError::TruncatedImmediate {
+					section: 0,
+					offset: 1,
+				}),
+			),
+			(container("00800000", "e0000000", ""), Ok(())),
+			(container("00800001", "5fe1fffc00", ""), Ok(())),
+			(container("00800001", "5fe2010000000100fe", ""), Ok(())),
+			(
+				container("00800001", "6001", ""),
+				Err(EofError::NoTerminatingInstruction { section: 0 }),
+			),
+			(
+				container("00800001", "5fe10000", ""),
+				Err(EofError::NoTerminatingInstruction { section: 0 }),
+			),
+			(
+				container("00800000", "e2010000", ""),
+				Err(EofError::TruncatedImmediate {
+					section: 0,
+					offset: 0,
+				}),
+			),
+			(
+				container("00800001", "6001e1fffc00", ""),
+				Err(EofError::InvalidJumpDestination {
+					section: 0,
+					offset: 2,
+				}),
+			),
+			(
+				container("00800000", "e0000100", ""),
+				Err(EofError::InvalidJumpDestination {
+					section: 0,
+					offset: 0,
+				}),
+			),
+			(
+				container("00800000", "e0fff000", ""),
+				Err(EofError::InvalidJumpDestination {
+					section: 0,
+					offset: 0,
+				}),
+			),
+			(
+				functions(&[("00800001", "e3000100"), ("00010001", "602ae4")], ""),
+				Ok(()),
+			),
+			(
+				functions(&[("00800001", "5fe50001"), ("01800001", "00")], ""),
+				Ok(()),
+			),
+			(
+				container("00800000", "e3000100", ""),
+				Err(EofError::InvalidFunctionTarget {
+					section: 0,
+					offset: 0,
+				}),
+			),
+			(
+				functions(&[("00800000", "e3000100"), ("00800000", "00")], ""),
+				Err(EofError::InvalidFunctionTarget {
+					section: 0,
+					offset: 0,
+				}),
+			),
+			(
+				container("00800000", "e4", ""),
+				Err(EofError::InvalidOpcode {
+					section: 0,
+					offset: 0,
+					opcode: Opcode::RETF,
+				}),
+			),
+			(
+				container("00800001", "600056", ""),
+				Err(EofError::InvalidOpcode {
+					section: 0,
+					offset: 2,
+					opcode: Opcode::JUMP,
+				}),
+			),
+			(
+				functions(&[("00800001", "e3000100"), ("00010000", "e4")], ""),
+				Err(EofError::InconsistentStackHeight {
+					section: 1,
+					offset: 0,
+				}),
+			),
+			(
+				container("00800001", "5fe100015f00", ""),
+				Err(EofError::InconsistentStackHeight {
+					section: 0,
+					offset: 5,
+				}),
+			),
+			(
+				container("00800000", "0100", ""),
+				Err(EofError::StackUnderflow {
+					section: 0,
+					offset: 0,
+				}),
+			),
+			(
+				container("00800002", "5f5000", ""),
+				Err(EofError::InvalidMaxStackHeight { section: 0 }),
+			),
+			(
+				container("00800000", "00fe", ""),
+				Err(EofError::UnreachableCode {
+					section: 0,
+					offset: 1,
+				}),
+			),
+			(
+				with_container(&container("00800000", "f2", "")),
+				Err(EofError::InvalidOpcode {
+					section: 0,
+					offset: 0,
+					opcode: Opcode::CALLCODE,
+				}),
+			),
+			(
+				with_nested("00800004", "5f5f5f5fec005000", &minimal),
+				Ok(()),
+			),
+			(with_nested("00800002", "5f5fee00", &minimal), Ok(())),
+			(
+				with_nested("00800004", "5f5f5f5fec015000", &minimal),
+				Err(EofError::InvalidContainerIndex {
+					section: 0,
+					offset: 4,
+				}),
+			),
+			(
+				container("00800002", "5f5fee00", ""),
+				Err(EofError::InvalidContainerIndex {
+					section: 0,
+					offset: 2,
+				}),
+			),
+			(container("00800002", "5fe60000", ""), Ok(())),
+			(container("00800002", "5f5fe70000", ""), Ok(())),
+			(container("00800003", "5f5f5fe80000", ""), Ok(())),
+			(
+				container("00800001", "5fe60100", ""),
+				Err(EofError::StackUnderflow {
+					section: 0,
+					offset: 1,
+				}),
+			),
+			(
+				container("00800001", "5fe70000", ""),
+				Err(EofError::StackUnderflow {
+					section: 0,
+					offset: 1,
+				}),
+			),
+			(
+				container("00800002", "5f5fe80000", ""),
+				Err(EofError::StackUnderflow {
+					section: 0,
+					offset: 2,
+				}),
+			),
+			(
+				container("00800000", "e6", ""),
+				Err(EofError::TruncatedImmediate {
+					section: 0,
+					offset: 0,
+				}),
+			),
+			(container("00800001", "d1000800", &"aa".repeat(40)), Ok(())),
+			(
+				container("00800001", "d1000900", &"aa".repeat(40)),
+				Err(EofError::InvalidDataOffset {
+					section: 0,
+					offset: 0,
+				}),
+			),
+			(
+				container("00800001", "d100", ""),
+				Err(EofError::TruncatedImmediate {
+					section: 0,
+					offset: 0,
+				}),
+			),
+			(
+				with_nested("00800002", "5f5fee", &minimal),
+				Err(EofError::TruncatedImmediate {
+					section: 0,
+					offset: 2,
+				}),
+			),
+		];
+
+		for (code, expected) in vectors {
+			assert_eq!(
+				&validate(code).map(|_| ()),
+				expected,
+				"{}",
+				hex::encode(code)
+			);
+		}
+
+		let code = container("00800002", "600160015500", "aabb");
+		let parsed = parse(&code).unwrap();
+		assert_eq!(
+			parsed.types,
+			[CodeType {
+				inputs: 0,
+				outputs: NON_RETURNING,
+				max_stack_height: 2,
+			}]
+		);
+		assert_eq!(
+			parsed.code_sections,
+			[&[0x60, 0x01, 0x60, 0x01, 0x55, 0x00][..]]
+		);
+		assert_eq!(layout(&code).unwrap().code_sections[0], 19..25);
+		assert_eq!(parsed.data, [0xaa, 0xbb]);
+	}
+
+	#[test]
+	fn append_aux_data() {
+		let code = with_nested("00800002", "5f5fee00", &container("00800000", "00", "aa"));
+		let layout = layout(&code).unwrap();
+		assert_eq!(layout.container_sections[0], 30..51);
+		assert_eq!(layout.data, 51..51);
+
+		let deployed =
+			append_data(&code[layout.container_sections[0].clone()], &[0xbb, 0xcc]).unwrap();
+		assert_eq!(deployed, container("00800000", "00", "aabbcc"));
+		assert!(validate(&deployed).is_ok());
+	}
+}
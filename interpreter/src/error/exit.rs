@@ -1,6 +1,8 @@
 use alloc::borrow::Cow;
 use core::fmt;
 
+use primitive_types::H160;
+
 use crate::opcode::Opcode;
 
 /// Exit result.
@@ -96,8 +98,11 @@ pub enum ExitException {
 	/// Create init code exceeds limit (runtime).
 	#[cfg_attr(feature = "scale", codec(index = 7))]
 	CreateContractLimit,
+	/// Deployed code starts with 0xef ([EIP-3541](https://github.com/ethereum/EIPs/blob/master/EIPS/eip-3541.md)).
+	#[cfg_attr(feature = "scale", codec(index = 18))]
+	CreateContractStartingWithEF,
 
-	/// Invalid opcode during execution or starting byte is 0xef ([EIP-3541](https://github.com/ethereum/EIPs/blob/master/EIPS/eip-3541.md)).
+	/// Invalid opcode during execution.
 	#[cfg_attr(feature = "scale", codec(index = 15))]
 	InvalidOpcode(Opcode),
 
@@ -125,6 +130,39 @@ pub enum ExitException {
 	/// https://eips.ethereum.org/EIPS/eip-2681
 	#[cfg_attr(feature = "scale", codec(index = 14))]
 	MaxNonce,
+	/// Transaction gas limit above the cap of
+	/// [EIP-7825](https://eips.ethereum.org/EIPS/eip-7825).
+	#[cfg_attr(feature = "scale", codec(index = 19))]
+	TransactionGasLimitTooHigh,
+	/// Blob transaction without blobs, or with more blobs than fit in a
+	/// block, which holds none before Cancun.
+	#[cfg_attr(feature = "scale", codec(index = 20))]
+	InvalidBlobCount,
+	/// `AUTHCALL` without an account authorized by `AUTH`, see
+	/// [EIP-3074](https://eips.ethereum.org/EIPS/eip-3074).
+	#[cfg_attr(feature = "scale", codec(index = 21))]
+	Unauthorized,
+	/// EOF container failing validation when deployed or executed, see
+	/// [eof::validate](crate::eof::validate).
+	#[cfg_attr(feature = "scale", codec(index = 22))]
+	InvalidContainer,
+
+	/// Memory expansion beyond what can be served regardless of gas: past
+	/// `usize`, past the memory limit or past the total memory cap of all
+	/// frames.
+	#[cfg_attr(feature = "scale", codec(index = 16))]
+	MemoryLimit,
+
+	/// A state-modifying opcode was executed in a static context.
+	#[cfg_attr(feature = "scale", codec(index = 17))]
+	StaticModeViolation {
+		/// The offending opcode.
+		opcode: Opcode,
+		/// Address of the executing contract.
+		address: H160,
+		/// Call depth of the executing frame.
+		depth: u32,
+	},
 
 	/// Other normal errors.
 	#[cfg_attr(feature = "scale", codec(index = 13))]
@@ -1,6 +1,6 @@
 //! Call and create trap handler.
 
-use alloc::vec::Vec;
+use alloc::{sync::Arc, vec::Vec};
 use core::{
 	cmp::{max, min},
 	convert::Infallible,
@@ -30,10 +30,15 @@ pub trait TrapConsume<T> {
 pub enum CallCreateTrap {
 	Create,
 	Create2,
+	/// `EOFCREATE` of the given subcontainer.
+	EofCreate {
+		container: usize,
+	},
 	Call,
 	CallCode,
 	DelegateCall,
 	StaticCall,
+	AuthCall,
 }
 
 impl TrapConstruct<CallCreateTrap> for CallCreateTrap {
@@ -74,6 +79,9 @@ impl CallCreateTrapData {
 		match opcode {
 			CallCreateTrap::Create => Ok(Self::Create(CreateTrapData::new_create_from(machine)?)),
 			CallCreateTrap::Create2 => Ok(Self::Create(CreateTrapData::new_create2_from(machine)?)),
+			CallCreateTrap::EofCreate { container } => Ok(Self::Create(
+				CreateTrapData::new_eofcreate_from(machine, container)?,
+			)),
 			CallCreateTrap::Call => Ok(Self::Call(CallTrapData::new_from(
 				CallScheme::Call,
 				machine,
@@ -90,6 +98,10 @@ impl CallCreateTrapData {
 				CallScheme::StaticCall,
 				machine,
 			)?)),
+			CallCreateTrap::AuthCall => Ok(Self::Call(CallTrapData::new_from(
+				CallScheme::AuthCall,
+				machine,
+			)?)),
 		}
 	}
 
@@ -103,6 +115,7 @@ impl CallCreateTrapData {
 
 /// Call scheme.
 #[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CallScheme {
 	/// `CALL`
 	Call,
@@ -112,13 +125,16 @@ pub enum CallScheme {
 	DelegateCall,
 	/// `STATICCALL`
 	StaticCall,
+	/// `AUTHCALL`, see [EIP-3074](https://eips.ethereum.org/EIPS/eip-3074).
+	AuthCall,
 }
 
 #[derive(Clone, Debug)]
 pub struct CallTrapData {
+	pub scheme: CallScheme,
 	pub target: H160,
 	pub transfer: Option<Transfer>,
-	pub input: Vec<u8>,
+	pub input: Arc<Vec<u8>>,
 	pub gas: U256,
 	pub is_static: bool,
 	pub out_offset: U256,
@@ -162,31 +178,46 @@ impl CallTrapData {
 
 		memory.resize_end(max(in_end, out_end))?;
 
-		let input = in_offset_len
-			.map(|(in_offset, in_len)| memory.get(in_offset, in_len))
-			.unwrap_or(Vec::new());
+		let input = Arc::new(
+			in_offset_len
+				.map(|(in_offset, in_len)| memory.get(in_offset, in_len))
+				.unwrap_or(Vec::new()),
+		);
+
+		let caller = if scheme == CallScheme::AuthCall {
+			state
+				.as_ref()
+				.context
+				.authorized
+				.ok_or(ExitException::Unauthorized)?
+		} else {
+			state.as_ref().context.address
+		};
 
 		let context = match scheme {
-			CallScheme::Call | CallScheme::StaticCall => Context {
+			CallScheme::Call | CallScheme::StaticCall | CallScheme::AuthCall => Context {
 				address: (*to).into(),
-				caller: state.as_ref().context.address,
+				caller,
 				apparent_value: value,
+				authorized: None,
 			},
 			CallScheme::CallCode => Context {
 				address: state.as_ref().context.address,
-				caller: state.as_ref().context.address,
+				caller,
 				apparent_value: value,
+				authorized: None,
 			},
 			CallScheme::DelegateCall => Context {
 				address: state.as_ref().context.address,
 				caller: state.as_ref().context.caller,
 				apparent_value: state.as_ref().context.apparent_value,
+				authorized: None,
 			},
 		};
 
-		let transfer = if scheme == CallScheme::Call {
+		let transfer = if scheme == CallScheme::Call || scheme == CallScheme::AuthCall {
 			Some(Transfer {
-				source: state.as_ref().context.address,
+				source: caller,
 				target: (*to).into(),
 				value,
 			})
@@ -205,6 +236,7 @@ impl CallTrapData {
 		Ok((
 			(),
 			Self {
+				scheme,
 				target: (*to).into(),
 				transfer,
 				input,
@@ -226,8 +258,8 @@ impl CallTrapData {
 		let state = &mut machine.state;
 
 		match scheme {
-			CallScheme::Call | CallScheme::CallCode => stack.perform_pop7_push0(
-				|gas, to, value, in_offset, in_len, out_offset, out_len| {
+			CallScheme::Call | CallScheme::CallCode | CallScheme::AuthCall => stack
+				.perform_pop7_push0(|gas, to, value, in_offset, in_len, out_offset, out_len| {
 					Self::new_from_params(
 						scheme,
 						memory,
@@ -240,8 +272,7 @@ impl CallTrapData {
 						out_offset,
 						out_len,
 					)
-				},
-			),
+				}),
 			CallScheme::DelegateCall | CallScheme::StaticCall => {
 				stack.perform_pop6_push0(|gas, to, in_offset, in_len, out_offset, out_len| {
 					Self::new_from_params(
@@ -332,6 +363,7 @@ impl CallTrapData {
 
 /// Create scheme.
 #[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CreateScheme {
 	/// Legacy create scheme of `CREATE`.
 	Legacy {
@@ -347,6 +379,14 @@ pub enum CreateScheme {
 		/// Salt.
 		salt: H256,
 	},
+	/// Create scheme of `EOFCREATE`, see
+	/// [EIP-7620](https://eips.ethereum.org/EIPS/eip-7620).
+	EofCreate {
+		/// Caller of the create call.
+		caller: H160,
+		/// Salt.
+		salt: H256,
+	},
 }
 
 impl CreateScheme {
@@ -364,6 +404,13 @@ impl CreateScheme {
 				hasher.update(&code_hash[..]);
 				H256::from_slice(hasher.finalize().as_slice()).into()
 			}
+			Self::EofCreate { caller, salt } => {
+				let mut hasher = Keccak256::new();
+				hasher.update([0xff]);
+				hasher.update(H256::from(*caller));
+				hasher.update(&salt[..]);
+				H256::from_slice(hasher.finalize().as_slice()).into()
+			}
 			Self::Legacy { caller } => {
 				let nonce = handler.nonce(*caller);
 				let mut stream = rlp::RlpStream::new_list(2);
@@ -378,6 +425,7 @@ impl CreateScheme {
 	pub const fn caller(&self) -> H160 {
 		match self {
 			Self::Create2 { caller, .. } => *caller,
+			Self::EofCreate { caller, .. } => *caller,
 			Self::Legacy { caller } => *caller,
 		}
 	}
@@ -388,6 +436,8 @@ pub struct CreateTrapData {
 	pub scheme: CreateScheme,
 	pub value: U256,
 	pub code: Vec<u8>,
+	/// Input data of the initcode, only used by `EOFCREATE`.
+	pub input: Vec<u8>,
 }
 
 impl CreateTrapData {
@@ -425,6 +475,7 @@ impl CreateTrapData {
 					scheme,
 					value,
 					code,
+					input: Vec::new(),
 				},
 			))
 		})
@@ -468,6 +519,54 @@ impl CreateTrapData {
 					scheme,
 					value,
 					code,
+					input: Vec::new(),
+				},
+			))
+		})
+	}
+
+	pub fn new_eofcreate_from<S: AsRef<RuntimeState> + AsMut<RuntimeState>>(
+		machine: &mut Machine<S>,
+		container: usize,
+	) -> Result<Self, ExitError> {
+		let code = match &machine.eof {
+			Some(layout) => layout
+				.container_sections
+				.get(container)
+				.map(|range| machine.code[range.clone()].to_vec())
+				.ok_or(ExitException::InvalidContainer)?,
+			None => return Err(ExitException::InvalidContainer.into()),
+		};
+
+		let stack = &mut machine.stack;
+		let memory = &mut machine.memory;
+		let state = &mut machine.state;
+
+		stack.perform_pop4_push0(|value, salt, input_offset, input_len| {
+			let value = h256_to_u256(*value);
+			let input_offset = h256_to_u256(*input_offset);
+			let input_len = h256_to_u256(*input_len);
+
+			let input = if input_len == U256::zero() {
+				Vec::new()
+			} else {
+				memory.get(u256_to_usize(input_offset)?, u256_to_usize(input_len)?)
+			};
+
+			let scheme = CreateScheme::EofCreate {
+				caller: state.as_ref().context.address,
+				salt: *salt,
+			};
+
+			state.as_mut().retbuf = Vec::new();
+
+			Ok((
+				(),
+				Self {
+					scheme,
+					value,
+					code,
+					input,
 				},
 			))
 		})
@@ -8,7 +8,7 @@ use crate::{
 	eval::*,
 	machine::Machine,
 	opcode::Opcode,
-	runtime::{GasState, RuntimeBackend, RuntimeEnvironment, RuntimeState},
+	runtime::{GasState, RuntimeBackend, RuntimeBaseBackend, RuntimeEnvironment, RuntimeState},
 };
 
 pub trait EtableSet {
@@ -188,6 +188,22 @@ impl<S, H, Tr> Etable<S, H, Tr> {
 		table[Opcode::JUMPDEST.as_usize()] = eval_jumpdest as _;
 		table[Opcode::MCOPY.as_usize()] = eval_mcopy as _;
 
+		table[Opcode::DATALOAD.as_usize()] = eval_dataload as _;
+		table[Opcode::DATALOADN.as_usize()] = eval_dataloadn as _;
+		table[Opcode::DATASIZE.as_usize()] = eval_datasize as _;
+		table[Opcode::DATACOPY.as_usize()] = eval_datacopy as _;
+
+		table[Opcode::RJUMP.as_usize()] = eval_rjump as _;
+		table[Opcode::RJUMPI.as_usize()] = eval_rjumpi as _;
+		table[Opcode::RJUMPV.as_usize()] = eval_rjumpv as _;
+		table[Opcode::CALLF.as_usize()] = eval_callf as _;
+		table[Opcode::RETF.as_usize()] = eval_retf as _;
+		table[Opcode::JUMPF.as_usize()] = eval_jumpf as _;
+		table[Opcode::DUPN.as_usize()] = eval_dupn as _;
+		table[Opcode::SWAPN.as_usize()] = eval_swapn as _;
+		table[Opcode::EXCHANGE.as_usize()] = eval_exchange as _;
+		table[Opcode::RETURNCONTRACT.as_usize()] = eval_returncontract as _;
+
 		table[Opcode::PUSH0.as_usize()] = eval_push0 as _;
 		table[Opcode::PUSH1.as_usize()] = eval_push1 as _;
 		table[Opcode::PUSH2.as_usize()] = eval_push2 as _;
@@ -266,13 +282,18 @@ impl<S, H, Tr> Etable<S, H, Tr> {
 	}
 }
 
-impl<S, H: RuntimeEnvironment + RuntimeBackend, Tr: TrapConstruct<CallCreateTrap>> Etable<S, H, Tr>
+impl<S, H: RuntimeEnvironment + RuntimeBaseBackend, Tr: TrapConstruct<CallCreateTrap>>
+	Etable<S, H, Tr>
 where
 	S: AsRef<RuntimeState> + GasState,
 {
-	/// Runtime Etable.
+	/// Runtime Etable without the opcodes modifying state, for handlers that
+	/// can only read it, such as analyzers and gas simulators.
+	///
+	/// `SSTORE`, `TSTORE`, `LOG*` and `SUICIDE` are unknown opcodes. Calls and
+	/// creates still trap.
 	#[must_use]
-	pub const fn runtime() -> Self {
+	pub const fn read_only() -> Self {
 		let mut table = Self::core();
 
 		table.0[Opcode::SHA3.as_usize()] = eval_sha3 as _;
@@ -299,20 +320,14 @@ where
 		table.0[Opcode::CHAINID.as_usize()] = eval_chainid as _;
 		table.0[Opcode::SELFBALANCE.as_usize()] = eval_selfbalance as _;
 		table.0[Opcode::BASEFEE.as_usize()] = eval_basefee as _;
+		table.0[Opcode::BLOBHASH.as_usize()] = eval_blobhash as _;
+		table.0[Opcode::BLOBBASEFEE.as_usize()] = eval_blobbasefee as _;
 
 		table.0[Opcode::SLOAD.as_usize()] = eval_sload as _;
-		table.0[Opcode::SSTORE.as_usize()] = eval_sstore as _;
 
 		table.0[Opcode::GAS.as_usize()] = eval_gas as _;
 
 		table.0[Opcode::TLOAD.as_usize()] = eval_tload as _;
-		table.0[Opcode::TSTORE.as_usize()] = eval_tstore as _;
-
-		table.0[Opcode::LOG0.as_usize()] = eval_log0 as _;
-		table.0[Opcode::LOG1.as_usize()] = eval_log1 as _;
-		table.0[Opcode::LOG2.as_usize()] = eval_log2 as _;
-		table.0[Opcode::LOG3.as_usize()] = eval_log3 as _;
-		table.0[Opcode::LOG4.as_usize()] = eval_log4 as _;
 
 		table.0[Opcode::CREATE.as_usize()] = eval_call_create_trap as _;
 		table.0[Opcode::CALL.as_usize()] = eval_call_create_trap as _;
@@ -320,11 +335,37 @@ where
 
 		table.0[Opcode::DELEGATECALL.as_usize()] = eval_call_create_trap as _;
 		table.0[Opcode::CREATE2.as_usize()] = eval_call_create_trap as _;
+		table.0[Opcode::EOFCREATE.as_usize()] = eval_call_create_trap as _;
 
 		table.0[Opcode::STATICCALL.as_usize()] = eval_call_create_trap as _;
 
+		table
+	}
+}
+
+impl<S, H: RuntimeEnvironment + RuntimeBackend, Tr: TrapConstruct<CallCreateTrap>> Etable<S, H, Tr>
+where
+	S: AsRef<RuntimeState> + AsMut<RuntimeState> + GasState,
+{
+	/// Runtime Etable.
+	#[must_use]
+	pub const fn runtime() -> Self {
+		let mut table = Self::read_only();
+
+		table.0[Opcode::SSTORE.as_usize()] = eval_sstore as _;
+		table.0[Opcode::TSTORE.as_usize()] = eval_tstore as _;
+
+		table.0[Opcode::LOG0.as_usize()] = eval_log0 as _;
+		table.0[Opcode::LOG1.as_usize()] = eval_log1 as _;
+		table.0[Opcode::LOG2.as_usize()] = eval_log2 as _;
+		table.0[Opcode::LOG3.as_usize()] = eval_log3 as _;
+		table.0[Opcode::LOG4.as_usize()] = eval_log4 as _;
+
 		table.0[Opcode::SUICIDE.as_usize()] = eval_suicide as _;
 
+		table.0[Opcode::AUTH.as_usize()] = eval_auth as _;
+		table.0[Opcode::AUTHCALL.as_usize()] = eval_call_create_trap as _;
+
 		table
 	}
 }
@@ -336,5 +377,8 @@ pub enum Control<Trap> {
 	ContinueN(usize),
 	Exit(ExitResult),
 	Jump(usize),
+	/// Jump to a destination validated before execution, as relative jumps
+	/// of EOF code.
+	StaticJump(usize),
 	Trap(Trap),
 }
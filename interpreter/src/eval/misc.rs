@@ -1,11 +1,14 @@
+use alloc::vec::Vec;
 use core::cmp::{max, min};
 
 use primitive_types::{H256, U256};
 
 use crate::{
+	eof,
 	error::{ExitError, ExitException, ExitFatal, ExitSucceed},
 	etable::Control,
-	machine::Machine,
+	machine::{Machine, RETURN_STACK_LIMIT},
+	opcode::Opcode,
 	utils::u256_to_h256,
 };
 
@@ -161,6 +164,180 @@ pub fn jumpi<S, Tr>(state: &mut Machine<S>) -> Control<Tr> {
 	}
 }
 
+/// Destination of a relative jump of `offset` from the end of the
+/// instruction at `position`, with an immediate of `len` bytes.
+fn relative_destination<S>(
+	state: &Machine<S>,
+	position: usize,
+	len: usize,
+	offset: usize,
+) -> usize {
+	let offset = i16::from_be_bytes([state.code[offset], state.code[offset + 1]]);
+	(position + 1 + len).wrapping_add(offset as isize as usize)
+}
+
+/// Word of `data` at `index`, padded with zeros.
+fn load_word(data: &[u8], index: U256) -> H256 {
+	let mut load = [0u8; 32];
+	if index < U256::from(data.len()) {
+		let index = index.as_usize();
+		let len = min(32, data.len() - index);
+		load[..len].copy_from_slice(&data[index..(index + len)]);
+	}
+	H256::from(load)
+}
+
+#[inline]
+pub fn dataload<S, Tr>(state: &mut Machine<S>, opcode: Opcode) -> Control<Tr> {
+	let data = match &state.eof {
+		Some(layout) => layout.data.clone(),
+		None => return Control::Exit(ExitException::InvalidOpcode(opcode).into()),
+	};
+	pop_u256!(state, index);
+	let value = load_word(&state.code[data], index);
+	push!(state, value);
+	Control::Continue
+}
+
+#[inline]
+pub fn dataloadn<S, Tr>(state: &mut Machine<S>, opcode: Opcode, position: usize) -> Control<Tr> {
+	let data = match &state.eof {
+		Some(layout) => layout.data.clone(),
+		None => return Control::Exit(ExitException::InvalidOpcode(opcode).into()),
+	};
+	let index = u16::from_be_bytes([state.code[position + 1], state.code[position + 2]]);
+	let value = load_word(&state.code[data], U256::from(index));
+	push!(state, value);
+	Control::ContinueN(3)
+}
+
+#[inline]
+pub fn datasize<S, Tr>(state: &mut Machine<S>, opcode: Opcode) -> Control<Tr> {
+	let data = match &state.eof {
+		Some(layout) => layout.data.clone(),
+		None => return Control::Exit(ExitException::InvalidOpcode(opcode).into()),
+	};
+	push_u256!(state, U256::from(data.len()));
+	Control::Continue
+}
+
+#[inline]
+pub fn datacopy<S, Tr>(state: &mut Machine<S>, opcode: Opcode) -> Control<Tr> {
+	let data = match &state.eof {
+		Some(layout) => layout.data.clone(),
+		None => return Control::Exit(ExitException::InvalidOpcode(opcode).into()),
+	};
+	pop_u256!(state, memory_offset, data_offset, len);
+
+	try_or_fail!(state.memory.resize_offset(memory_offset, len));
+	if len == U256::zero() {
+		return Control::Continue;
+	}
+
+	match state
+		.memory
+		.copy_large(memory_offset, data_offset, len, &state.code[data])
+	{
+		Ok(()) => Control::Continue,
+		Err(e) => Control::Exit(e.into()),
+	}
+}
+
+#[inline]
+pub fn rjump<S, Tr>(state: &mut Machine<S>, opcode: Opcode, position: usize) -> Control<Tr> {
+	if state.eof.is_none() {
+		return Control::Exit(ExitException::InvalidOpcode(opcode).into());
+	}
+
+	Control::StaticJump(relative_destination(state, position, 2, position + 1))
+}
+
+#[inline]
+pub fn rjumpi<S, Tr>(state: &mut Machine<S>, opcode: Opcode, position: usize) -> Control<Tr> {
+	if state.eof.is_none() {
+		return Control::Exit(ExitException::InvalidOpcode(opcode).into());
+	}
+	pop!(state, value);
+
+	if value == H256::zero() {
+		Control::ContinueN(3)
+	} else {
+		Control::StaticJump(relative_destination(state, position, 2, position + 1))
+	}
+}
+
+#[inline]
+pub fn rjumpv<S, Tr>(state: &mut Machine<S>, opcode: Opcode, position: usize) -> Control<Tr> {
+	if state.eof.is_none() {
+		return Control::Exit(ExitException::InvalidOpcode(opcode).into());
+	}
+	pop_u256!(state, case);
+
+	let max_index = state.code[position + 1] as usize;
+	let len = 1 + (max_index + 1) * 2;
+	if case > U256::from(max_index) {
+		Control::ContinueN(1 + len)
+	} else {
+		let offset = position + 2 + case.as_usize() * 2;
+		Control::StaticJump(relative_destination(state, position, len, offset))
+	}
+}
+
+/// Start of the code section called by the instruction at `position`, after
+/// checking that its maximum stack height fits in the stack.
+fn function_start<S>(state: &Machine<S>, position: usize) -> Result<usize, ExitError> {
+	let layout = state
+		.eof
+		.as_ref()
+		.ok_or(ExitException::InvalidOpcode(Opcode(state.code[position])))?;
+	let index = u16::from_be_bytes([state.code[position + 1], state.code[position + 2]]) as usize;
+	let ty = &layout.types[index];
+
+	if state.stack.len() + ty.max_stack_height as usize - ty.inputs as usize > state.stack.limit() {
+		return Err(ExitException::StackOverflow.into());
+	}
+
+	Ok(layout.code_sections[index].start)
+}
+
+#[inline]
+pub fn callf<S, Tr>(state: &mut Machine<S>, opcode: Opcode, position: usize) -> Control<Tr> {
+	if state.eof.is_none() {
+		return Control::Exit(ExitException::InvalidOpcode(opcode).into());
+	}
+	if state.return_stack.len() >= RETURN_STACK_LIMIT {
+		return Control::Exit(ExitException::CallTooDeep.into());
+	}
+
+	match function_start(state, position) {
+		Ok(start) => {
+			state.return_stack.push(position + 3);
+			Control::StaticJump(start)
+		}
+		Err(e) => Control::Exit(e.into()),
+	}
+}
+
+#[inline]
+pub fn retf<S, Tr>(state: &mut Machine<S>, opcode: Opcode) -> Control<Tr> {
+	match state.return_stack.pop() {
+		Some(position) if state.eof.is_some() => Control::StaticJump(position),
+		_ => Control::Exit(ExitException::InvalidOpcode(opcode).into()),
+	}
+}
+
+#[inline]
+pub fn jumpf<S, Tr>(state: &mut Machine<S>, opcode: Opcode, position: usize) -> Control<Tr> {
+	if state.eof.is_none() {
+		return Control::Exit(ExitException::InvalidOpcode(opcode).into());
+	}
+
+	match function_start(state, position) {
+		Ok(start) => Control::StaticJump(start),
+		Err(e) => Control::Exit(e.into()),
+	}
+}
+
 #[inline]
 pub fn pc<S, Tr>(state: &mut Machine<S>, position: usize) -> Control<Tr> {
 	push_u256!(state, U256::from(position));
@@ -197,25 +374,70 @@ pub fn dup<S, Tr>(state: &mut Machine<S>, n: usize) -> Control<Tr> {
 
 #[inline]
 pub fn swap<S, Tr>(state: &mut Machine<S>, n: usize) -> Control<Tr> {
-	let val1 = match state.stack.peek(0) {
+	exchange_items(state, 0, n)
+}
+
+/// Swap the stack items at indexes `a` and `b` from the top.
+#[inline]
+fn exchange_items<S, Tr>(state: &mut Machine<S>, a: usize, b: usize) -> Control<Tr> {
+	let val1 = match state.stack.peek(a) {
 		Ok(value) => value,
 		Err(e) => return Control::Exit(e.into()),
 	};
-	let val2 = match state.stack.peek(n) {
+	let val2 = match state.stack.peek(b) {
 		Ok(value) => value,
 		Err(e) => return Control::Exit(e.into()),
 	};
-	match state.stack.set(0, val2) {
+	match state.stack.set(a, val2) {
 		Ok(()) => (),
 		Err(e) => return Control::Exit(e.into()),
 	}
-	match state.stack.set(n, val1) {
+	match state.stack.set(b, val1) {
 		Ok(()) => (),
 		Err(e) => return Control::Exit(e.into()),
 	}
 	Control::Continue
 }
 
+#[inline]
+pub fn dupn<S, Tr>(state: &mut Machine<S>, opcode: Opcode, position: usize) -> Control<Tr> {
+	if state.eof.is_none() {
+		return Control::Exit(ExitException::InvalidOpcode(opcode).into());
+	}
+
+	match dup(state, state.code[position + 1] as usize + 1) {
+		Control::Continue => Control::ContinueN(2),
+		control => control,
+	}
+}
+
+#[inline]
+pub fn swapn<S, Tr>(state: &mut Machine<S>, opcode: Opcode, position: usize) -> Control<Tr> {
+	if state.eof.is_none() {
+		return Control::Exit(ExitException::InvalidOpcode(opcode).into());
+	}
+
+	match exchange_items(state, 0, state.code[position + 1] as usize + 1) {
+		Control::Continue => Control::ContinueN(2),
+		control => control,
+	}
+}
+
+#[inline]
+pub fn exchange<S, Tr>(state: &mut Machine<S>, opcode: Opcode, position: usize) -> Control<Tr> {
+	if state.eof.is_none() {
+		return Control::Exit(ExitException::InvalidOpcode(opcode).into());
+	}
+
+	let immediate = state.code[position + 1] as usize;
+	let n = (immediate >> 4) + 1;
+	let m = (immediate & 0x0f) + 1;
+	match exchange_items(state, n, n + m) {
+		Control::Continue => Control::ContinueN(2),
+		control => control,
+	}
+}
+
 #[inline]
 pub fn ret<S, Tr>(state: &mut Machine<S>) -> Control<Tr> {
 	pop_u256!(state, start, len);
@@ -225,6 +447,33 @@ pub fn ret<S, Tr>(state: &mut Machine<S>) -> Control<Tr> {
 	Control::Exit(ExitSucceed::Returned.into())
 }
 
+#[inline]
+pub fn returncontract<S, Tr>(
+	state: &mut Machine<S>,
+	opcode: Opcode,
+	position: usize,
+) -> Control<Tr> {
+	let container = match &state.eof {
+		Some(layout) => layout.container_sections[state.code[position + 1] as usize].clone(),
+		None => return Control::Exit(ExitException::InvalidOpcode(opcode).into()),
+	};
+	pop_u256!(state, start, len);
+	try_or_fail!(state.memory.resize_offset(start, len));
+
+	let aux = if len == U256::zero() {
+		Vec::new()
+	} else {
+		state.memory.get(start.as_usize(), len.as_usize())
+	};
+	match eof::append_data(&state.code[container], &aux) {
+		Ok(deployed) => {
+			state.retval = deployed;
+			Control::Exit(ExitSucceed::Returned.into())
+		}
+		Err(_) => Control::Exit(ExitException::InvalidContainer.into()),
+	}
+}
+
 #[inline]
 pub fn revert<S, Tr>(state: &mut Machine<S>) -> Control<Tr> {
 	pop_u256!(state, start, len);
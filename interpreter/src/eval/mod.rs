@@ -14,7 +14,7 @@ use crate::{
 	etable::Control,
 	machine::Machine,
 	opcode::Opcode,
-	runtime::{GasState, RuntimeBackend, RuntimeEnvironment, RuntimeState},
+	runtime::{GasState, RuntimeBackend, RuntimeBaseBackend, RuntimeEnvironment, RuntimeState},
 };
 
 pub fn eval_pass<S, H, Tr>(
@@ -359,6 +359,132 @@ pub fn eval_jumpi<S, H, Tr>(
 	self::misc::jumpi(machine)
 }
 
+pub fn eval_dataload<S, H, Tr>(
+	machine: &mut Machine<S>,
+	_handle: &mut H,
+	opcode: Opcode,
+	_position: usize,
+) -> Control<Tr> {
+	self::misc::dataload(machine, opcode)
+}
+
+pub fn eval_dataloadn<S, H, Tr>(
+	machine: &mut Machine<S>,
+	_handle: &mut H,
+	opcode: Opcode,
+	position: usize,
+) -> Control<Tr> {
+	self::misc::dataloadn(machine, opcode, position)
+}
+
+pub fn eval_datasize<S, H, Tr>(
+	machine: &mut Machine<S>,
+	_handle: &mut H,
+	opcode: Opcode,
+	_position: usize,
+) -> Control<Tr> {
+	self::misc::datasize(machine, opcode)
+}
+
+pub fn eval_datacopy<S, H, Tr>(
+	machine: &mut Machine<S>,
+	_handle: &mut H,
+	opcode: Opcode,
+	_position: usize,
+) -> Control<Tr> {
+	self::misc::datacopy(machine, opcode)
+}
+
+pub fn eval_rjump<S, H, Tr>(
+	machine: &mut Machine<S>,
+	_handle: &mut H,
+	opcode: Opcode,
+	position: usize,
+) -> Control<Tr> {
+	self::misc::rjump(machine, opcode, position)
+}
+
+pub fn eval_rjumpi<S, H, Tr>(
+	machine: &mut Machine<S>,
+	_handle: &mut H,
+	opcode: Opcode,
+	position: usize,
+) -> Control<Tr> {
+	self::misc::rjumpi(machine, opcode, position)
+}
+
+pub fn eval_rjumpv<S, H, Tr>(
+	machine: &mut Machine<S>,
+	_handle: &mut H,
+	opcode: Opcode,
+	position: usize,
+) -> Control<Tr> {
+	self::misc::rjumpv(machine, opcode, position)
+}
+
+pub fn eval_callf<S, H, Tr>(
+	machine: &mut Machine<S>,
+	_handle: &mut H,
+	opcode: Opcode,
+	position: usize,
+) -> Control<Tr> {
+	self::misc::callf(machine, opcode, position)
+}
+
+pub fn eval_retf<S, H, Tr>(
+	machine: &mut Machine<S>,
+	_handle: &mut H,
+	opcode: Opcode,
+	_position: usize,
+) -> Control<Tr> {
+	self::misc::retf(machine, opcode)
+}
+
+pub fn eval_jumpf<S, H, Tr>(
+	machine: &mut Machine<S>,
+	_handle: &mut H,
+	opcode: Opcode,
+	position: usize,
+) -> Control<Tr> {
+	self::misc::jumpf(machine, opcode, position)
+}
+
+pub fn eval_dupn<S, H, Tr>(
+	machine: &mut Machine<S>,
+	_handle: &mut H,
+	opcode: Opcode,
+	position: usize,
+) -> Control<Tr> {
+	self::misc::dupn(machine, opcode, position)
+}
+
+pub fn eval_swapn<S, H, Tr>(
+	machine: &mut Machine<S>,
+	_handle: &mut H,
+	opcode: Opcode,
+	position: usize,
+) -> Control<Tr> {
+	self::misc::swapn(machine, opcode, position)
+}
+
+pub fn eval_exchange<S, H, Tr>(
+	machine: &mut Machine<S>,
+	_handle: &mut H,
+	opcode: Opcode,
+	position: usize,
+) -> Control<Tr> {
+	self::misc::exchange(machine, opcode, position)
+}
+
+pub fn eval_returncontract<S, H, Tr>(
+	machine: &mut Machine<S>,
+	_handle: &mut H,
+	opcode: Opcode,
+	position: usize,
+) -> Control<Tr> {
+	self::misc::returncontract(machine, opcode, position)
+}
+
 pub fn eval_pc<S, H, Tr>(
 	machine: &mut Machine<S>,
 	_handle: &mut H,
@@ -485,7 +611,7 @@ pub fn eval_unknown<S, H, Tr>(
 	Control::Exit(ExitException::InvalidOpcode(opcode).into())
 }
 
-pub fn eval_sha3<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn eval_sha3<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	_handle: &mut H,
 	_opcode: Opcode,
@@ -494,7 +620,7 @@ pub fn eval_sha3<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend,
 	self::system::sha3(machine)
 }
 
-pub fn eval_address<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn eval_address<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	_handle: &mut H,
 	_opcode: Opcode,
@@ -503,7 +629,7 @@ pub fn eval_address<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBacke
 	self::system::address(machine)
 }
 
-pub fn eval_balance<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn eval_balance<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	handle: &mut H,
 	_opcode: Opcode,
@@ -512,7 +638,7 @@ pub fn eval_balance<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBacke
 	self::system::balance(machine, handle)
 }
 
-pub fn eval_selfbalance<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn eval_selfbalance<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	handle: &mut H,
 	_opcode: Opcode,
@@ -521,7 +647,7 @@ pub fn eval_selfbalance<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeB
 	self::system::selfbalance(machine, handle)
 }
 
-pub fn eval_origin<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn eval_origin<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	handle: &mut H,
 	_opcode: Opcode,
@@ -530,7 +656,7 @@ pub fn eval_origin<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBacken
 	self::system::origin(machine, handle)
 }
 
-pub fn eval_caller<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn eval_caller<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	_handle: &mut H,
 	_opcode: Opcode,
@@ -539,7 +665,7 @@ pub fn eval_caller<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBacken
 	self::system::caller(machine)
 }
 
-pub fn eval_callvalue<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn eval_callvalue<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	_handle: &mut H,
 	_opcode: Opcode,
@@ -548,7 +674,7 @@ pub fn eval_callvalue<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBac
 	self::system::callvalue(machine)
 }
 
-pub fn eval_gasprice<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn eval_gasprice<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	handle: &mut H,
 	_opcode: Opcode,
@@ -557,7 +683,7 @@ pub fn eval_gasprice<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBack
 	self::system::gasprice(machine, handle)
 }
 
-pub fn eval_extcodesize<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn eval_extcodesize<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	handle: &mut H,
 	_opcode: Opcode,
@@ -566,7 +692,7 @@ pub fn eval_extcodesize<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeB
 	self::system::extcodesize(machine, handle)
 }
 
-pub fn eval_extcodehash<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn eval_extcodehash<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	handle: &mut H,
 	_opcode: Opcode,
@@ -575,7 +701,7 @@ pub fn eval_extcodehash<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeB
 	self::system::extcodehash(machine, handle)
 }
 
-pub fn eval_extcodecopy<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn eval_extcodecopy<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	handle: &mut H,
 	_opcode: Opcode,
@@ -584,7 +710,11 @@ pub fn eval_extcodecopy<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeB
 	self::system::extcodecopy(machine, handle)
 }
 
-pub fn eval_returndatasize<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn eval_returndatasize<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBaseBackend,
+	Tr,
+>(
 	machine: &mut Machine<S>,
 	_handle: &mut H,
 	_opcode: Opcode,
@@ -593,7 +723,11 @@ pub fn eval_returndatasize<S: AsRef<RuntimeState>, H: RuntimeEnvironment + Runti
 	self::system::returndatasize(machine)
 }
 
-pub fn eval_returndatacopy<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn eval_returndatacopy<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBaseBackend,
+	Tr,
+>(
 	machine: &mut Machine<S>,
 	_handle: &mut H,
 	_opcode: Opcode,
@@ -602,7 +736,7 @@ pub fn eval_returndatacopy<S: AsRef<RuntimeState>, H: RuntimeEnvironment + Runti
 	self::system::returndatacopy(machine)
 }
 
-pub fn eval_blockhash<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn eval_blockhash<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	handle: &mut H,
 	_opcode: Opcode,
@@ -611,7 +745,7 @@ pub fn eval_blockhash<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBac
 	self::system::blockhash(machine, handle)
 }
 
-pub fn eval_coinbase<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn eval_coinbase<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	handle: &mut H,
 	_opcode: Opcode,
@@ -620,7 +754,7 @@ pub fn eval_coinbase<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBack
 	self::system::coinbase(machine, handle)
 }
 
-pub fn eval_timestamp<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn eval_timestamp<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	handle: &mut H,
 	_opcode: Opcode,
@@ -629,7 +763,7 @@ pub fn eval_timestamp<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBac
 	self::system::timestamp(machine, handle)
 }
 
-pub fn eval_number<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn eval_number<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	handle: &mut H,
 	_opcode: Opcode,
@@ -638,7 +772,7 @@ pub fn eval_number<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBacken
 	self::system::number(machine, handle)
 }
 
-pub fn eval_difficulty<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn eval_difficulty<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	handle: &mut H,
 	_opcode: Opcode,
@@ -647,7 +781,7 @@ pub fn eval_difficulty<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBa
 	self::system::prevrandao(machine, handle)
 }
 
-pub fn eval_gaslimit<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn eval_gaslimit<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	handle: &mut H,
 	_opcode: Opcode,
@@ -656,7 +790,7 @@ pub fn eval_gaslimit<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBack
 	self::system::gaslimit(machine, handle)
 }
 
-pub fn eval_sload<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn eval_sload<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	handle: &mut H,
 	_opcode: Opcode,
@@ -674,7 +808,7 @@ pub fn eval_sstore<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBacken
 	self::system::sstore(machine, handle)
 }
 
-pub fn eval_gas<S: GasState, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn eval_gas<S: GasState, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	handle: &mut H,
 	_opcode: Opcode,
@@ -683,7 +817,7 @@ pub fn eval_gas<S: GasState, H: RuntimeEnvironment + RuntimeBackend, Tr>(
 	self::system::gas(machine, handle)
 }
 
-pub fn eval_tload<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn eval_tload<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	handle: &mut H,
 	_opcode: Opcode,
@@ -727,7 +861,20 @@ pub fn eval_suicide<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBacke
 	self::system::suicide(machine, handle)
 }
 
-pub fn eval_chainid<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn eval_auth<
+	S: AsRef<RuntimeState> + AsMut<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+>(
+	machine: &mut Machine<S>,
+	handle: &mut H,
+	_opcode: Opcode,
+	_position: usize,
+) -> Control<Tr> {
+	self::system::auth(machine, handle)
+}
+
+pub fn eval_chainid<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	handle: &mut H,
 	_opcode: Opcode,
@@ -736,7 +883,7 @@ pub fn eval_chainid<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBacke
 	self::system::chainid(machine, handle)
 }
 
-pub fn eval_basefee<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn eval_basefee<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	handle: &mut H,
 	_opcode: Opcode,
@@ -745,19 +892,41 @@ pub fn eval_basefee<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBacke
 	self::system::basefee(machine, handle)
 }
 
+pub fn eval_blobhash<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
+	machine: &mut Machine<S>,
+	handle: &mut H,
+	_opcode: Opcode,
+	_position: usize,
+) -> Control<Tr> {
+	self::system::blobhash(machine, handle)
+}
+
+pub fn eval_blobbasefee<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
+	machine: &mut Machine<S>,
+	handle: &mut H,
+	_opcode: Opcode,
+	_position: usize,
+) -> Control<Tr> {
+	self::system::blobbasefee(machine, handle)
+}
+
 pub fn eval_call_create_trap<S, H, Tr: TrapConstruct<CallCreateTrap>>(
-	_machine: &mut Machine<S>,
+	machine: &mut Machine<S>,
 	_handle: &mut H,
 	opcode: Opcode,
-	_position: usize,
+	position: usize,
 ) -> Control<Tr> {
 	let trap = match opcode {
 		Opcode::CREATE => CallCreateTrap::Create,
 		Opcode::CREATE2 => CallCreateTrap::Create2,
+		Opcode::EOFCREATE if machine.eof().is_some() => CallCreateTrap::EofCreate {
+			container: machine.code()[position + 1] as usize,
+		},
 		Opcode::CALL => CallCreateTrap::Call,
 		Opcode::CALLCODE => CallCreateTrap::CallCode,
 		Opcode::DELEGATECALL => CallCreateTrap::DelegateCall,
 		Opcode::STATICCALL => CallCreateTrap::StaticCall,
+		Opcode::AUTHCALL => CallCreateTrap::AuthCall,
 		_ => return Control::Exit(Err(ExitException::InvalidOpcode(opcode).into())),
 	};
 
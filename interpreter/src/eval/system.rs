@@ -1,13 +1,17 @@
 use alloc::vec::Vec;
+use core::cmp::min;
 
-use primitive_types::{H256, U256};
+use primitive_types::{H160, H256, U256};
 use sha3::{Digest, Keccak256};
 
 use crate::{
 	error::{ExitException, ExitFatal, ExitSucceed},
 	etable::Control,
 	machine::Machine,
-	runtime::{GasState, Log, RuntimeBackend, RuntimeEnvironment, RuntimeState, Transfer},
+	runtime::{
+		GasState, Log, RuntimeBackend, RuntimeBaseBackend, RuntimeEnvironment, RuntimeState,
+		SelfDestruct, Transfer,
+	},
 };
 
 pub fn sha3<S: AsRef<RuntimeState>, Tr>(machine: &mut Machine<S>) -> Control<Tr> {
@@ -29,7 +33,7 @@ pub fn sha3<S: AsRef<RuntimeState>, Tr>(machine: &mut Machine<S>) -> Control<Tr>
 	Control::Continue
 }
 
-pub fn chainid<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn chainid<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	handler: &H,
 ) -> Control<Tr> {
@@ -45,7 +49,7 @@ pub fn address<S: AsRef<RuntimeState>, Tr>(machine: &mut Machine<S>) -> Control<
 	Control::Continue
 }
 
-pub fn balance<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn balance<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	handler: &mut H,
 ) -> Control<Tr> {
@@ -55,7 +59,7 @@ pub fn balance<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, T
 	Control::Continue
 }
 
-pub fn selfbalance<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn selfbalance<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	handler: &H,
 ) -> Control<Tr> {
@@ -67,7 +71,7 @@ pub fn selfbalance<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBacken
 	Control::Continue
 }
 
-pub fn origin<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn origin<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	_handler: &H,
 ) -> Control<Tr> {
@@ -97,7 +101,7 @@ pub fn callvalue<S: AsRef<RuntimeState>, Tr>(machine: &mut Machine<S>) -> Contro
 	Control::Continue
 }
 
-pub fn gasprice<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn gasprice<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	_handler: &H,
 ) -> Control<Tr> {
@@ -113,7 +117,7 @@ pub fn gasprice<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend,
 	Control::Continue
 }
 
-pub fn basefee<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn basefee<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	handler: &H,
 ) -> Control<Tr> {
@@ -124,7 +128,28 @@ pub fn basefee<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, T
 	Control::Continue
 }
 
-pub fn extcodesize<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn blobhash<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
+	machine: &mut Machine<S>,
+	handler: &H,
+) -> Control<Tr> {
+	pop_u256!(machine, index);
+	push!(machine, handler.blob_hash(index));
+
+	Control::Continue
+}
+
+pub fn blobbasefee<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
+	machine: &mut Machine<S>,
+	handler: &H,
+) -> Control<Tr> {
+	let mut ret = H256::default();
+	handler.block_blob_base_fee().to_big_endian(&mut ret[..]);
+	push!(machine, ret);
+
+	Control::Continue
+}
+
+pub fn extcodesize<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	handler: &mut H,
 ) -> Control<Tr> {
@@ -135,7 +160,7 @@ pub fn extcodesize<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBacken
 	Control::Continue
 }
 
-pub fn extcodehash<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn extcodehash<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	handler: &mut H,
 ) -> Control<Tr> {
@@ -146,7 +171,7 @@ pub fn extcodehash<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBacken
 	Control::Continue
 }
 
-pub fn extcodecopy<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn extcodecopy<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	handler: &mut H,
 ) -> Control<Tr> {
@@ -154,10 +179,17 @@ pub fn extcodecopy<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBacken
 	pop_u256!(machine, memory_offset, code_offset, len);
 	try_or_fail!(machine.memory.resize_offset(memory_offset, len));
 
-	let code = handler.code(address.into());
+	// Only the copied range is read. Offsets past the addressable range read
+	// as zero, as in `copy_large`.
+	let code = match code_offset.checked_add(len) {
+		Some(end) if !len.is_zero() && end <= U256::from(usize::MAX) => {
+			handler.code_slice(address.into(), code_offset.as_usize(), len.as_usize())
+		}
+		_ => Vec::new(),
+	};
 	match machine
 		.memory
-		.copy_large(memory_offset, code_offset, len, &code)
+		.copy_large(memory_offset, U256::zero(), len, &code)
 	{
 		Ok(()) => (),
 		Err(e) => return Control::Exit(e.into()),
@@ -194,7 +226,7 @@ pub fn returndatacopy<S: AsRef<RuntimeState>, Tr>(machine: &mut Machine<S>) -> C
 	}
 }
 
-pub fn blockhash<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn blockhash<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	handler: &H,
 ) -> Control<Tr> {
@@ -204,7 +236,7 @@ pub fn blockhash<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend,
 	Control::Continue
 }
 
-pub fn coinbase<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn coinbase<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	handler: &H,
 ) -> Control<Tr> {
@@ -212,7 +244,7 @@ pub fn coinbase<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend,
 	Control::Continue
 }
 
-pub fn timestamp<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn timestamp<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	handler: &H,
 ) -> Control<Tr> {
@@ -220,7 +252,7 @@ pub fn timestamp<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend,
 	Control::Continue
 }
 
-pub fn number<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn number<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	handler: &H,
 ) -> Control<Tr> {
@@ -228,7 +260,7 @@ pub fn number<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr
 	Control::Continue
 }
 
-pub fn difficulty<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn difficulty<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	handler: &H,
 ) -> Control<Tr> {
@@ -236,19 +268,20 @@ pub fn difficulty<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend
 	Control::Continue
 }
 
-pub fn prevrandao<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn prevrandao<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	handler: &H,
 ) -> Control<Tr> {
-	if let Some(rand) = handler.block_randomness() {
-		push!(machine, rand);
-		Control::Continue
-	} else {
-		difficulty(machine, handler)
+	match handler.block_randomness() {
+		Some(rand) if machine.prevrandao => {
+			push!(machine, rand);
+			Control::Continue
+		}
+		_ => difficulty(machine, handler),
 	}
 }
 
-pub fn gaslimit<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn gaslimit<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	handler: &H,
 ) -> Control<Tr> {
@@ -256,7 +289,7 @@ pub fn gaslimit<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend,
 	Control::Continue
 }
 
-pub fn sload<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn sload<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	handler: &mut H,
 ) -> Control<Tr> {
@@ -279,7 +312,7 @@ pub fn sstore<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr
 	}
 }
 
-pub fn gas<S: GasState, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn gas<S: GasState, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	_handler: &H,
 ) -> Control<Tr> {
@@ -288,7 +321,7 @@ pub fn gas<S: GasState, H: RuntimeEnvironment + RuntimeBackend, Tr>(
 	Control::Continue
 }
 
-pub fn tload<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
+pub fn tload<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBaseBackend, Tr>(
 	machine: &mut Machine<S>,
 	handler: &mut H,
 ) -> Control<Tr> {
@@ -347,6 +380,63 @@ pub fn log<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
 	}
 }
 
+/// Magic prefix of the message signed for `AUTH`.
+const AUTH_MAGIC: u8 = 0x04;
+
+/// Length of the `AUTH` input: `yParity`, `r`, `s` and `commit`.
+const AUTH_INPUT_LEN: usize = 97;
+
+pub fn auth<
+	S: AsRef<RuntimeState> + AsMut<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+>(
+	machine: &mut Machine<S>,
+	handler: &H,
+) -> Control<Tr> {
+	pop!(machine, authority);
+	pop_u256!(machine, offset, len);
+	let authority: H160 = authority.into();
+
+	try_or_fail!(machine.memory.resize_offset(offset, len));
+	let mut input = [0u8; AUTH_INPUT_LEN];
+	if len != U256::zero() {
+		let offset = as_usize_or_fail!(offset);
+		let len = min(as_usize_or_fail!(len), AUTH_INPUT_LEN);
+
+		input[..len].copy_from_slice(&machine.memory.get(offset, len));
+	}
+
+	let y_parity = input[0];
+	let r = H256::from_slice(&input[1..33]);
+	let s = H256::from_slice(&input[33..65]);
+	let commit = &input[65..97];
+
+	let mut message = [0u8; 1 + 32 * 4];
+	message[0] = AUTH_MAGIC;
+	handler.chain_id().to_big_endian(&mut message[1..33]);
+	handler.nonce(authority).to_big_endian(&mut message[33..65]);
+	message[77..97].copy_from_slice(&machine.state.as_ref().context.address[..]);
+	message[97..].copy_from_slice(commit);
+	let message = H256::from_slice(Keccak256::digest(message).as_slice());
+
+	let authorized = y_parity <= 1
+		&& handler.code_size(authority) == U256::zero()
+		&& handler.recover_signer(message, y_parity == 1, r, s) == Some(authority);
+
+	machine.state.as_mut().context.authorized = if authorized { Some(authority) } else { None };
+	push_u256!(
+		machine,
+		if authorized {
+			U256::one()
+		} else {
+			U256::zero()
+		}
+	);
+
+	Control::Continue
+}
+
 pub fn suicide<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
 	machine: &mut Machine<S>,
 	handler: &mut H,
@@ -362,8 +452,15 @@ pub fn suicide<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, T
 			value: balance,
 		})?;
 
-		handler.mark_delete(address);
-		handler.reset_balance(address);
+		handler.record_selfdestruct(SelfDestruct {
+			address,
+			beneficiary: (*target).into(),
+			balance,
+		});
+		if handler.deletable(address) {
+			handler.mark_delete(address);
+			handler.reset_balance(address);
+		}
 
 		Ok(((), ()))
 	}) {
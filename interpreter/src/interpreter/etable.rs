@@ -2,10 +2,11 @@ use alloc::vec::Vec;
 use core::ops::{Deref, DerefMut};
 
 use crate::{
+	eof,
 	error::{Capture, ExitError, ExitException, ExitFatal, ExitResult, ExitSucceed},
 	etable::{Control, EtableSet},
 	interpreter::{valids::Valids, Interpreter, RunInterpreter, StepInterpreter},
-	machine::{Machine, Stack},
+	machine::{Machine, MachineDump, Stack},
 	opcode::Opcode,
 };
 
@@ -34,18 +35,41 @@ impl<'etable, ES> EtableInterpreter<'etable, ES>
 where
 	ES: EtableSet,
 {
-	/// Return a reference of the program counter.
+	/// Return the program counter, which is the position of the next opcode.
 	pub const fn position(&self) -> usize {
 		self.position
 	}
 
+	/// Whether the machine has exited, either explicitly or by running past
+	/// the end of the code. Further step will return error.
+	pub fn is_exited(&self) -> bool {
+		self.position >= self.code.len()
+	}
+
+	/// Immutable view of the inner machine, for rendering the execution state
+	/// between steps.
+	pub fn machine(&self) -> &Machine<ES::State> {
+		&self.machine
+	}
+
+	/// Render the execution state at the current position, see
+	/// [Machine::dump].
+	pub fn dump(&self) -> MachineDump<'_, ES::State> {
+		self.machine.dump(self.position)
+	}
+
 	pub fn new(machine: Machine<ES::State>, etable: &'etable ES) -> Self {
 		let valids = Valids::new(&machine.code[..]);
+		// EOF code starts at its first code section.
+		let position = machine
+			.eof
+			.as_ref()
+			.map_or(0, |layout| layout.code_sections[0].start);
 
 		Self {
 			machine,
 			valids,
-			position: 0,
+			position,
 			etable,
 		}
 	}
@@ -85,6 +109,34 @@ where
 	pub fn peek_opcode(&self) -> Option<Opcode> {
 		self.code.get(self.position).map(|opcode| Opcode(*opcode))
 	}
+
+	/// Assert the invariants that must hold after evaluating `opcode`, unless
+	/// the machine exited or trapped. Only a `PUSH*` truncated by the end of
+	/// the code can move the program counter past the virtual stop at the
+	/// end of the code.
+	#[cfg(feature = "invariants")]
+	fn assert_invariants(&self, opcode: Opcode) {
+		assert!(
+			self.stack.len() <= self.stack.limit(),
+			"{:?}: stack length {} above limit {}",
+			opcode,
+			self.stack.len(),
+			self.stack.limit(),
+		);
+		assert!(
+			self.position <= self.code.len() || opcode.is_push().is_some(),
+			"{:?}: program counter {} past code length {}",
+			opcode,
+			self.position,
+			self.code.len(),
+		);
+		assert!(
+			self.memory.effective_len().low_u32() % 32 == 0,
+			"{:?}: memory length {} not word-aligned",
+			opcode,
+			self.memory.effective_len(),
+		);
+	}
 }
 
 impl<'etable, ES: EtableSet> Interpreter for EtableInterpreter<'etable, ES> {
@@ -107,7 +159,13 @@ impl<'etable, ES: EtableSet> Interpreter for EtableInterpreter<'etable, ES> {
 			return;
 		}
 
-		self.position += 1;
+		// Instructions of EOF code trapping with an immediate, such as
+		// `EOFCREATE`, resume after it.
+		if self.machine.eof.is_some() {
+			self.position += 1 + eof::immediate_size(&self.code, self.position);
+		} else {
+			self.position += 1;
+		}
 	}
 }
 
@@ -164,9 +222,15 @@ where
 					return Err(Capture::Exit(ExitException::InvalidJump.into()));
 				}
 			}
+			Control::StaticJump(p) => {
+				self.position = p;
+			}
 			Control::Trap(opcode) => return Err(Capture::Trap(opcode)),
 		};
 
+		#[cfg(feature = "invariants")]
+		self.assert_invariants(opcode);
+
 		if self.position >= self.code.len() {
 			return Err(Capture::Exit(ExitSucceed::Stopped.into()));
 		}
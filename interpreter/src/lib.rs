@@ -6,6 +6,7 @@
 
 extern crate alloc;
 
+pub mod eof;
 pub mod error;
 pub mod etable;
 pub mod eval;
@@ -13,6 +14,8 @@ mod interpreter;
 pub mod machine;
 pub mod opcode;
 pub mod runtime;
+#[cfg(feature = "taint")]
+pub mod taint;
 pub mod utils;
 
 pub use self::interpreter::{EtableInterpreter, Interpreter, RunInterpreter, StepInterpreter};
@@ -0,0 +1,142 @@
+use core::{fmt, ops::Range};
+
+use primitive_types::U256;
+
+use crate::{
+	machine::{Machine, Memory, Stack},
+	opcode::Opcode,
+	runtime::GasState,
+};
+
+/// Number of bytes per line of a [MemoryHexdump].
+const HEXDUMP_LINE: usize = 32;
+
+/// Human-readable rendering of a machine at a given position, created by
+/// [Machine::dump]. Its `Display` and `Debug` output are the same, so that it
+/// can be used directly in panic messages and test assertions.
+pub struct MachineDump<'a, S> {
+	machine: &'a Machine<S>,
+	position: usize,
+}
+
+impl<S> Machine<S> {
+	/// Render the program counter, the instruction at `position`, the stack
+	/// from the top, the memory size and the gas left.
+	pub fn dump(&self, position: usize) -> MachineDump<'_, S> {
+		MachineDump {
+			machine: self,
+			position,
+		}
+	}
+}
+
+impl<'a, S: GasState> fmt::Display for MachineDump<'a, S> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let machine = self.machine;
+		let code = machine.code();
+
+		write!(f, "pc: {}, instruction: ", self.position)?;
+		match code.get(self.position) {
+			Some(byte) => {
+				let opcode = Opcode(*byte);
+				match opcode.name_with_prevrandao(machine.prevrandao) {
+					Some(name) => write!(f, "{}", name)?,
+					None => write!(f, "0x{:02x}", byte)?,
+				}
+				if let Some(len) = opcode.is_push() {
+					let start = self.position + 1;
+					let end = core::cmp::min(start + usize::from(len), code.len());
+					write!(f, " 0x")?;
+					for byte in &code[start..end] {
+						write!(f, "{:02x}", byte)?;
+					}
+				}
+			}
+			None => write!(f, "(end of code)")?,
+		}
+		writeln!(f)?;
+
+		writeln!(f, "gas: {}", machine.state.gas())?;
+		writeln!(f, "memory: {} bytes", machine.memory.effective_len())?;
+		write!(f, "{}", machine.stack.display_top(machine.stack.len()))
+	}
+}
+
+impl<'a, S: GasState> fmt::Debug for MachineDump<'a, S> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Display::fmt(self, f)
+	}
+}
+
+/// Hex dump of a memory range, created by [Memory::hexdump].
+pub struct MemoryHexdump<'a> {
+	memory: &'a Memory,
+	range: Range<usize>,
+}
+
+impl Memory {
+	/// Hex dump of the memory range, clamped to the effective length, with
+	/// 32 bytes per line, each prefixed by its offset.
+	pub fn hexdump(&self, range: Range<usize>) -> MemoryHexdump<'_> {
+		let len = if self.effective_len() > U256::from(usize::MAX) {
+			usize::MAX
+		} else {
+			self.effective_len().as_usize()
+		};
+		let end = core::cmp::min(range.end, len);
+		let start = core::cmp::min(range.start, end);
+
+		MemoryHexdump {
+			memory: self,
+			range: start..end,
+		}
+	}
+}
+
+impl<'a> fmt::Display for MemoryHexdump<'a> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let mut offset = self.range.start;
+		while offset < self.range.end {
+			if offset != self.range.start {
+				writeln!(f)?;
+			}
+
+			let len = core::cmp::min(HEXDUMP_LINE, self.range.end - offset);
+			write!(f, "{:08x}:", offset)?;
+			for byte in self.memory.get(offset, len) {
+				write!(f, " {:02x}", byte)?;
+			}
+			offset += len;
+		}
+
+		Ok(())
+	}
+}
+
+/// Top values of a stack, created by [Stack::display_top].
+pub struct StackTop<'a> {
+	stack: &'a Stack,
+	n: usize,
+}
+
+impl Stack {
+	/// Display the stack size and its `n` top values, one per line from the
+	/// top, each prefixed by its index.
+	pub fn display_top(&self, n: usize) -> StackTop<'_> {
+		StackTop { stack: self, n }
+	}
+}
+
+impl<'a> fmt::Display for StackTop<'a> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "stack: {} items, from the top", self.stack.len())?;
+		for (i, value) in self.stack.iter().take(self.n).enumerate() {
+			write!(f, "\n  {}: {:?}", i, value)?;
+		}
+		if self.n < self.stack.len() {
+			write!(f, "\n  ...")?;
+		}
+
+		Ok(())
+	}
+}
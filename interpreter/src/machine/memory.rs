@@ -7,6 +7,8 @@ use core::{
 
 use primitive_types::U256;
 
+#[cfg(feature = "arena")]
+use super::FrameArena;
 use crate::error::{ExitException, ExitFatal};
 
 /// A sequencial memory. It uses Rust's `Vec` for internal
@@ -16,6 +18,8 @@ pub struct Memory {
 	data: Vec<u8>,
 	effective_len: U256,
 	limit: usize,
+	#[cfg(feature = "arena")]
+	arena: Option<FrameArena>,
 }
 
 impl Memory {
@@ -26,9 +30,51 @@ impl Memory {
 			data: Vec::new(),
 			effective_len: U256::zero(),
 			limit,
+			#[cfg(feature = "arena")]
+			arena: None,
 		}
 	}
 
+	/// Create a new memory with the given limit, reusing a buffer, such as
+	/// one returned by [Memory::into_buffer]. The memory does not reallocate
+	/// while it grows within the capacity of the buffer, but return values
+	/// are still allocated separately.
+	#[must_use]
+	pub fn with_buffer(mut buffer: Vec<u8>, limit: usize) -> Self {
+		buffer.clear();
+		Self {
+			data: buffer,
+			effective_len: U256::zero(),
+			limit,
+			#[cfg(feature = "arena")]
+			arena: None,
+		}
+	}
+
+	/// Create a new memory with the given limit, whose buffer is taken from
+	/// the arena and released back to it on drop.
+	#[cfg(feature = "arena")]
+	#[must_use]
+	pub fn new_in(limit: usize, arena: &FrameArena) -> Self {
+		Self {
+			data: arena.take(),
+			effective_len: U256::zero(),
+			limit,
+			arena: Some(arena.clone()),
+		}
+	}
+
+	/// Consume the memory, returning its buffer for reuse. The buffer of a
+	/// memory taken from an arena is returned instead of being released.
+	#[must_use]
+	pub fn into_buffer(mut self) -> Vec<u8> {
+		#[cfg(feature = "arena")]
+		{
+			self.arena = None;
+		}
+		mem::take(&mut self.data)
+	}
+
 	/// Memory limit.
 	#[must_use]
 	pub const fn limit(&self) -> usize {
@@ -121,19 +167,9 @@ impl Memory {
 	pub fn get(&self, offset: usize, size: usize) -> Vec<u8> {
 		let mut ret = vec![0; size];
 
-		#[allow(clippy::needless_range_loop)]
-		for index in 0..size {
-			let position = if let Some(position) = offset.checked_add(index) {
-				position
-			} else {
-				break;
-			};
-
-			if position >= self.data.len() {
-				break;
-			}
-
-			ret[index] = self.data[position];
+		if offset < self.data.len() {
+			let available = min(size, self.data.len() - offset);
+			ret[..available].copy_from_slice(&self.data[offset..(offset + available)]);
 		}
 
 		ret
@@ -164,13 +200,13 @@ impl Memory {
 			self.data.resize(offset + target_size, 0);
 		}
 
+		let target = &mut self.data[offset..(offset + target_size)];
 		if target_size > value.len() {
-			self.data[offset..((value.len()) + offset)].clone_from_slice(value);
-			for index in (value.len())..target_size {
-				self.data[offset + index] = 0;
-			}
+			let (copied, zeroed) = target.split_at_mut(value.len());
+			copied.copy_from_slice(value);
+			zeroed.fill(0);
 		} else {
-			self.data[offset..(target_size + offset)].clone_from_slice(&value[..target_size]);
+			target.copy_from_slice(&value[..target_size]);
 		}
 
 		Ok(())
@@ -240,8 +276,19 @@ fn next_multiple_of_32(x: U256) -> Option<U256> {
 	x.checked_add(r.into())
 }
 
+#[cfg(feature = "arena")]
+impl Drop for Memory {
+	fn drop(&mut self) {
+		if let Some(arena) = &self.arena {
+			arena.release(mem::take(&mut self.data));
+		}
+	}
+}
+
 #[cfg(test)]
 mod tests {
+	#[cfg(feature = "arena")]
+	use super::FrameArena;
 	use super::{next_multiple_of_32, Memory, U256};
 
 	#[test]
@@ -320,4 +367,61 @@ mod tests {
 			&[0u8, 0u8, 0u8, 4u8, 0u8, 3u8, 4u8, 0u8].to_vec()
 		);
 	}
+
+	#[cfg(feature = "arena")]
+	#[test]
+	fn test_memory_buffer_returns_to_arena() {
+		let arena = FrameArena::new();
+
+		let mut memory = Memory::new_in(100usize, &arena);
+		memory.set(0usize, &[1u8; 64], None).unwrap();
+		drop(memory);
+		assert_eq!(arena.len(), 1);
+
+		let memory = Memory::new_in(100usize, &arena);
+		assert!(memory.data().is_empty());
+		assert!(memory.data().capacity() >= 64);
+		assert!(arena.is_empty());
+	}
+
+	#[test]
+	fn test_memory_get_and_set_ranges() {
+		let mut memory = Memory::new(100usize);
+		memory.set(2usize, &[1u8, 2u8, 3u8], None).unwrap();
+
+		// Reads past the end are zero-padded.
+		assert_eq!(
+			memory.get(1usize, 6usize),
+			vec![0u8, 1u8, 2u8, 3u8, 0u8, 0u8]
+		);
+		assert_eq!(memory.get(10usize, 2usize), vec![0u8, 0u8]);
+		assert_eq!(memory.get(usize::MAX, 2usize), vec![0u8, 0u8]);
+
+		// Writes shorter than the target size are zero-padded.
+		memory.set(1usize, &[9u8], Some(3usize)).unwrap();
+		assert_eq!(memory.data(), &[0u8, 9u8, 0u8, 0u8, 3u8].to_vec());
+	}
+
+	#[test]
+	fn test_memory_with_buffer_does_not_reallocate() {
+		let buffer = Vec::with_capacity(100);
+		let pointer = buffer.as_ptr();
+
+		let mut memory = Memory::with_buffer(buffer, 100usize);
+		memory.set(0usize, &[1u8; 100], None).unwrap();
+		assert_eq!(memory.data().as_ptr(), pointer);
+		assert!(memory.set(1usize, &[1u8; 100], None).is_err());
+		assert_eq!(memory.into_buffer().as_ptr(), pointer);
+	}
+
+	#[cfg(feature = "arena")]
+	#[test]
+	fn test_memory_into_buffer_bypasses_arena() {
+		let arena = FrameArena::new();
+
+		let mut memory = Memory::new_in(100usize, &arena);
+		memory.set(0usize, &[1u8; 64], None).unwrap();
+		assert_eq!(memory.into_buffer(), vec![1u8; 64]);
+		assert!(arena.is_empty());
+	}
 }
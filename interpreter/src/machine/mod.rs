@@ -1,16 +1,30 @@
+#[cfg(feature = "arena")]
+mod arena;
+mod dump;
 mod memory;
 mod stack;
 
-use alloc::{rc::Rc, vec::Vec};
+use alloc::{sync::Arc, vec::Vec};
 
-pub use self::{memory::Memory, stack::Stack};
+#[cfg(feature = "arena")]
+pub use self::arena::FrameArena;
+pub use self::{
+	dump::{MachineDump, MemoryHexdump, StackTop},
+	memory::Memory,
+	stack::Stack,
+};
+use crate::eof::EofLayout;
+
+/// Maximum depth of the return stack of EOF functions, see
+/// [EIP-4750](https://eips.ethereum.org/EIPS/eip-4750).
+pub const RETURN_STACK_LIMIT: usize = 1024;
 
 /// Core execution layer for EVM.
 pub struct Machine<S> {
 	/// Program data.
-	pub(crate) data: Rc<Vec<u8>>,
+	pub(crate) data: Arc<Vec<u8>>,
 	/// Program code.
-	pub(crate) code: Rc<Vec<u8>>,
+	pub(crate) code: Arc<Vec<u8>>,
 	/// Return value. Note the difference between `retbuf`.
 	/// A `retval` holds what's returned by the current machine, with `RETURN` or `REVERT` opcode.
 	/// A `retbuf` holds the buffer of returned value by sub-calls.
@@ -21,13 +35,20 @@ pub struct Machine<S> {
 	pub stack: Stack,
 	/// Extra state,
 	pub state: S,
+	/// Layout of the code if it is an EOF container, or `None` for legacy
+	/// code.
+	pub(crate) eof: Option<EofLayout>,
+	/// Positions to return to from the EOF functions called with `CALLF`.
+	pub(crate) return_stack: Vec<usize>,
+	/// Whether `0x44` is `PREVRANDAO` rather than `DIFFICULTY`.
+	pub(crate) prevrandao: bool,
 }
 
 impl<S> Machine<S> {
 	/// Create a new machine with given code and data.
 	pub fn new(
-		code: Rc<Vec<u8>>,
-		data: Rc<Vec<u8>>,
+		code: Arc<Vec<u8>>,
+		data: Arc<Vec<u8>>,
 		stack_limit: usize,
 		memory_limit: usize,
 		state: S,
@@ -39,9 +60,67 @@ impl<S> Machine<S> {
 			memory: Memory::new(memory_limit),
 			stack: Stack::new(stack_limit),
 			state,
+			eof: None,
+			return_stack: Vec::new(),
+			prevrandao: false,
+		}
+	}
+
+	/// Create a new machine with given code and data, whose memory buffer
+	/// is taken from and released to the arena.
+	#[cfg(feature = "arena")]
+	pub fn new_in(
+		code: Arc<Vec<u8>>,
+		data: Arc<Vec<u8>>,
+		stack_limit: usize,
+		memory_limit: usize,
+		state: S,
+		arena: &FrameArena,
+	) -> Self {
+		Self {
+			data,
+			code,
+			retval: Vec::new(),
+			memory: Memory::new_in(memory_limit, arena),
+			stack: Stack::new(stack_limit),
+			state,
+			eof: None,
+			return_stack: Vec::new(),
+			prevrandao: false,
 		}
 	}
 
+	/// Execute the code as the EOF container of the given layout, see
+	/// [eof::layout](crate::eof::layout).
+	#[must_use]
+	pub fn with_eof(mut self, layout: EofLayout) -> Self {
+		self.eof = Some(layout);
+		self
+	}
+
+	/// Layout of the code if it is an EOF container.
+	pub fn eof(&self) -> Option<&EofLayout> {
+		self.eof.as_ref()
+	}
+
+	/// Execute `0x44` as `PREVRANDAO`, pushing the block randomness instead of
+	/// the difficulty, see [EIP-4399](https://eips.ethereum.org/EIPS/eip-4399).
+	#[must_use]
+	pub fn with_prevrandao(mut self) -> Self {
+		self.prevrandao = true;
+		self
+	}
+
+	/// Whether `0x44` is executed as `PREVRANDAO`.
+	pub fn prevrandao(&self) -> bool {
+		self.prevrandao
+	}
+
+	/// Return stack of EOF function calls.
+	pub fn return_stack(&self) -> &[usize] {
+		&self.return_stack
+	}
+
 	/// Machine code.
 	pub fn code(&self) -> &[u8] {
 		&self.code
@@ -1,4 +1,5 @@
 use alloc::vec::Vec;
+use core::{iter::Rev, slice};
 
 use primitive_types::H256;
 
@@ -58,6 +59,24 @@ impl Stack {
 		}
 	}
 
+	/// Create a new stack with given limit, reusing a buffer, such as one
+	/// returned by [Stack::into_buffer]. If the capacity of the buffer covers
+	/// the limit, pushing never reallocates.
+	#[must_use]
+	pub fn with_buffer(mut buffer: Vec<H256>, limit: usize) -> Self {
+		buffer.clear();
+		Self {
+			data: buffer,
+			limit,
+		}
+	}
+
+	/// Consume the stack, returning its buffer for reuse.
+	#[must_use]
+	pub fn into_buffer(self) -> Vec<H256> {
+		self.data
+	}
+
 	/// Stack limit.
 	#[inline]
 	#[must_use]
@@ -79,13 +98,33 @@ impl Stack {
 		self.data.is_empty()
 	}
 
-	/// Stack data.
+	/// Stack data, from the bottom of the stack.
 	#[inline]
 	#[must_use]
 	pub const fn data(&self) -> &Vec<H256> {
 		&self.data
 	}
 
+	/// Consume the stack, returning its data from the bottom of the stack.
+	#[must_use]
+	pub fn into_data(self) -> Vec<H256> {
+		self.data
+	}
+
+	/// Iterate over the stack values, from the top of the stack.
+	pub fn iter(&self) -> Rev<slice::Iter<'_, H256>> {
+		self.data.iter().rev()
+	}
+
+	/// Value at given index for the stack, where the top of the stack is at
+	/// index `0`. Returns `None` if the index is too large.
+	#[inline]
+	#[must_use]
+	pub fn get(&self, no_from_top: usize) -> Option<&H256> {
+		let index = self.data.len().checked_sub(no_from_top.checked_add(1)?)?;
+		self.data.get(index)
+	}
+
 	/// Clear the stack.
 	pub fn clear(&mut self) {
 		self.data.clear();
@@ -194,3 +233,28 @@ impl Stack {
 		unchecked_pop_push0
 	);
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn access_from_top() {
+		let mut stack = Stack::new(4);
+		stack.push(H256::repeat_byte(1)).unwrap();
+		stack.push(H256::repeat_byte(2)).unwrap();
+
+		assert_eq!(stack.get(0), Some(&H256::repeat_byte(2)));
+		assert_eq!(stack.get(1), Some(&H256::repeat_byte(1)));
+		assert_eq!(stack.get(2), None);
+		assert_eq!(stack.get(usize::MAX), None);
+		assert_eq!(
+			stack.iter().copied().collect::<Vec<_>>(),
+			vec![H256::repeat_byte(2), H256::repeat_byte(1)]
+		);
+		assert_eq!(
+			stack.into_data(),
+			vec![H256::repeat_byte(1), H256::repeat_byte(2)]
+		);
+	}
+}
@@ -169,6 +169,42 @@ impl Opcode {
 	pub const SWAP15: Opcode = Opcode(0x9e);
 	pub const SWAP16: Opcode = Opcode(0x9f);
 
+	/// `DATALOAD`, only valid in EOF code, see
+	/// [EIP-7480](https://eips.ethereum.org/EIPS/eip-7480)
+	pub const DATALOAD: Opcode = Opcode(0xd0);
+	/// `DATALOADN`, only valid in EOF code
+	pub const DATALOADN: Opcode = Opcode(0xd1);
+	/// `DATASIZE`, only valid in EOF code
+	pub const DATASIZE: Opcode = Opcode(0xd2);
+	/// `DATACOPY`, only valid in EOF code
+	pub const DATACOPY: Opcode = Opcode(0xd3);
+
+	/// `RJUMP`, only valid in EOF code, see
+	/// [EIP-4200](https://eips.ethereum.org/EIPS/eip-4200)
+	pub const RJUMP: Opcode = Opcode(0xe0);
+	/// `RJUMPI`, only valid in EOF code
+	pub const RJUMPI: Opcode = Opcode(0xe1);
+	/// `RJUMPV`, only valid in EOF code
+	pub const RJUMPV: Opcode = Opcode(0xe2);
+	/// `CALLF`, only valid in EOF code, see
+	/// [EIP-4750](https://eips.ethereum.org/EIPS/eip-4750)
+	pub const CALLF: Opcode = Opcode(0xe3);
+	/// `RETF`, only valid in EOF code
+	pub const RETF: Opcode = Opcode(0xe4);
+	/// `JUMPF`, only valid in EOF code, see
+	/// [EIP-6206](https://eips.ethereum.org/EIPS/eip-6206)
+	pub const JUMPF: Opcode = Opcode(0xe5);
+	/// `DUPN`, only valid in EOF code, see
+	/// [EIP-663](https://eips.ethereum.org/EIPS/eip-663)
+	pub const DUPN: Opcode = Opcode(0xe6);
+	/// `SWAPN`, only valid in EOF code
+	pub const SWAPN: Opcode = Opcode(0xe7);
+	/// `EXCHANGE`, only valid in EOF code
+	pub const EXCHANGE: Opcode = Opcode(0xe8);
+	/// `RETURNCONTRACT`, only valid in EOF code, see
+	/// [EIP-7620](https://eips.ethereum.org/EIPS/eip-7620)
+	pub const RETURNCONTRACT: Opcode = Opcode(0xee);
+
 	/// See [EIP-3541](https://github.com/ethereum/EIPs/blob/master/EIPS/eip-3541.md)
 	pub const EOFMAGIC: Opcode = Opcode(0xef);
 
@@ -221,6 +257,9 @@ impl Opcode {
 	pub const NUMBER: Opcode = Opcode(0x43);
 	/// `DIFFICULTY`
 	pub const DIFFICULTY: Opcode = Opcode(0x44);
+	/// `PREVRANDAO`, replacing `DIFFICULTY` from the Merge on, see
+	/// [EIP-4399](https://eips.ethereum.org/EIPS/eip-4399)
+	pub const PREVRANDAO: Opcode = Opcode(0x44);
 	/// `GASLIMIT`
 	pub const GASLIMIT: Opcode = Opcode(0x45);
 	/// `CHAINID`
@@ -229,6 +268,10 @@ impl Opcode {
 	pub const SELFBALANCE: Opcode = Opcode(0x47);
 	/// `BASEFEE`
 	pub const BASEFEE: Opcode = Opcode(0x48);
+	/// `BLOBHASH`
+	pub const BLOBHASH: Opcode = Opcode(0x49);
+	/// `BLOBBASEFEE`
+	pub const BLOBBASEFEE: Opcode = Opcode(0x4a);
 
 	/// `SLOAD`
 	pub const SLOAD: Opcode = Opcode(0x54);
@@ -250,6 +293,10 @@ impl Opcode {
 	pub const LOG3: Opcode = Opcode(0xa3);
 	pub const LOG4: Opcode = Opcode(0xa4);
 
+	/// `EOFCREATE`, only valid in EOF code, see
+	/// [EIP-7620](https://eips.ethereum.org/EIPS/eip-7620)
+	pub const EOFCREATE: Opcode = Opcode(0xec);
+
 	/// `CREATE`
 	pub const CREATE: Opcode = Opcode(0xf0);
 	/// `CALL`
@@ -261,6 +308,10 @@ impl Opcode {
 	pub const DELEGATECALL: Opcode = Opcode(0xf4);
 	/// `CREATE2`
 	pub const CREATE2: Opcode = Opcode(0xf5);
+	/// `AUTH`
+	pub const AUTH: Opcode = Opcode(0xf6);
+	/// `AUTHCALL`
+	pub const AUTHCALL: Opcode = Opcode(0xf7);
 
 	/// `STATICCALL`
 	pub const STATICCALL: Opcode = Opcode(0xfa);
@@ -292,4 +343,189 @@ impl Opcode {
 	pub const fn as_usize(&self) -> usize {
 		self.0 as usize
 	}
+
+	/// Mnemonic of the opcode, or `None` if the byte is not a known opcode.
+	#[must_use]
+	pub const fn name(&self) -> Option<&'static str> {
+		Some(match *self {
+			Self::STOP => "STOP",
+			Self::ADD => "ADD",
+			Self::MUL => "MUL",
+			Self::SUB => "SUB",
+			Self::DIV => "DIV",
+			Self::SDIV => "SDIV",
+			Self::MOD => "MOD",
+			Self::SMOD => "SMOD",
+			Self::ADDMOD => "ADDMOD",
+			Self::MULMOD => "MULMOD",
+			Self::EXP => "EXP",
+			Self::SIGNEXTEND => "SIGNEXTEND",
+			Self::LT => "LT",
+			Self::GT => "GT",
+			Self::SLT => "SLT",
+			Self::SGT => "SGT",
+			Self::EQ => "EQ",
+			Self::ISZERO => "ISZERO",
+			Self::AND => "AND",
+			Self::OR => "OR",
+			Self::XOR => "XOR",
+			Self::NOT => "NOT",
+			Self::BYTE => "BYTE",
+			Self::SHL => "SHL",
+			Self::SHR => "SHR",
+			Self::SAR => "SAR",
+			Self::SHA3 => "SHA3",
+			Self::ADDRESS => "ADDRESS",
+			Self::BALANCE => "BALANCE",
+			Self::ORIGIN => "ORIGIN",
+			Self::CALLER => "CALLER",
+			Self::CALLVALUE => "CALLVALUE",
+			Self::CALLDATALOAD => "CALLDATALOAD",
+			Self::CALLDATASIZE => "CALLDATASIZE",
+			Self::CALLDATACOPY => "CALLDATACOPY",
+			Self::CODESIZE => "CODESIZE",
+			Self::CODECOPY => "CODECOPY",
+			Self::GASPRICE => "GASPRICE",
+			Self::EXTCODESIZE => "EXTCODESIZE",
+			Self::EXTCODECOPY => "EXTCODECOPY",
+			Self::RETURNDATASIZE => "RETURNDATASIZE",
+			Self::RETURNDATACOPY => "RETURNDATACOPY",
+			Self::EXTCODEHASH => "EXTCODEHASH",
+			Self::BLOCKHASH => "BLOCKHASH",
+			Self::COINBASE => "COINBASE",
+			Self::TIMESTAMP => "TIMESTAMP",
+			Self::NUMBER => "NUMBER",
+			Self::DIFFICULTY => "DIFFICULTY",
+			Self::GASLIMIT => "GASLIMIT",
+			Self::CHAINID => "CHAINID",
+			Self::SELFBALANCE => "SELFBALANCE",
+			Self::BASEFEE => "BASEFEE",
+			Self::BLOBHASH => "BLOBHASH",
+			Self::BLOBBASEFEE => "BLOBBASEFEE",
+			Self::POP => "POP",
+			Self::MLOAD => "MLOAD",
+			Self::MSTORE => "MSTORE",
+			Self::MSTORE8 => "MSTORE8",
+			Self::SLOAD => "SLOAD",
+			Self::SSTORE => "SSTORE",
+			Self::JUMP => "JUMP",
+			Self::JUMPI => "JUMPI",
+			Self::PC => "PC",
+			Self::MSIZE => "MSIZE",
+			Self::GAS => "GAS",
+			Self::JUMPDEST => "JUMPDEST",
+			Self::TLOAD => "TLOAD",
+			Self::TSTORE => "TSTORE",
+			Self::MCOPY => "MCOPY",
+			Self::PUSH0 => "PUSH0",
+			Self::PUSH1 => "PUSH1",
+			Self::PUSH2 => "PUSH2",
+			Self::PUSH3 => "PUSH3",
+			Self::PUSH4 => "PUSH4",
+			Self::PUSH5 => "PUSH5",
+			Self::PUSH6 => "PUSH6",
+			Self::PUSH7 => "PUSH7",
+			Self::PUSH8 => "PUSH8",
+			Self::PUSH9 => "PUSH9",
+			Self::PUSH10 => "PUSH10",
+			Self::PUSH11 => "PUSH11",
+			Self::PUSH12 => "PUSH12",
+			Self::PUSH13 => "PUSH13",
+			Self::PUSH14 => "PUSH14",
+			Self::PUSH15 => "PUSH15",
+			Self::PUSH16 => "PUSH16",
+			Self::PUSH17 => "PUSH17",
+			Self::PUSH18 => "PUSH18",
+			Self::PUSH19 => "PUSH19",
+			Self::PUSH20 => "PUSH20",
+			Self::PUSH21 => "PUSH21",
+			Self::PUSH22 => "PUSH22",
+			Self::PUSH23 => "PUSH23",
+			Self::PUSH24 => "PUSH24",
+			Self::PUSH25 => "PUSH25",
+			Self::PUSH26 => "PUSH26",
+			Self::PUSH27 => "PUSH27",
+			Self::PUSH28 => "PUSH28",
+			Self::PUSH29 => "PUSH29",
+			Self::PUSH30 => "PUSH30",
+			Self::PUSH31 => "PUSH31",
+			Self::PUSH32 => "PUSH32",
+			Self::DUP1 => "DUP1",
+			Self::DUP2 => "DUP2",
+			Self::DUP3 => "DUP3",
+			Self::DUP4 => "DUP4",
+			Self::DUP5 => "DUP5",
+			Self::DUP6 => "DUP6",
+			Self::DUP7 => "DUP7",
+			Self::DUP8 => "DUP8",
+			Self::DUP9 => "DUP9",
+			Self::DUP10 => "DUP10",
+			Self::DUP11 => "DUP11",
+			Self::DUP12 => "DUP12",
+			Self::DUP13 => "DUP13",
+			Self::DUP14 => "DUP14",
+			Self::DUP15 => "DUP15",
+			Self::DUP16 => "DUP16",
+			Self::SWAP1 => "SWAP1",
+			Self::SWAP2 => "SWAP2",
+			Self::SWAP3 => "SWAP3",
+			Self::SWAP4 => "SWAP4",
+			Self::SWAP5 => "SWAP5",
+			Self::SWAP6 => "SWAP6",
+			Self::SWAP7 => "SWAP7",
+			Self::SWAP8 => "SWAP8",
+			Self::SWAP9 => "SWAP9",
+			Self::SWAP10 => "SWAP10",
+			Self::SWAP11 => "SWAP11",
+			Self::SWAP12 => "SWAP12",
+			Self::SWAP13 => "SWAP13",
+			Self::SWAP14 => "SWAP14",
+			Self::SWAP15 => "SWAP15",
+			Self::SWAP16 => "SWAP16",
+			Self::DATALOAD => "DATALOAD",
+			Self::DATALOADN => "DATALOADN",
+			Self::DATASIZE => "DATASIZE",
+			Self::DATACOPY => "DATACOPY",
+			Self::RJUMP => "RJUMP",
+			Self::RJUMPI => "RJUMPI",
+			Self::RJUMPV => "RJUMPV",
+			Self::CALLF => "CALLF",
+			Self::RETF => "RETF",
+			Self::JUMPF => "JUMPF",
+			Self::DUPN => "DUPN",
+			Self::SWAPN => "SWAPN",
+			Self::EXCHANGE => "EXCHANGE",
+			Self::RETURNCONTRACT => "RETURNCONTRACT",
+			Self::LOG0 => "LOG0",
+			Self::LOG1 => "LOG1",
+			Self::LOG2 => "LOG2",
+			Self::LOG3 => "LOG3",
+			Self::LOG4 => "LOG4",
+			Self::EOFCREATE => "EOFCREATE",
+			Self::CREATE => "CREATE",
+			Self::CALL => "CALL",
+			Self::CALLCODE => "CALLCODE",
+			Self::RETURN => "RETURN",
+			Self::DELEGATECALL => "DELEGATECALL",
+			Self::CREATE2 => "CREATE2",
+			Self::AUTH => "AUTH",
+			Self::AUTHCALL => "AUTHCALL",
+			Self::STATICCALL => "STATICCALL",
+			Self::REVERT => "REVERT",
+			Self::INVALID => "INVALID",
+			Self::SUICIDE => "SUICIDE",
+			_ => return None,
+		})
+	}
+
+	/// Mnemonic of the opcode, naming `0x44` `PREVRANDAO` rather than
+	/// `DIFFICULTY` if `prevrandao` is set.
+	#[must_use]
+	pub const fn name_with_prevrandao(&self, prevrandao: bool) -> Option<&'static str> {
+		if prevrandao && self.0 == Self::PREVRANDAO.0 {
+			Some("PREVRANDAO")
+		} else {
+			self.name()
+		}
+	}
 }
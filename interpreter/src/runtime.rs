@@ -1,4 +1,4 @@
-use alloc::{rc::Rc, vec::Vec};
+use alloc::{sync::Arc, vec::Vec};
 
 use primitive_types::{H160, H256, U256};
 use sha3::{Digest, Keccak256};
@@ -16,7 +16,7 @@ pub struct RuntimeState {
 	/// Runtime context.
 	pub context: Context,
 	/// Transaction context.
-	pub transaction_context: Rc<TransactionContext>,
+	pub transaction_context: Arc<TransactionContext>,
 	/// Return data buffer.
 	pub retbuf: Vec<u8>,
 }
@@ -41,6 +41,7 @@ impl GasState for RuntimeState {
 
 /// Context of the runtime.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Context {
 	/// Execution address.
 	pub address: H160,
@@ -48,6 +49,10 @@ pub struct Context {
 	pub caller: H160,
 	/// Apparent value of the EVM.
 	pub apparent_value: U256,
+	/// Account authorized by `AUTH`, and used as the caller of `AUTHCALL`,
+	/// as in [EIP-3074](https://eips.ethereum.org/EIPS/eip-3074). It is
+	/// unset in every new frame.
+	pub authorized: Option<H160>,
 }
 
 #[derive(Clone, Debug)]
@@ -60,6 +65,7 @@ pub struct TransactionContext {
 
 /// Transfer from source to target, with given value.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Transfer {
 	/// Source address.
 	pub source: H160,
@@ -71,12 +77,51 @@ pub struct Transfer {
 
 /// Log
 #[derive(Clone, Debug)]
+#[cfg_attr(
+	feature = "scale",
+	derive(scale_codec::Encode, scale_codec::Decode, scale_info::TypeInfo)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Log {
 	pub address: H160,
 	pub topics: Vec<H256>,
 	pub data: Vec<u8>,
 }
 
+/// Balance swept by a `SELFDESTRUCT`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(
+	feature = "scale",
+	derive(scale_codec::Encode, scale_codec::Decode, scale_info::TypeInfo)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SelfDestruct {
+	/// Destructed account.
+	pub address: H160,
+	/// Beneficiary of the balance.
+	pub beneficiary: H160,
+	/// Balance transferred to the beneficiary.
+	pub balance: U256,
+}
+
+/// Account metadata, without the code itself.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(
+	feature = "scale",
+	derive(scale_codec::Encode, scale_codec::Decode, scale_info::TypeInfo)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Basic {
+	/// Account balance.
+	pub balance: U256,
+	/// Account nonce.
+	pub nonce: U256,
+	/// Size of the account code.
+	pub code_size: U256,
+	/// Hash of the account code.
+	pub code_hash: H256,
+}
+
 // Identify if the origin of set_code() comes from a transact or subcall.
 #[derive(Clone, Debug)]
 pub enum SetCodeOrigin {
@@ -84,29 +129,157 @@ pub enum SetCodeOrigin {
 	Subcall(H160),
 }
 
-#[auto_impl::auto_impl(&, Box)]
-pub trait RuntimeEnvironment {
+/// Block hash lookup used by the `BLOCKHASH` opcode. It is separate from
+/// [RuntimeEnvironment] so that block hashes can be served by a different
+/// source than the rest of the block information.
+#[auto_impl::auto_impl(&, Box, Arc)]
+pub trait RuntimeBlockHashes {
 	/// Get environmental block hash.
 	fn block_hash(&self, number: U256) -> H256;
+}
+
+/// Environment of the execution. Block information is given all at once by
+/// [RuntimeEnvironment::block_env], on top of which the per-field getters are
+/// implemented. Implementors can override the getters to answer them
+/// directly, and wrappers only need to forward `block_env` and the
+/// per-transaction methods.
+#[auto_impl::auto_impl(&, Box, Arc)]
+pub trait RuntimeEnvironment: RuntimeBlockHashes {
+	/// Get all block information at once.
+	fn block_env(&self) -> BlockEnv;
 	/// Get environmental block number.
-	fn block_number(&self) -> U256;
+	fn block_number(&self) -> U256 {
+		self.block_env().number
+	}
 	/// Get environmental coinbase.
-	fn block_coinbase(&self) -> H160;
+	fn block_coinbase(&self) -> H160 {
+		self.block_env().coinbase
+	}
 	/// Get environmental block timestamp.
-	fn block_timestamp(&self) -> U256;
+	fn block_timestamp(&self) -> U256 {
+		self.block_env().timestamp
+	}
 	/// Get environmental block difficulty.
-	fn block_difficulty(&self) -> U256;
+	fn block_difficulty(&self) -> U256 {
+		self.block_env().difficulty
+	}
 	/// Get environmental block randomness.
-	fn block_randomness(&self) -> Option<H256>;
+	fn block_randomness(&self) -> Option<H256> {
+		self.block_env().randomness
+	}
 	/// Get environmental gas limit.
-	fn block_gas_limit(&self) -> U256;
+	fn block_gas_limit(&self) -> U256 {
+		self.block_env().gas_limit
+	}
 	/// Environmental block base fee.
-	fn block_base_fee_per_gas(&self) -> U256;
+	fn block_base_fee_per_gas(&self) -> U256 {
+		self.block_env().base_fee_per_gas
+	}
 	/// Get environmental chain ID.
-	fn chain_id(&self) -> U256;
+	fn chain_id(&self) -> U256 {
+		self.block_env().chain_id
+	}
+	/// Environmental blob base fee, as in
+	/// [EIP-7516](https://eips.ethereum.org/EIPS/eip-7516).
+	fn block_blob_base_fee(&self) -> U256 {
+		self.block_env().blob_base_fee
+	}
+	/// Versioned hash of the blob at `index` of the current transaction, as
+	/// in [EIP-4844](https://eips.ethereum.org/EIPS/eip-4844), or zero if it
+	/// has no such blob.
+	fn blob_hash(&self, _index: U256) -> H256 {
+		H256::zero()
+	}
+	/// Recover the signer of a secp256k1 signature over `message`, for `AUTH`
+	/// of [EIP-3074](https://eips.ethereum.org/EIPS/eip-3074). Environments
+	/// without signature recovery keep the default, with which `AUTH` always
+	/// fails.
+	fn recover_signer(&self, _message: H256, _y_parity: bool, _r: H256, _s: H256) -> Option<H160> {
+		None
+	}
+}
+
+/// Block information, as returned by [RuntimeEnvironment::block_env].
+///
+/// It is itself an environment, for replaying executions and for backends
+/// built on a fixed block. Block hashes are not part of it and are always
+/// the zero hash, and so are blob hashes, which belong to transactions.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(
+	feature = "scale",
+	derive(scale_codec::Encode, scale_codec::Decode, scale_info::TypeInfo)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BlockEnv {
+	/// Block number.
+	pub number: U256,
+	/// Block coinbase.
+	pub coinbase: H160,
+	/// Block timestamp.
+	pub timestamp: U256,
+	/// Block difficulty.
+	pub difficulty: U256,
+	/// Block randomness.
+	pub randomness: Option<H256>,
+	/// Block gas limit.
+	pub gas_limit: U256,
+	/// Block base fee per gas.
+	pub base_fee_per_gas: U256,
+	/// Chain ID.
+	pub chain_id: U256,
+	/// Blob base fee.
+	pub blob_base_fee: U256,
+}
+
+impl RuntimeBlockHashes for BlockEnv {
+	fn block_hash(&self, _number: U256) -> H256 {
+		H256::default()
+	}
+}
+
+impl RuntimeEnvironment for BlockEnv {
+	fn block_number(&self) -> U256 {
+		self.number
+	}
+
+	fn block_coinbase(&self) -> H160 {
+		self.coinbase
+	}
+
+	fn block_timestamp(&self) -> U256 {
+		self.timestamp
+	}
+
+	fn block_difficulty(&self) -> U256 {
+		self.difficulty
+	}
+
+	fn block_randomness(&self) -> Option<H256> {
+		self.randomness
+	}
+
+	fn block_gas_limit(&self) -> U256 {
+		self.gas_limit
+	}
+
+	fn block_base_fee_per_gas(&self) -> U256 {
+		self.base_fee_per_gas
+	}
+
+	fn chain_id(&self) -> U256 {
+		self.chain_id
+	}
+
+	fn block_blob_base_fee(&self) -> U256 {
+		self.blob_base_fee
+	}
+
+	fn block_env(&self) -> BlockEnv {
+		self.clone()
+	}
 }
 
-#[auto_impl::auto_impl(&, Box)]
+#[auto_impl::auto_impl(&, Box, Arc)]
 pub trait RuntimeBaseBackend {
 	/// Get balance of address.
 	fn balance(&self, address: H160) -> U256;
@@ -120,6 +293,18 @@ pub trait RuntimeBaseBackend {
 	}
 	/// Get code of address.
 	fn code(&self, address: H160) -> Vec<u8>;
+	/// Get `len` bytes of the code of address, starting at `offset`. The
+	/// result is shorter if the code ends before. Backends storing large
+	/// code can override it to avoid loading the whole code.
+	fn code_slice(&self, address: H160, offset: usize, len: usize) -> Vec<u8> {
+		let code = self.code(address);
+		if offset >= code.len() {
+			return Vec::new();
+		}
+
+		let end = core::cmp::min(offset.saturating_add(len), code.len());
+		code[offset..end].to_vec()
+	}
 	/// Get storage value of address at index.
 	fn storage(&self, address: H160, index: H256) -> H256;
 	/// Get transient storage value of address at index.
@@ -130,12 +315,27 @@ pub trait RuntimeBaseBackend {
 
 	/// Get the current nonce of an account.
 	fn nonce(&self, address: H160) -> U256;
+
+	/// Get the balance, nonce, code size and code hash of an account at once.
+	/// Remote backends can override it to answer in a single request, and
+	/// should override [RuntimeBaseBackend::code_size] and
+	/// [RuntimeBaseBackend::code_hash] to answer without fetching the code.
+	fn basic(&self, address: H160) -> Basic {
+		Basic {
+			balance: self.balance(address),
+			nonce: self.nonce(address),
+			code_size: self.code_size(address),
+			code_hash: self.code_hash(address),
+		}
+	}
 }
 
 /// The distinguish between `RuntimeBaseBackend` and `RuntimeBackend` is for the implementation of
 /// overlays.
 pub trait RuntimeBackend: RuntimeBaseBackend {
-	/// Get original storage value of address at index.
+	/// Get original storage value of address at index, which is the value at
+	/// the start of the current transaction. It is unaffected by changes made
+	/// so far in the transaction, whether they are later committed or reverted.
 	fn original_storage(&self, address: H160, index: H256) -> H256;
 	/// Check whether an address has already been deleted.
 	fn deleted(&self, address: H160) -> bool;
@@ -145,8 +345,23 @@ pub trait RuntimeBackend: RuntimeBaseBackend {
 		!self.is_cold(address, index)
 	}
 
+	/// Checks a batch of address or (address, index) pairs, returning whether
+	/// each of them is cold.
+	fn is_cold_batch(&self, accesses: &[(H160, Option<H256>)]) -> Vec<bool> {
+		accesses
+			.iter()
+			.map(|(address, index)| self.is_cold(*address, *index))
+			.collect()
+	}
+
 	/// Mark an address or (address, index) pair as hot.
 	fn mark_hot(&mut self, address: H160, index: Option<H256>);
+	/// Mark a batch of address or (address, index) pairs as hot.
+	fn mark_hot_batch(&mut self, accesses: &[(H160, Option<H256>)]) {
+		for (address, index) in accesses {
+			self.mark_hot(*address, *index);
+		}
+	}
 	/// Set storage value of address at index.
 	fn set_storage(&mut self, address: H160, index: H256, value: H256) -> Result<(), ExitError>;
 	/// Set transient storage value of address at index, transient storage gets discarded after every transaction. (see EIP-1153)
@@ -160,6 +375,20 @@ pub trait RuntimeBackend: RuntimeBaseBackend {
 	fn log(&mut self, log: Log) -> Result<(), ExitError>;
 	/// Mark an address to be deleted.
 	fn mark_delete(&mut self, address: H160);
+	/// Record the beneficiary and swept balance of a `SELFDESTRUCT`, right
+	/// before the account is marked to be deleted. Backends not reporting
+	/// selfdestructs can ignore it.
+	fn record_selfdestruct(&mut self, _selfdestruct: SelfDestruct) {}
+	/// Allow `SELFDESTRUCT` to delete the account for the rest of the
+	/// current transaction, see [RuntimeBackend::deletable].
+	fn mark_deletable(&mut self, _address: H160) {}
+	/// Whether `SELFDESTRUCT` deletes the account, rather than only sending
+	/// its balance to the beneficiary. Since EIP-6780, only accounts created
+	/// in the current transaction are deletable. Backends not tracking it
+	/// treat every account as deletable.
+	fn deletable(&self, _address: H160) -> bool {
+		true
+	}
 	/// Fully delete storages of an account.
 	fn reset_storage(&mut self, address: H160);
 	/// Set code of an account.
@@ -0,0 +1,211 @@
+//! # Taint tracking
+//!
+//! Propagate user-defined metadata ("tags") alongside stack values, for taint
+//! analysis and calldata provenance research. Wrap the etable with
+//! [TaintEtable] and implement [TaintHook] to decide how tags flow through
+//! each opcode.
+//!
+//! Only the stack is tracked. Values moving through memory or storage can be
+//! followed by the hook itself, which observes the machine after every
+//! opcode.
+
+use alloc::{sync::Arc, vec::Vec};
+use core::cell::RefCell;
+
+use crate::{
+	eof,
+	etable::{Control, EtableSet},
+	machine::Machine,
+	opcode::Opcode,
+};
+
+/// Hook deciding how tags flow through opcodes.
+pub trait TaintHook<S> {
+	/// Metadata attached to each stack value. Values pushed from outside of
+	/// the etable, such as call results, get the default tag.
+	type Tag: Clone + Default;
+
+	/// Called after `opcode` is evaluated, with the tags of the values it
+	/// popped, the top of the stack first. Returns the tag of the value it
+	/// pushed, which is discarded for opcodes that push nothing.
+	///
+	/// `DUP*`, `SWAP*`, `DUPN`, `SWAPN` and `EXCHANGE` move tags without
+	/// calling the hook.
+	fn propagate(
+		&mut self,
+		machine: &Machine<S>,
+		opcode: Opcode,
+		inputs: &[Self::Tag],
+	) -> Self::Tag;
+}
+
+struct TaintFrame<T> {
+	code: usize,
+	tags: Vec<T>,
+}
+
+/// Etable set propagating tags of stack values through a [TaintHook].
+///
+/// The same etable is shared by every call frame, and tags are kept per frame.
+pub struct TaintEtable<ES: EtableSet, T: TaintHook<ES::State>> {
+	etable: ES,
+	hook: RefCell<T>,
+	frames: RefCell<Vec<TaintFrame<T::Tag>>>,
+}
+
+impl<ES: EtableSet, T: TaintHook<ES::State>> TaintEtable<ES, T> {
+	/// Wrap an etable set.
+	pub fn new(etable: ES, hook: T) -> Self {
+		Self {
+			etable,
+			hook: RefCell::new(hook),
+			frames: RefCell::new(Vec::new()),
+		}
+	}
+
+	/// Tags of the current stack of the innermost frame, from the bottom.
+	pub fn tags(&self) -> Vec<T::Tag> {
+		self.frames
+			.borrow()
+			.last()
+			.map(|frame| frame.tags.clone())
+			.unwrap_or_default()
+	}
+
+	/// Deconstruct into the inner etable and the hook.
+	pub fn deconstruct(self) -> (ES, T) {
+		(self.etable, self.hook.into_inner())
+	}
+}
+
+impl<ES: EtableSet, T: TaintHook<ES::State>> EtableSet for TaintEtable<ES, T> {
+	type State = ES::State;
+	type Handle = ES::Handle;
+	type Trap = ES::Trap;
+
+	fn eval(
+		&self,
+		machine: &mut Machine<Self::State>,
+		handle: &mut Self::Handle,
+		opcode: Opcode,
+		position: usize,
+	) -> Control<Self::Trap> {
+		let mut frames = self.frames.borrow_mut();
+
+		// Frames are identified by their code allocation, which is unique among
+		// live frames. Frames above the current one have exited.
+		let code = Arc::as_ptr(&machine.code) as usize;
+		match frames.iter().rposition(|frame| frame.code == code) {
+			Some(index) => frames.truncate(index + 1),
+			None => frames.push(TaintFrame {
+				code,
+				tags: Vec::new(),
+			}),
+		}
+		let tags = &mut frames.last_mut().expect("frame was just pushed").tags;
+		tags.resize(machine.stack.len(), T::Tag::default());
+
+		let before = machine.stack.len();
+		let ret = self.etable.eval(machine, handle, opcode, position);
+		let after = machine.stack.len();
+
+		if matches!(ret, Control::Exit(Err(_))) {
+			return ret;
+		}
+
+		// Instructions with immediates only complete in EOF code, where they
+		// are validated, so their immediates are in the code.
+		let (inputs, outputs) = eof::stack_io(&machine.code, position);
+		let inputs = inputs.min(before);
+		let value = opcode.as_u8();
+		if (Opcode::DUP1.as_u8()..=Opcode::DUP16.as_u8()).contains(&value) || opcode == Opcode::DUPN
+		{
+			if after == before + 1 {
+				let tag = tags[before - inputs].clone();
+				tags.push(tag);
+			}
+		} else if (Opcode::SWAP1.as_u8()..=Opcode::SWAP16.as_u8()).contains(&value)
+			|| opcode == Opcode::SWAPN
+		{
+			if after == before && inputs > 1 {
+				tags.swap(before - 1, before - inputs);
+			}
+		} else if opcode == Opcode::EXCHANGE {
+			let immediate = machine.code[position + 1] as usize;
+			let n = (immediate >> 4) + 1;
+			let m = (immediate & 0x0f) + 1;
+			if after == before && before > n + m {
+				tags.swap(before - 1 - n, before - 1 - n - m);
+			}
+		} else {
+			// Calls and creates push their result after the trap is resolved,
+			// with the default tag.
+			let pushes = if matches!(ret, Control::Trap(_)) {
+				0
+			} else {
+				outputs
+			};
+			let mut inputs = tags.split_off(before - inputs);
+			inputs.reverse();
+			let tag = self.hook.borrow_mut().propagate(machine, opcode, &inputs);
+			if pushes == 1 {
+				tags.push(tag);
+			}
+		}
+		tags.resize(after, T::Tag::default());
+
+		ret
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{etable::Etable, EtableInterpreter, RunInterpreter};
+
+	fn run(code: Vec<u8>) -> Vec<bool> {
+		let etable = TaintEtable::new(Etable::<(), (), ()>::core(), CalldataTaint);
+		let mut machine = Machine::new(Arc::new(code), Arc::new(Vec::new()), 1024, 10000, ());
+		if let Ok(layout) = eof::layout(&machine.code) {
+			machine = machine.with_eof(layout);
+		}
+		let mut vm = EtableInterpreter::new(machine, &etable);
+		let _ = vm.run(&mut ());
+		drop(vm);
+
+		etable.tags()
+	}
+
+	/// Marks calldata loads as tainted.
+	struct CalldataTaint;
+
+	impl TaintHook<()> for CalldataTaint {
+		type Tag = bool;
+
+		fn propagate(&mut self, _machine: &Machine<()>, opcode: Opcode, inputs: &[bool]) -> bool {
+			opcode == Opcode::CALLDATALOAD || inputs.iter().any(|tag| *tag)
+		}
+	}
+
+	#[test]
+	fn calldata_taint_flows_through_arithmetic() {
+		// PUSH1 0 CALLDATALOAD PUSH1 1 ADD PUSH1 2 DUP1 DUP3 INVALID
+		let code = hex::decode("60003560010160028082fe").unwrap();
+
+		assert_eq!(run(code), vec![true, false, false, true]);
+	}
+
+	#[test]
+	fn eof_instructions_move_tags() {
+		// PUSH1 0 PUSH1 0 PUSH1 0 DATACOPY PUSH1 1 RJUMPI 0 PUSH0 CALLDATALOAD
+		// PUSH1 1 PUSH1 2 DUPN 2 SWAPN 1 EXCHANGE 0x00 STOP
+		let code = hex::decode(concat!(
+			"ef00010100040200010019ff000000008000",
+			"04600060006000d36001e100005f3560016002e602e701e80000"
+		))
+		.unwrap();
+		assert!(eof::validate(&code).is_ok());
+
+		assert_eq!(run(code), vec![true, false, true, false]);
+	}
+}
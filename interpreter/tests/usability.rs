@@ -1,13 +1,14 @@
-use std::rc::Rc;
+use std::sync::Arc;
 
 use evm_interpreter::{
-	error::{CallCreateTrap, Capture, ExitError, ExitSucceed},
+	eof,
+	error::{CallCreateTrap, Capture, ExitError, ExitException, ExitSucceed},
 	etable::{Control, Etable},
 	machine::Machine,
 	opcode::Opcode,
 	runtime::{
-		Context, Log, RuntimeBackend, RuntimeBaseBackend, RuntimeEnvironment, RuntimeState,
-		SetCodeOrigin, TransactionContext,
+		BlockEnv, Context, Log, RuntimeBackend, RuntimeBaseBackend, RuntimeBlockHashes,
+		RuntimeEnvironment, RuntimeState, SetCodeOrigin, TransactionContext,
 	},
 	EtableInterpreter, RunInterpreter,
 };
@@ -30,7 +31,7 @@ fn etable_wrap() {
 		}
 	});
 
-	let machine = Machine::new(Rc::new(code), Rc::new(data), 1024, 10000, ());
+	let machine = Machine::new(Arc::new(code), Arc::new(data), 1024, 10000, ());
 	let mut vm = EtableInterpreter::new(machine, &wrapped_etable);
 	let result = vm.run(&mut ());
 	assert_eq!(result, Capture::Exit(Ok(ExitSucceed::Returned)));
@@ -60,7 +61,7 @@ fn etable_wrap2() {
 		},
 	);
 
-	let machine = Machine::new(Rc::new(code), Rc::new(data), 1024, 10000, ());
+	let machine = Machine::new(Arc::new(code), Arc::new(data), 1024, 10000, ());
 	let mut vm = EtableInterpreter::new(machine, &wrapped_etable);
 	let result = vm.run(&mut ());
 	assert_eq!(result, Capture::Trap(Opcode(0x50)));
@@ -68,32 +69,14 @@ fn etable_wrap2() {
 
 pub struct UnimplementedHandler;
 
-impl RuntimeEnvironment for UnimplementedHandler {
+impl RuntimeBlockHashes for UnimplementedHandler {
 	fn block_hash(&self, _number: U256) -> H256 {
 		unimplemented!()
 	}
-	fn block_number(&self) -> U256 {
-		unimplemented!()
-	}
-	fn block_coinbase(&self) -> H160 {
-		unimplemented!()
-	}
-	fn block_timestamp(&self) -> U256 {
-		unimplemented!()
-	}
-	fn block_difficulty(&self) -> U256 {
-		unimplemented!()
-	}
-	fn block_randomness(&self) -> Option<H256> {
-		unimplemented!()
-	}
-	fn block_gas_limit(&self) -> U256 {
-		unimplemented!()
-	}
-	fn block_base_fee_per_gas(&self) -> U256 {
-		unimplemented!()
-	}
-	fn chain_id(&self) -> U256 {
+}
+
+impl RuntimeEnvironment for UnimplementedHandler {
+	fn block_env(&self) -> BlockEnv {
 		unimplemented!()
 	}
 }
@@ -200,8 +183,8 @@ fn etable_runtime() {
 	let mut handler = UnimplementedHandler;
 
 	let machine = Machine::new(
-		Rc::new(code),
-		Rc::new(data),
+		Arc::new(code),
+		Arc::new(data),
 		1024,
 		10000,
 		RuntimeState {
@@ -209,6 +192,7 @@ fn etable_runtime() {
 				address: H160::default(),
 				caller: H160::default(),
 				apparent_value: U256::default(),
+				authorized: None,
 			},
 			transaction_context: TransactionContext {
 				gas_price: U256::default(),
@@ -224,3 +208,384 @@ fn etable_runtime() {
 	assert_eq!(res, Ok(ExitSucceed::Returned));
 	assert_eq!(vm.retval, hex::decode(RET1).unwrap());
 }
+
+pub struct ReadOnlyHandler;
+
+impl RuntimeBlockHashes for ReadOnlyHandler {
+	fn block_hash(&self, _number: U256) -> H256 {
+		H256::default()
+	}
+}
+
+impl RuntimeEnvironment for ReadOnlyHandler {
+	fn block_env(&self) -> BlockEnv {
+		BlockEnv {
+			difficulty: U256::from(0x20000),
+			randomness: Some(H256::repeat_byte(0x44)),
+			chain_id: U256::one(),
+			blob_base_fee: U256::from(7),
+			..Default::default()
+		}
+	}
+	fn blob_hash(&self, index: U256) -> H256 {
+		H256::from_low_u64_be(index.low_u64() + 0x100)
+	}
+}
+
+impl RuntimeBaseBackend for ReadOnlyHandler {
+	fn balance(&self, _address: H160) -> U256 {
+		U256::from(42)
+	}
+	fn code(&self, _address: H160) -> Vec<u8> {
+		Vec::new()
+	}
+	fn storage(&self, _address: H160, _index: H256) -> H256 {
+		H256::default()
+	}
+	fn transient_storage(&self, _address: H160, _index: H256) -> H256 {
+		H256::default()
+	}
+	fn exists(&self, _address: H160) -> bool {
+		false
+	}
+	fn nonce(&self, _address: H160) -> U256 {
+		U256::zero()
+	}
+}
+
+static READ_ONLY_ETABLE: Etable<RuntimeState, ReadOnlyHandler, CallCreateTrap> =
+	Etable::read_only();
+
+#[test]
+fn etable_read_only() {
+	let run = |code: &str| {
+		let machine = Machine::new(
+			Arc::new(hex::decode(code).unwrap()),
+			Arc::new(Vec::new()),
+			1024,
+			10000,
+			RuntimeState {
+				context: Context {
+					address: H160::default(),
+					caller: H160::default(),
+					apparent_value: U256::default(),
+					authorized: None,
+				},
+				transaction_context: TransactionContext {
+					gas_price: U256::default(),
+					origin: H160::default(),
+				}
+				.into(),
+				retbuf: Vec::new(),
+			},
+		);
+		let mut vm = EtableInterpreter::new(machine, &READ_ONLY_ETABLE);
+		let res = vm.run(&mut ReadOnlyHandler).exit().unwrap();
+		(res, vm.retval.clone())
+	};
+
+	// SELFBALANCE PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+	let (res, retval) = run("4760005260206000f3");
+	assert_eq!(res, Ok(ExitSucceed::Returned));
+	assert_eq!(U256::from_big_endian(&retval), U256::from(42));
+
+	// PUSH1 1 BLOBHASH BLOBBASEFEE ADD PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+	let (res, retval) = run("6001494a0160005260206000f3");
+	assert_eq!(res, Ok(ExitSucceed::Returned));
+	assert_eq!(U256::from_big_endian(&retval), U256::from(0x108));
+
+	// PUSH1 1 PUSH1 0 SSTORE
+	let (res, _) = run("6001600055");
+	assert!(res.is_err());
+}
+
+#[test]
+fn prevrandao() {
+	let run = |prevrandao: bool| {
+		// DIFFICULTY PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+		let mut machine = Machine::new(
+			Arc::new(hex::decode("4460005260206000f3").unwrap()),
+			Arc::new(Vec::new()),
+			1024,
+			10000,
+			RuntimeState {
+				context: Context {
+					address: H160::default(),
+					caller: H160::default(),
+					apparent_value: U256::default(),
+					authorized: None,
+				},
+				transaction_context: TransactionContext {
+					gas_price: U256::default(),
+					origin: H160::default(),
+				}
+				.into(),
+				retbuf: Vec::new(),
+			},
+		);
+		if prevrandao {
+			machine = machine.with_prevrandao();
+		}
+		let name = Opcode::DIFFICULTY.name_with_prevrandao(machine.prevrandao());
+		let mut vm = EtableInterpreter::new(machine, &READ_ONLY_ETABLE);
+		let res = vm.run(&mut ReadOnlyHandler).exit().unwrap();
+		assert_eq!(res, Ok(ExitSucceed::Returned));
+		(name, vm.retval.clone())
+	};
+
+	let (name, retval) = run(false);
+	assert_eq!(name, Some("DIFFICULTY"));
+	assert_eq!(U256::from_big_endian(&retval), U256::from(0x20000));
+
+	let (name, retval) = run(true);
+	assert_eq!(name, Some("PREVRANDAO"));
+	assert_eq!(retval, H256::repeat_byte(0x44).as_bytes());
+}
+
+#[test]
+fn etable_step_position() {
+	use evm_interpreter::StepInterpreter;
+
+	let etable = Etable::<(), (), ()>::core();
+	// PUSH1 1 PUSH1 2 ADD STOP
+	let machine = Machine::new(
+		Arc::new(hex::decode("600160020100").unwrap()),
+		Arc::new(Vec::new()),
+		1024,
+		10000,
+		(),
+	);
+	let mut vm = EtableInterpreter::new(machine, &etable);
+
+	assert_eq!(vm.position(), 0);
+	assert_eq!(vm.peek_opcode(), Some(Opcode::PUSH1));
+	vm.step(&mut ()).unwrap();
+	vm.step(&mut ()).unwrap();
+	assert_eq!(vm.position(), 4);
+	assert_eq!(vm.peek_opcode(), Some(Opcode::ADD));
+	assert_eq!(vm.machine().stack.len(), 2);
+
+	vm.step(&mut ()).unwrap();
+	assert!(vm.step(&mut ()).is_err());
+	assert!(vm.is_exited());
+	assert_eq!(vm.peek_opcode(), None);
+}
+
+#[test]
+fn eof_relative_jumps() {
+	let etable = Etable::<(), (), ()>::core();
+	let run = |code: Vec<u8>, eof: bool| {
+		let mut machine = Machine::new(
+			Arc::new(code.clone()),
+			Arc::new(Vec::new()),
+			1024,
+			10000,
+			(),
+		);
+		if eof {
+			machine = machine.with_eof(eof::layout(&code).unwrap());
+		}
+		let mut vm = EtableInterpreter::new(machine, &etable);
+		let result = vm.run(&mut ());
+		(result, vm.retval.clone())
+	};
+
+	// PUSH1 2 RJUMPV [0, 0] (out of range, falls through)
+	// PUSH1 1 RJUMPI 5 (taken) PUSH1 0 RJUMP 2 PUSH1 42
+	// PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+	let code = "6002e201000000006001e100056000e00002602a60005260206000f3";
+	let container = hex::decode(format!("ef0001010004020001001cff00000000800002{}", code)).unwrap();
+	assert_eq!(eof::validate(&container).map(|_| ()), Ok(()));
+
+	let (result, retval) = run(container, true);
+	assert_eq!(result, Capture::Exit(Ok(ExitSucceed::Returned)));
+	assert_eq!(U256::from_big_endian(&retval), U256::from(42));
+
+	// Relative jumps are undefined in legacy code.
+	let (result, _) = run(hex::decode("e0000000").unwrap(), false);
+	assert_eq!(
+		result,
+		Capture::Exit(Err(ExitException::InvalidOpcode(Opcode::RJUMP).into()))
+	);
+}
+
+#[test]
+fn eof_functions() {
+	let etable = Etable::<(), (), ()>::core();
+	let run = |container: &str| {
+		let code = hex::decode(container).unwrap();
+		assert_eq!(eof::validate(&code).map(|_| ()), Ok(()));
+		let layout = eof::layout(&code).unwrap();
+		let machine =
+			Machine::new(Arc::new(code), Arc::new(Vec::new()), 1024, 10000, ()).with_eof(layout);
+		let mut vm = EtableInterpreter::new(machine, &etable);
+		let result = vm.run(&mut ());
+		(result, vm.retval.clone(), vm.return_stack().len())
+	};
+
+	// Section 0: CALLF 1 JUMPF 2
+	// Section 1: PUSH1 42 RETF
+	// Section 2: PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+	let (result, retval, depth) = run(
+		"ef000101000c020003000600030008ff000000008000010001000101800002\
+		 e30001e50002602ae460005260206000f3",
+	);
+	assert_eq!(result, Capture::Exit(Ok(ExitSucceed::Returned)));
+	assert_eq!(U256::from_big_endian(&retval), U256::from(42));
+	assert_eq!(depth, 0);
+
+	// Section 0: CALLF 1 STOP
+	// Section 1: CALLF 1 RETF
+	let (result, _, depth) =
+		run("ef000101000802000200040004ff0000000080000100010001e3000100e30001e4");
+	assert_eq!(
+		result,
+		Capture::Exit(Err(ExitException::CallTooDeep.into()))
+	);
+	assert_eq!(depth, 1024);
+}
+
+#[test]
+fn eof_data_section() {
+	let etable = Etable::<(), (), ()>::core();
+	let data: Vec<u8> = (1..=40).collect();
+
+	// DATALOADN 0 PUSH1 0 MSTORE PUSH1 16 DATALOAD PUSH1 32 MSTORE
+	// DATASIZE PUSH1 64 MSTORE PUSH1 16 PUSH1 0 PUSH1 96 DATACOPY
+	// PUSH1 128 PUSH1 0 RETURN
+	let code = [
+		hex::decode(
+			"ef0001010004020001001cff00280000800003\
+			 d100006000526010d0602052d2604052601060006060d360806000f3",
+		)
+		.unwrap(),
+		data.clone(),
+	]
+	.concat();
+	assert_eq!(eof::validate(&code).map(|_| ()), Ok(()));
+
+	let layout = eof::layout(&code).unwrap();
+	let machine =
+		Machine::new(Arc::new(code), Arc::new(Vec::new()), 1024, 10000, ()).with_eof(layout);
+	let mut vm = EtableInterpreter::new(machine, &etable);
+	let result = vm.run(&mut ());
+	assert_eq!(result, Capture::Exit(Ok(ExitSucceed::Returned)));
+
+	let mut expected = data[..32].to_vec();
+	expected.extend_from_slice(&data[16..]);
+	expected.extend_from_slice(&[0; 8]);
+	expected.extend_from_slice(H256::from_low_u64_be(40).as_bytes());
+	expected.extend_from_slice(&data[..16]);
+	expected.extend_from_slice(&[0; 16]);
+	assert_eq!(vm.retval, expected);
+}
+
+#[test]
+fn eof_stack_operations() {
+	let etable = Etable::<(), (), ()>::core();
+
+	// PUSH1 1 PUSH1 2 PUSH1 3 PUSH1 4 EXCHANGE 0x01 SWAPN 1 DUPN 3
+	// PUSH0 MSTORE PUSH1 32 MSTORE PUSH1 64 MSTORE PUSH1 96 MSTORE
+	// PUSH1 128 MSTORE PUSH1 160 PUSH0 RETURN
+	let code = hex::decode(
+		"ef00010100040200010020ff00000000800006\
+		 6001600260036004e801e701e6035f52602052604052606052608052\
+		 60a05ff3",
+	)
+	.unwrap();
+	assert_eq!(eof::validate(&code).map(|_| ()), Ok(()));
+
+	let layout = eof::layout(&code).unwrap();
+	let machine =
+		Machine::new(Arc::new(code), Arc::new(Vec::new()), 1024, 10000, ()).with_eof(layout);
+	let mut vm = EtableInterpreter::new(machine, &etable);
+	let result = vm.run(&mut ());
+	assert_eq!(result, Capture::Exit(Ok(ExitSucceed::Returned)));
+
+	let words = vm
+		.retval
+		.chunks(32)
+		.map(|word| U256::from_big_endian(word).as_u64())
+		.collect::<Vec<_>>();
+	assert_eq!(words, [3, 2, 1, 4, 3]);
+}
+
+#[test]
+fn machine_dump() {
+	// PUSH1 1 PUSH2 0x0203 STOP
+	let mut machine = Machine::new(
+		Arc::new(hex::decode("600161020300").unwrap()),
+		Arc::new(Vec::new()),
+		1024,
+		10000,
+		RuntimeState {
+			context: Context {
+				address: H160::default(),
+				caller: H160::default(),
+				apparent_value: U256::default(),
+				authorized: None,
+			},
+			transaction_context: TransactionContext {
+				gas_price: U256::default(),
+				origin: H160::default(),
+			}
+			.into(),
+			retbuf: Vec::new(),
+		},
+	);
+	machine.stack.push(H256::from_low_u64_be(1)).unwrap();
+
+	let dump = machine.dump(2).to_string();
+	assert!(dump.starts_with("pc: 2, instruction: PUSH2 0x0203\n"));
+	assert!(dump.contains("memory: 0 bytes"));
+	assert!(dump.ends_with(&format!(
+		"stack: 1 items, from the top\n  0: {:?}",
+		H256::from_low_u64_be(1)
+	)));
+	assert!(machine.dump(6).to_string().contains("(end of code)"));
+}
+
+#[test]
+fn memory_hexdump_and_stack_top() {
+	let mut memory = evm_interpreter::machine::Memory::new(10000);
+	memory.resize_end(U256::from(40)).unwrap();
+	memory.set(1, &[0xab, 0xcd], None).unwrap();
+	let dump = memory.hexdump(0..100).to_string();
+	let lines = dump.lines().collect::<Vec<_>>();
+	// Clamped to the effective length of 64 bytes.
+	assert_eq!(lines.len(), 2);
+	assert!(lines[0].starts_with("00000000: 00 ab cd 00"));
+	assert!(lines[1].starts_with("00000020: "));
+	assert_eq!(memory.hexdump(0..0).to_string(), "");
+
+	let mut stack = evm_interpreter::machine::Stack::new(1024);
+	stack.push(H256::from_low_u64_be(1)).unwrap();
+	stack.push(H256::from_low_u64_be(2)).unwrap();
+	assert_eq!(
+		stack.display_top(1).to_string(),
+		format!(
+			"stack: 2 items, from the top\n  0: {:?}\n  ...",
+			H256::from_low_u64_be(2)
+		)
+	);
+}
+
+#[cfg(feature = "invariants")]
+#[test]
+#[should_panic(expected = "program counter")]
+fn invariants_catch_corrupted_program_counter() {
+	let code = hex::decode("6001600201").unwrap();
+	let etable = Etable::<_, _, Opcode>::core().wrap(|f, opcode_t| {
+		move |machine, handle, opcode, position| {
+			if opcode_t == Opcode::ADD {
+				Control::ContinueN(2)
+			} else {
+				f(machine, handle, opcode, position)
+			}
+		}
+	});
+
+	let machine = Machine::new(Arc::new(code), Arc::new(Vec::new()), 1024, 10000, ());
+	let mut vm = EtableInterpreter::new(machine, &etable);
+	let _ = vm.run(&mut ());
+}
@@ -0,0 +1,90 @@
+use std::{
+	collections::BTreeMap,
+	fs::{self, File},
+	io::BufReader,
+};
+
+use evm::interpreter::eof;
+
+use crate::{
+	error::{Error, TestError},
+	types::{EofTestMulti, EofTestVector, TestCompletionStatus},
+};
+
+/// Run EOF validation tests for specific json file with debug flag
+fn run_file(filename: &str, debug: bool) -> Result<TestCompletionStatus, Error> {
+	let test_multi: BTreeMap<String, EofTestMulti> =
+		serde_json::from_reader(BufReader::new(File::open(filename)?))?;
+	let mut tests_status = TestCompletionStatus::default();
+
+	for (test_name, test_multi) in test_multi {
+		for (vector_name, vector) in &test_multi.vectors {
+			print!("{} | {}/{}: ", filename, test_name, vector_name);
+			match run_vector(vector, debug) {
+				Ok(()) => {
+					tests_status.inc_completed();
+					println!("ok")
+				}
+				Err(Error::UnsupportedContainerKind) => {
+					tests_status.inc_skipped();
+					println!("skipped")
+				}
+				Err(err) => {
+					println!("ERROR: {:?}", err);
+					return Err(err);
+				}
+			}
+		}
+
+		tests_status.print_completion();
+	}
+
+	Ok(tests_status)
+}
+
+/// Run EOF validation tests for single json file or directory, including its
+/// subdirectories
+pub fn run_single(filename: &str, debug: bool) -> Result<TestCompletionStatus, Error> {
+	if fs::metadata(filename)?.is_dir() {
+		let mut tests_status = TestCompletionStatus::default();
+
+		for filename in fs::read_dir(filename)? {
+			let filepath = filename?.path();
+			let filename = filepath.to_str().ok_or(Error::NonUtf8Filename)?;
+			tests_status += run_single(filename, debug)?;
+		}
+		tests_status.print_total_for_dir(filename);
+		Ok(tests_status)
+	} else if filename.ends_with(".json") {
+		run_file(filename, debug)
+	} else {
+		Ok(TestCompletionStatus::default())
+	}
+}
+
+/// Run single EOF validation vector
+pub fn run_vector(vector: &EofTestVector, debug: bool) -> Result<(), Error> {
+	// Init code containers have rules of their own, which are not validated.
+	if vector.container_kind.as_deref() == Some("INITCODE") {
+		return Err(Error::UnsupportedContainerKind);
+	}
+
+	let result = eof::validate(&vector.code.0).map(|_| ());
+	for (fork, expected) in &vector.results {
+		if result.is_ok() != expected.result {
+			if debug {
+				println!(
+					"fork: {}, code: 0x{}, result: {:?}, expected: {:?}",
+					fork,
+					hex::encode(&vector.code.0),
+					result,
+					expected.exception
+				);
+			}
+
+			return Err(TestError::ValidityMismatch.into());
+		}
+	}
+
+	Ok(())
+}
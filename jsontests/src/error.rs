@@ -5,6 +5,8 @@ pub enum TestError {
 	StateMismatch,
 	#[error("expect error, but got okay")]
 	ExpectException,
+	#[error("container validity is different")]
+	ValidityMismatch,
 }
 
 #[allow(clippy::upper_case_acronyms)]
@@ -18,6 +20,8 @@ pub enum Error {
 	EVM(#[from] evm::interpreter::error::ExitError),
 	#[error("unsupported fork")]
 	UnsupportedFork,
+	#[error("unsupported container kind")]
+	UnsupportedContainerKind,
 	#[error("non-utf8 filename")]
 	NonUtf8Filename,
 	#[error("test error")]
@@ -1,6 +1,5 @@
 use evm::interpreter::utils::h256_to_u256;
 use primitive_types::{H256, U256};
-use sha3::{Digest, Keccak256};
 
 use crate::in_memory::InMemoryBackend;
 
@@ -77,12 +76,11 @@ pub fn state_root(backend: &InMemoryBackend) -> H256 {
 					.map(|(k, v)| (k, rlp::encode(&h256_to_u256(*v)))),
 			);
 
-			let code_hash = H256::from_slice(&Keccak256::digest(&account.code));
 			let account = TrieAccount {
 				nonce: account.nonce,
 				balance: account.balance,
 				storage_root,
-				code_hash,
+				code_hash: account.code_hash,
 				code_version: U256::zero(),
 			};
 
@@ -1,14 +1,16 @@
 use std::collections::BTreeMap;
 
 use evm::{
-	backend::OverlayedChangeSet,
-	interpreter::runtime::{RuntimeBaseBackend, RuntimeEnvironment},
+	backend::{BlockHashRing, OverlayedChangeSet},
+	interpreter::runtime::{BlockEnv, RuntimeBaseBackend, RuntimeBlockHashes, RuntimeEnvironment},
+	standard::BlobSchedule,
 };
 use primitive_types::{H160, H256, U256};
+use sha3::{Digest, Keccak256};
 
 #[derive(Clone, Debug)]
 pub struct InMemoryEnvironment {
-	pub block_hashes: BTreeMap<U256, H256>,
+	pub block_hashes: BlockHashRing,
 	pub block_number: U256,
 	pub block_coinbase: H160,
 	pub block_timestamp: U256,
@@ -17,17 +19,126 @@ pub struct InMemoryEnvironment {
 	pub block_gas_limit: U256,
 	pub block_base_fee_per_gas: U256,
 	pub chain_id: U256,
+	pub block_excess_blob_gas: Option<U256>,
+	pub block_blob_base_fee: Option<U256>,
 }
 
-#[derive(Clone, Debug, Default)]
+impl Default for InMemoryEnvironment {
+	fn default() -> Self {
+		Self {
+			block_hashes: BlockHashRing::new(U256::zero()),
+			block_number: U256::zero(),
+			block_coinbase: H160::default(),
+			block_timestamp: U256::zero(),
+			block_difficulty: U256::zero(),
+			block_randomness: None,
+			block_gas_limit: U256::from(30_000_000u64),
+			block_base_fee_per_gas: U256::zero(),
+			chain_id: U256::one(),
+			block_excess_blob_gas: None,
+			block_blob_base_fee: None,
+		}
+	}
+}
+
+impl InMemoryEnvironment {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Set the hash of one of the 256 blocks before the block number, which
+	/// must be set first.
+	pub fn with_block_hash(mut self, number: U256, hash: H256) -> Self {
+		self.block_hashes.insert(number, hash);
+		self
+	}
+
+	pub fn with_block_number(mut self, block_number: U256) -> Self {
+		self.block_number = block_number;
+		if block_number < self.block_hashes.current() {
+			self.block_hashes = BlockHashRing::new(block_number);
+		} else {
+			self.block_hashes.advance_to(block_number);
+		}
+		self
+	}
+
+	pub fn with_block_coinbase(mut self, block_coinbase: H160) -> Self {
+		self.block_coinbase = block_coinbase;
+		self
+	}
+
+	pub fn with_block_timestamp(mut self, block_timestamp: U256) -> Self {
+		self.block_timestamp = block_timestamp;
+		self
+	}
+
+	pub fn with_block_difficulty(mut self, block_difficulty: U256) -> Self {
+		self.block_difficulty = block_difficulty;
+		self
+	}
+
+	/// Set `PREVRANDAO`. It replaces the difficulty from the merge on.
+	pub fn with_block_randomness(mut self, block_randomness: H256) -> Self {
+		self.block_randomness = Some(block_randomness);
+		self
+	}
+
+	pub fn with_block_gas_limit(mut self, block_gas_limit: U256) -> Self {
+		self.block_gas_limit = block_gas_limit;
+		self
+	}
+
+	pub fn with_block_base_fee_per_gas(mut self, block_base_fee_per_gas: U256) -> Self {
+		self.block_base_fee_per_gas = block_base_fee_per_gas;
+		self
+	}
+
+	pub fn with_chain_id(mut self, chain_id: U256) -> Self {
+		self.chain_id = chain_id;
+		self
+	}
+
+	/// Set the excess blob gas, and the blob base fee derived from it with
+	/// the blob schedule of the fork.
+	pub fn with_block_excess_blob_gas(
+		mut self,
+		block_excess_blob_gas: U256,
+		schedule: &BlobSchedule,
+	) -> Self {
+		self.block_excess_blob_gas = Some(block_excess_blob_gas);
+		self.block_blob_base_fee = Some(schedule.blob_base_fee(block_excess_blob_gas));
+		self
+	}
+}
+
+/// Account of an [InMemoryBackend]. The code itself is stored once per hash
+/// in [InMemoryBackend::codes].
+#[derive(Clone, Debug)]
 pub struct InMemoryAccount {
 	pub balance: U256,
-	pub code: Vec<u8>,
+	pub code_hash: H256,
 	pub nonce: U256,
 	pub storage: BTreeMap<H256, H256>,
 	pub transient_storage: BTreeMap<H256, H256>,
 }
 
+impl Default for InMemoryAccount {
+	fn default() -> Self {
+		Self {
+			balance: U256::zero(),
+			code_hash: code_hash(&[]),
+			nonce: U256::zero(),
+			storage: BTreeMap::new(),
+			transient_storage: BTreeMap::new(),
+		}
+	}
+}
+
+fn code_hash(code: &[u8]) -> H256 {
+	H256::from_slice(&Keccak256::digest(code))
+}
+
 #[derive(Clone, Debug)]
 pub struct InMemorySuicideInfo {
 	pub address: H160,
@@ -37,16 +148,48 @@ pub struct InMemorySuicideInfo {
 pub struct InMemoryBackend {
 	pub environment: InMemoryEnvironment,
 	pub state: BTreeMap<H160, InMemoryAccount>,
+	/// Non-empty codes by hash, shared by all accounts with the same code.
+	pub codes: BTreeMap<H256, Vec<u8>>,
 }
 
 impl InMemoryBackend {
+	pub fn new(environment: InMemoryEnvironment) -> Self {
+		Self {
+			environment,
+			state: BTreeMap::new(),
+			codes: BTreeMap::new(),
+		}
+	}
+
+	/// Store a code, returning its hash. Identical codes are only stored once.
+	pub fn insert_code(&mut self, code: Vec<u8>) -> H256 {
+		let hash = code_hash(&code);
+		if !code.is_empty() {
+			self.codes.entry(hash).or_insert(code);
+		}
+		hash
+	}
+
+	/// Code of an account.
+	pub fn account_code(&self, account: &InMemoryAccount) -> &[u8] {
+		self.codes
+			.get(&account.code_hash)
+			.map(|code| &code[..])
+			.unwrap_or_default()
+	}
+
 	pub fn apply_overlayed(&mut self, changeset: &OverlayedChangeSet) {
+		for address in changeset.deletes.clone() {
+			self.state.remove(&address);
+		}
+
 		for (address, balance) in changeset.balances.clone() {
 			self.state.entry(address).or_default().balance = balance;
 		}
 
 		for (address, code) in changeset.codes.clone() {
-			self.state.entry(address).or_default().code = code;
+			let code_hash = self.insert_code(code);
+			self.state.entry(address).or_default().code_hash = code_hash;
 		}
 
 		for (address, nonce) in changeset.nonces.clone() {
@@ -76,52 +219,28 @@ impl InMemoryBackend {
 				account.transient_storage.insert(key, value);
 			}
 		}
-
-		for address in changeset.deletes.clone() {
-			self.state.remove(&address);
-		}
 	}
 }
 
-impl RuntimeEnvironment for InMemoryBackend {
+impl RuntimeBlockHashes for InMemoryBackend {
 	fn block_hash(&self, number: U256) -> H256 {
-		self.environment
-			.block_hashes
-			.get(&number)
-			.cloned()
-			.unwrap_or(H256::default())
-	}
-
-	fn block_number(&self) -> U256 {
-		self.environment.block_number
-	}
-
-	fn block_coinbase(&self) -> H160 {
-		self.environment.block_coinbase
-	}
-
-	fn block_timestamp(&self) -> U256 {
-		self.environment.block_timestamp
-	}
-
-	fn block_difficulty(&self) -> U256 {
-		self.environment.block_difficulty
-	}
-
-	fn block_randomness(&self) -> Option<H256> {
-		self.environment.block_randomness
-	}
-
-	fn block_gas_limit(&self) -> U256 {
-		self.environment.block_gas_limit
-	}
-
-	fn block_base_fee_per_gas(&self) -> U256 {
-		self.environment.block_base_fee_per_gas
+		self.environment.block_hashes.block_hash(number)
 	}
+}
 
-	fn chain_id(&self) -> U256 {
-		self.environment.chain_id
+impl RuntimeEnvironment for InMemoryBackend {
+	fn block_env(&self) -> BlockEnv {
+		BlockEnv {
+			number: self.environment.block_number,
+			coinbase: self.environment.block_coinbase,
+			timestamp: self.environment.block_timestamp,
+			difficulty: self.environment.block_difficulty,
+			randomness: self.environment.block_randomness,
+			gas_limit: self.environment.block_gas_limit,
+			base_fee_per_gas: self.environment.block_base_fee_per_gas,
+			chain_id: self.environment.chain_id,
+			blob_base_fee: self.environment.block_blob_base_fee.unwrap_or_default(),
+		}
 	}
 }
 
@@ -134,12 +253,18 @@ impl RuntimeBaseBackend for InMemoryBackend {
 			.balance
 	}
 
+	fn code_hash(&self, address: H160) -> H256 {
+		self.state
+			.get(&address)
+			.map(|account| account.code_hash)
+			.unwrap_or_else(|| code_hash(&[]))
+	}
+
 	fn code(&self, address: H160) -> Vec<u8> {
 		self.state
 			.get(&address)
-			.cloned()
-			.unwrap_or(Default::default())
-			.code
+			.map(|account| self.account_code(account).to_vec())
+			.unwrap_or_default()
 	}
 
 	fn exists(&self, address: H160) -> bool {
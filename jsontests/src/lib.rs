@@ -1,3 +1,4 @@
+pub mod eof;
 pub mod error;
 pub mod hash;
 pub mod in_memory;
@@ -81,3 +82,10 @@ fn sqt_eip_2930() {
 	let tests_status = run::run_single(JSON_FILENAME, false).unwrap();
 	tests_status.print_total();
 }
+
+#[test]
+fn eof_validation() {
+	const JSON_FILENAME: &str = "res/ethtests/EOFTests/";
+	let tests_status = eof::run_single(JSON_FILENAME, false).unwrap();
+	tests_status.print_total();
+}
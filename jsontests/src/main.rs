@@ -1,3 +1,4 @@
+mod eof;
 mod error;
 mod hash;
 mod in_memory;
@@ -15,6 +16,10 @@ struct Cli {
 
 	#[arg(short, long, default_value_t = false)]
 	debug: bool,
+
+	/// Run EOF validation tests instead of state tests.
+	#[arg(long, default_value_t = false)]
+	eof: bool,
 }
 
 fn main() -> Result<(), Error> {
@@ -22,7 +27,11 @@ fn main() -> Result<(), Error> {
 
 	let mut tests_status = TestCompletionStatus::default();
 	for filename in cli.filenames {
-		tests_status += run::run_single(&filename, cli.debug)?;
+		tests_status += if cli.eof {
+			eof::run_single(&filename, cli.debug)?
+		} else {
+			run::run_single(&filename, cli.debug)?
+		};
 	}
 	tests_status.print_total();
 
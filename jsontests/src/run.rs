@@ -6,8 +6,8 @@ use std::{
 
 use evm::{
 	backend::OverlayedBackend,
-	interpreter::{error::Capture, runtime::GasState, utils::u256_to_h256, Interpreter},
-	standard::{Config, Etable, EtableResolver, Invoker, TransactArgs},
+	interpreter::{error::Capture, runtime::GasState, utils::u256_to_h256},
+	standard::{Config, Etable, EtableResolver, Invoker, TransactArgs, TransactKind},
 };
 use evm_precompile::StandardPrecompileSet;
 use primitive_types::U256;
@@ -113,42 +113,46 @@ pub fn run_test(
 		return Ok(());
 	}
 
-	let env = InMemoryEnvironment {
-		block_hashes: BTreeMap::new(), // TODO: fill in this field.
-		block_number: test.env.current_number,
-		block_coinbase: test.env.current_coinbase,
-		block_timestamp: test.env.current_timestamp,
-		block_difficulty: test.env.current_difficulty,
-		block_randomness: Some(test.env.current_random),
-		block_gas_limit: test.env.current_gas_limit,
-		block_base_fee_per_gas: test.transaction.gas_price,
-		chain_id: U256::zero(), // TODO: fill in this field.
-	};
+	// TODO: fill in the chain ID.
+	let mut env = InMemoryEnvironment::new()
+		.with_block_number(test.env.current_number)
+		.with_block_coinbase(test.env.current_coinbase)
+		.with_block_timestamp(test.env.current_timestamp)
+		.with_block_difficulty(test.env.current_difficulty)
+		.with_block_randomness(test.env.current_random)
+		.with_block_gas_limit(test.env.current_gas_limit)
+		.with_block_base_fee_per_gas(test.transaction.gas_price)
+		.with_chain_id(U256::zero());
+	if let Some(number) = test.env.current_number.checked_sub(U256::one()) {
+		env = env.with_block_hash(number, test.env.previous_hash);
+	}
+	if let (Some(excess_blob_gas), Some(schedule)) =
+		(test.env.current_excess_blob_gas, &config.blob_schedule)
+	{
+		env = env.with_block_excess_blob_gas(excess_blob_gas, schedule);
+	}
 
-	let state = test
-		.pre
-		.clone()
-		.into_iter()
-		.map(|(address, account)| {
-			let storage = account
-				.storage
-				.into_iter()
-				.filter(|(_, value)| *value != U256::zero())
-				.map(|(key, value)| (u256_to_h256(key), u256_to_h256(value)))
-				.collect::<BTreeMap<_, _>>();
-
-			(
-				address,
-				InMemoryAccount {
-					balance: account.balance,
-					code: account.code.0,
-					nonce: account.nonce,
-					storage,
-					transient_storage: Default::default(),
-				},
-			)
-		})
-		.collect::<BTreeMap<_, _>>();
+	let mut base_backend = InMemoryBackend::new(env);
+	for (address, account) in test.pre.clone() {
+		let storage = account
+			.storage
+			.into_iter()
+			.filter(|(_, value)| *value != U256::zero())
+			.map(|(key, value)| (u256_to_h256(key), u256_to_h256(value)))
+			.collect::<BTreeMap<_, _>>();
+
+		let code_hash = base_backend.insert_code(account.code.0);
+		base_backend.state.insert(
+			address,
+			InMemoryAccount {
+				balance: account.balance,
+				code_hash,
+				nonce: account.nonce,
+				storage,
+				transient_storage: Default::default(),
+			},
+		);
+	}
 
 	let gas_etable = Etable::single(evm::standard::eval_gasometer);
 	let exec_etable = Etable::runtime();
@@ -169,6 +173,9 @@ pub fn run_test(
 			.into_iter()
 			.map(|access| (access.address, access.storage_keys))
 			.collect(),
+		authorization_list: Vec::new(),
+		blob_hashes: Vec::new(),
+		kind: TransactKind::Normal,
 	};
 
 	let initial_accessed = {
@@ -179,17 +186,16 @@ pub fn run_test(
 		hots
 	};
 
-	let base_backend = InMemoryBackend {
-		environment: env,
-		state,
-	};
-
-	let mut run_backend = OverlayedBackend::new(&base_backend, initial_accessed.clone());
 	let mut step_backend = OverlayedBackend::new(&base_backend, initial_accessed.clone());
 
 	// Run
-	let run_result = evm::transact(args.clone(), Some(4), &mut run_backend, &invoker);
-	let run_changeset = run_backend.deconstruct().1;
+	let (run_result, run_changeset) = evm::dry_run(
+		args.clone(),
+		Some(4),
+		&base_backend,
+		initial_accessed.clone(),
+		&invoker,
+	);
 	let mut run_backend = base_backend.clone();
 	run_backend.apply_overlayed(&run_changeset);
 
@@ -235,7 +241,7 @@ pub fn run_test(
 					address,
 					account.balance,
 					account.nonce,
-					hex::encode(&account.code),
+					hex::encode(run_backend.account_code(account)),
 					account.storage
 				);
 			}
@@ -153,6 +153,8 @@ pub struct TestEnv {
 	pub current_beacon_root: H256,
 	pub current_coinbase: H160,
 	pub current_difficulty: U256,
+	#[serde(default)]
+	pub current_excess_blob_gas: Option<U256>,
 	pub current_gas_limit: U256,
 	pub current_number: U256,
 	pub current_random: H256,
@@ -249,6 +251,31 @@ pub struct TestTransaction {
 	pub access_list: Vec<TestAccessListItem>,
 }
 
+/// `EofTestMulti` represents raw data from an EOF validation test file. Each
+/// vector is a container with its expected validity.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
+pub struct EofTestMulti {
+	pub vectors: BTreeMap<String, EofTestVector>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EofTestVector {
+	pub code: HexBytes,
+	/// `INITCODE` for containers validated as init code, runtime otherwise.
+	#[serde(default)]
+	pub container_kind: Option<String>,
+	/// Expected validity, by fork name.
+	pub results: BTreeMap<String, EofTestResult>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
+pub struct EofTestResult {
+	pub result: bool,
+	#[serde(default)]
+	pub exception: Option<String>,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
 pub struct HexBytes(#[serde(deserialize_with = "deserialize_hex_bytes")] pub Vec<u8>);
 
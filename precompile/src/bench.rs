@@ -0,0 +1,168 @@
+//! Harness measuring the execution time of precompiles against the gas they
+//! charge, for pricing custom precompiles.
+//!
+//! Run [benchmark_all] in release mode on the reference hardware of the
+//! chain, then check the samples with [underpriced]. A sensible reference
+//! throughput is the one of [ECRecover](crate::ECRecover), which is known to
+//! be fairly priced.
+
+use std::time::{Duration, Instant};
+
+use evm::{
+	interpreter::{error::ExitError, runtime::GasState},
+	GasMutState,
+};
+use primitive_types::U256;
+
+use crate::{
+	Blake2F, Bn128Add, Bn128Mul, Bn128Pairing, ECRecover, Identity, Modexp, PurePrecompile,
+	Ripemd160, Sha256,
+};
+
+/// Gasometer recording the charged gas, without limit.
+#[derive(Clone, Debug, Default)]
+pub struct BenchGasometer {
+	used: U256,
+	refunded: u64,
+}
+
+impl BenchGasometer {
+	/// Gas charged so far.
+	pub fn used_gas(&self) -> U256 {
+		self.used
+	}
+}
+
+impl GasState for BenchGasometer {
+	fn gas(&self) -> U256 {
+		U256::MAX - self.used
+	}
+}
+
+impl GasMutState for BenchGasometer {
+	fn record_gas(&mut self, gas: U256) -> Result<(), ExitError> {
+		self.used = self.used.saturating_add(gas);
+		Ok(())
+	}
+
+	fn refunded_gas(&self) -> u64 {
+		self.refunded
+	}
+
+	fn record_refund(&mut self, refund: i64) {
+		if refund >= 0 {
+			self.refunded = self.refunded.saturating_add(refund as u64);
+		} else {
+			self.refunded = self.refunded.saturating_sub(refund.unsigned_abs());
+		}
+	}
+}
+
+/// Measurement of a precompile on one input.
+#[derive(Clone, Debug)]
+pub struct BenchSample {
+	/// Name of the precompile.
+	pub name: &'static str,
+	/// Input length.
+	pub input_len: usize,
+	/// Gas charged.
+	pub gas: U256,
+	/// Average execution time.
+	pub time: Duration,
+	/// Whether the execution succeeded.
+	pub succeeded: bool,
+}
+
+impl BenchSample {
+	/// Execution time per unit of gas, in nanoseconds. Infinite if no gas is
+	/// charged.
+	pub fn nanos_per_gas(&self) -> f64 {
+		if self.gas.is_zero() {
+			return f64::INFINITY;
+		}
+
+		let gas = if self.gas > U256::from(u64::MAX) {
+			u64::MAX
+		} else {
+			self.gas.as_u64()
+		};
+		self.time.as_nanos() as f64 / gas as f64
+	}
+}
+
+/// Measure a precompile on each input, averaging over `iterations` runs.
+pub fn benchmark<P: PurePrecompile<BenchGasometer>>(
+	name: &'static str,
+	precompile: &P,
+	inputs: &[Vec<u8>],
+	iterations: u32,
+) -> Vec<BenchSample> {
+	let iterations = iterations.max(1);
+
+	inputs
+		.iter()
+		.map(|input| {
+			let mut gasometer = BenchGasometer::default();
+			let (result, _) = precompile.execute(input, &mut gasometer);
+
+			let start = Instant::now();
+			for _ in 0..iterations {
+				let _ = precompile.execute(input, &mut BenchGasometer::default());
+			}
+
+			BenchSample {
+				name,
+				input_len: input.len(),
+				gas: gasometer.used_gas(),
+				time: start.elapsed() / iterations,
+				succeeded: result.is_ok(),
+			}
+		})
+		.collect()
+}
+
+/// Pseudo-random inputs of the given sizes, for input-size sweeps.
+pub fn inputs(sizes: &[usize]) -> Vec<Vec<u8>> {
+	let mut state = 0x9e37_79b9_7f4a_7c15u64;
+	sizes
+		.iter()
+		.map(|size| {
+			(0..*size)
+				.map(|_| {
+					state ^= state << 13;
+					state ^= state >> 7;
+					state ^= state << 17;
+					state as u8
+				})
+				.collect()
+		})
+		.collect()
+}
+
+/// Measure all standard precompiles on the given inputs.
+pub fn benchmark_all(inputs: &[Vec<u8>], iterations: u32) -> Vec<BenchSample> {
+	let mut samples = Vec::new();
+	samples.extend(benchmark("ecrecover", &ECRecover, inputs, iterations));
+	samples.extend(benchmark("sha256", &Sha256, inputs, iterations));
+	samples.extend(benchmark("ripemd160", &Ripemd160, inputs, iterations));
+	samples.extend(benchmark("identity", &Identity, inputs, iterations));
+	samples.extend(benchmark("modexp", &Modexp, inputs, iterations));
+	samples.extend(benchmark("bn128_add", &Bn128Add, inputs, iterations));
+	samples.extend(benchmark("bn128_mul", &Bn128Mul, inputs, iterations));
+	samples.extend(benchmark(
+		"bn128_pairing",
+		&Bn128Pairing,
+		inputs,
+		iterations,
+	));
+	samples.extend(benchmark("blake2f", &Blake2F, inputs, iterations));
+	samples
+}
+
+/// Samples slower than `max_nanos_per_gas`, which are under-priced.
+pub fn underpriced(samples: &[BenchSample], max_nanos_per_gas: f64) -> Vec<&BenchSample> {
+	samples
+		.iter()
+		.filter(|sample| sample.nanos_per_gas() > max_nanos_per_gas)
+		.collect()
+}
@@ -15,6 +15,8 @@ macro_rules! try_some {
 
 extern crate alloc;
 
+#[cfg(feature = "std")]
+pub mod bench;
 mod blake2;
 mod bn128;
 mod modexp;
@@ -105,3 +107,19 @@ const fn address(last: u8) -> H160 {
 		0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, last,
 	])
 }
+
+// Precompile sets are only read during execution, and are shared by
+// executions running on any thread.
+const _: fn() = || {
+	fn assert_send_sync<T: Send + Sync>() {}
+	assert_send_sync::<StandardPrecompileSet>();
+	assert_send_sync::<ECRecover>();
+	assert_send_sync::<Sha256>();
+	assert_send_sync::<Ripemd160>();
+	assert_send_sync::<Identity>();
+	assert_send_sync::<Modexp>();
+	assert_send_sync::<Bn128Add>();
+	assert_send_sync::<Bn128Mul>();
+	assert_send_sync::<Bn128Pairing>();
+	assert_send_sync::<Blake2F>();
+};
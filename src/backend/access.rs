@@ -0,0 +1,169 @@
+use alloc::{collections::BTreeSet, vec::Vec};
+use core::cell::RefCell;
+
+use evm_interpreter::runtime::{
+	Basic, BlockEnv, RuntimeBaseBackend, RuntimeBlockHashes, RuntimeEnvironment,
+};
+use primitive_types::{H160, H256, U256};
+
+/// State access recorded by an [AccessRecorder].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StateAccess {
+	/// Any account field: balance, nonce, code or existence.
+	Account(H160),
+	/// A storage slot.
+	Storage(H160, H256),
+	/// A block hash.
+	BlockHash(U256),
+}
+
+/// Backend wrapper recording the order in which state is first accessed, as
+/// prefetch hints or scheduling metadata for later executions of similar
+/// transactions.
+///
+/// Each account, slot and block hash is only recorded on its first access.
+/// Like [WitnessRecorder](crate::backend::WitnessRecorder), it is meant to
+/// wrap the base backend under an
+/// [OverlayedBackend](crate::backend::OverlayedBackend), so that only the
+/// pre-state that is actually read is recorded.
+pub struct AccessRecorder<B> {
+	backend: B,
+	accesses: RefCell<Vec<StateAccess>>,
+	seen: RefCell<BTreeSet<StateAccess>>,
+}
+
+impl<B> AccessRecorder<B> {
+	/// Wrap a backend.
+	pub fn new(backend: B) -> Self {
+		Self {
+			backend,
+			accesses: RefCell::new(Vec::new()),
+			seen: RefCell::new(BTreeSet::new()),
+		}
+	}
+
+	/// Inner backend.
+	pub fn inner(&self) -> &B {
+		&self.backend
+	}
+
+	/// Accesses recorded so far, in order.
+	pub fn accesses(&self) -> Vec<StateAccess> {
+		self.accesses.borrow().clone()
+	}
+
+	/// Deconstruct into the inner backend and the recorded accesses.
+	pub fn deconstruct(self) -> (B, Vec<StateAccess>) {
+		(self.backend, self.accesses.into_inner())
+	}
+
+	fn record(&self, access: StateAccess) {
+		if self.seen.borrow_mut().insert(access) {
+			self.accesses.borrow_mut().push(access);
+		}
+	}
+}
+
+impl<B: RuntimeBlockHashes> RuntimeBlockHashes for AccessRecorder<B> {
+	fn block_hash(&self, number: U256) -> H256 {
+		self.record(StateAccess::BlockHash(number));
+		self.backend.block_hash(number)
+	}
+}
+
+impl<B: RuntimeEnvironment> RuntimeEnvironment for AccessRecorder<B> {
+	fn block_env(&self) -> BlockEnv {
+		self.backend.block_env()
+	}
+
+	fn blob_hash(&self, index: U256) -> H256 {
+		self.backend.blob_hash(index)
+	}
+
+	fn recover_signer(&self, message: H256, y_parity: bool, r: H256, s: H256) -> Option<H160> {
+		self.backend.recover_signer(message, y_parity, r, s)
+	}
+}
+
+impl<B: RuntimeBaseBackend> RuntimeBaseBackend for AccessRecorder<B> {
+	fn balance(&self, address: H160) -> U256 {
+		self.record(StateAccess::Account(address));
+		self.backend.balance(address)
+	}
+
+	fn code_size(&self, address: H160) -> U256 {
+		self.record(StateAccess::Account(address));
+		self.backend.code_size(address)
+	}
+
+	fn code_hash(&self, address: H160) -> H256 {
+		self.record(StateAccess::Account(address));
+		self.backend.code_hash(address)
+	}
+
+	fn code(&self, address: H160) -> Vec<u8> {
+		self.record(StateAccess::Account(address));
+		self.backend.code(address)
+	}
+
+	fn code_slice(&self, address: H160, offset: usize, len: usize) -> Vec<u8> {
+		self.record(StateAccess::Account(address));
+		self.backend.code_slice(address, offset, len)
+	}
+
+	fn storage(&self, address: H160, index: H256) -> H256 {
+		self.record(StateAccess::Storage(address, index));
+		self.backend.storage(address, index)
+	}
+
+	fn transient_storage(&self, address: H160, index: H256) -> H256 {
+		self.backend.transient_storage(address, index)
+	}
+
+	fn exists(&self, address: H160) -> bool {
+		self.record(StateAccess::Account(address));
+		self.backend.exists(address)
+	}
+
+	fn nonce(&self, address: H160) -> U256 {
+		self.record(StateAccess::Account(address));
+		self.backend.nonce(address)
+	}
+
+	fn basic(&self, address: H160) -> Basic {
+		self.record(StateAccess::Account(address));
+		self.backend.basic(address)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::backend::ChaosBackend;
+
+	#[test]
+	fn first_accesses_in_order() {
+		let a = H160::repeat_byte(0xaa);
+		let b = H160::repeat_byte(0xbb);
+		let index = H256::repeat_byte(0x01);
+
+		let recorder = AccessRecorder::new(ChaosBackend::new(1));
+		recorder.balance(b);
+		recorder.storage(a, index);
+		recorder.nonce(b);
+		recorder.code(a);
+		recorder.storage(a, index);
+		recorder.block_hash(U256::one());
+
+		assert_eq!(
+			recorder.accesses(),
+			vec![
+				StateAccess::Account(b),
+				StateAccess::Storage(a, index),
+				StateAccess::Account(a),
+				StateAccess::BlockHash(U256::one()),
+			]
+		);
+	}
+}
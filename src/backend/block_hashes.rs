@@ -0,0 +1,188 @@
+use alloc::{vec, vec::Vec};
+
+use evm_interpreter::runtime::RuntimeBlockHashes;
+use primitive_types::{H256, U256};
+
+/// Number of recent block hashes available to the `BLOCKHASH` opcode.
+pub const BLOCK_HASH_HISTORY: usize = 256;
+
+/// Ring buffer of the most recent [BLOCK_HASH_HISTORY] block hashes, with
+/// constant time lookup.
+///
+/// Hashes of blocks outside of the window, as well as of the current and
+/// future blocks, are reported as zero, and so are hashes of blocks in the
+/// window that were never set. With the `serde` feature, the ring is
+/// serialized as the current block number and the hashes of the window,
+/// oldest first.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(from = "BlockHashWindow", into = "BlockHashWindow")
+)]
+pub struct BlockHashRing {
+	current: U256,
+	hashes: Vec<H256>,
+}
+
+impl BlockHashRing {
+	/// Create an empty ring with the given current block number.
+	pub fn new(current: U256) -> Self {
+		Self {
+			current,
+			hashes: vec![H256::default(); BLOCK_HASH_HISTORY],
+		}
+	}
+
+	/// Create a ring with the given current block number, from the hashes of
+	/// the blocks right before it, oldest first. Only the last
+	/// [BLOCK_HASH_HISTORY] hashes are kept.
+	pub fn from_hashes<I: IntoIterator<Item = H256>>(current: U256, hashes: I) -> Self {
+		let hashes = hashes.into_iter().collect::<Vec<_>>();
+		let window = if current < U256::from(BLOCK_HASH_HISTORY) {
+			current.as_usize()
+		} else {
+			BLOCK_HASH_HISTORY
+		};
+		let skip = hashes.len().saturating_sub(window);
+		let first = current - U256::from(hashes.len() - skip);
+
+		let mut ring = Self::new(first);
+		for hash in hashes.into_iter().skip(skip) {
+			ring.push(hash);
+		}
+		ring.advance_to(current);
+		ring
+	}
+
+	/// Current block number, whose hash is not yet known.
+	pub fn current(&self) -> U256 {
+		self.current
+	}
+
+	/// Push the hash of the current block, and move on to the next block.
+	pub fn push(&mut self, hash: H256) {
+		let index = Self::index(self.current);
+		self.hashes[index] = hash;
+		self.current = self.current.saturating_add(U256::one());
+	}
+
+	/// Move on to a later current block, whose hash is not yet known. Hashes
+	/// of the skipped blocks are unknown, and can be set with
+	/// [BlockHashRing::insert].
+	pub fn advance_to(&mut self, current: U256) {
+		while self.current < current {
+			if current - self.current >= U256::from(BLOCK_HASH_HISTORY) {
+				self.hashes.fill(H256::default());
+				self.current = current;
+			} else {
+				self.push(H256::default());
+			}
+		}
+	}
+
+	/// Set the hash of a block in the window. Hashes of other blocks are
+	/// ignored.
+	pub fn insert(&mut self, number: U256, hash: H256) {
+		if self.in_window(number) {
+			self.hashes[Self::index(number)] = hash;
+		}
+	}
+
+	/// Blocks of the window, oldest first, with their hashes.
+	pub fn hashes(&self) -> Vec<(U256, H256)> {
+		let len = core::cmp::min(self.current, U256::from(BLOCK_HASH_HISTORY));
+		let first = self.current - len;
+		(0..len.as_usize())
+			.map(|offset| {
+				let number = first + U256::from(offset);
+				(number, self.hashes[Self::index(number)])
+			})
+			.collect()
+	}
+
+	fn in_window(&self, number: U256) -> bool {
+		number < self.current && self.current - number <= U256::from(BLOCK_HASH_HISTORY)
+	}
+
+	fn index(number: U256) -> usize {
+		(number % U256::from(BLOCK_HASH_HISTORY)).as_usize()
+	}
+}
+
+impl RuntimeBlockHashes for BlockHashRing {
+	fn block_hash(&self, number: U256) -> H256 {
+		if !self.in_window(number) {
+			return H256::default();
+		}
+
+		self.hashes[Self::index(number)]
+	}
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct BlockHashWindow {
+	current: U256,
+	hashes: Vec<H256>,
+}
+
+#[cfg(feature = "serde")]
+impl From<BlockHashWindow> for BlockHashRing {
+	fn from(window: BlockHashWindow) -> Self {
+		Self::from_hashes(window.current, window.hashes)
+	}
+}
+
+#[cfg(feature = "serde")]
+impl From<BlockHashRing> for BlockHashWindow {
+	fn from(ring: BlockHashRing) -> Self {
+		Self {
+			current: ring.current,
+			hashes: ring.hashes().into_iter().map(|(_, hash)| hash).collect(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn lookup_window() {
+		let mut ring = BlockHashRing::new(U256::from(10));
+		for i in 10..300u64 {
+			ring.push(H256::from_low_u64_be(i));
+		}
+		assert_eq!(ring.current(), U256::from(300));
+
+		assert_eq!(ring.block_hash(U256::from(299)), H256::from_low_u64_be(299));
+		assert_eq!(ring.block_hash(U256::from(44)), H256::from_low_u64_be(44));
+		assert_eq!(ring.block_hash(U256::from(43)), H256::default());
+		assert_eq!(ring.block_hash(U256::from(300)), H256::default());
+	}
+
+	#[test]
+	fn rebuild_from_hashes() {
+		let hashes = (0..300u64).map(H256::from_low_u64_be).collect::<Vec<_>>();
+		let ring = BlockHashRing::from_hashes(U256::from(300), hashes);
+		assert_eq!(ring.hashes().len(), BLOCK_HASH_HISTORY);
+		assert_eq!(
+			ring.hashes()[0],
+			(U256::from(44), H256::from_low_u64_be(44))
+		);
+		assert_eq!(
+			BlockHashRing::from_hashes(ring.current(), ring.hashes().into_iter().map(|(_, h)| h)),
+			ring
+		);
+
+		let mut ring = ring;
+		ring.advance_to(U256::from(310));
+		assert_eq!(ring.block_hash(U256::from(299)), H256::from_low_u64_be(299));
+		assert_eq!(ring.block_hash(U256::from(305)), H256::default());
+		ring.insert(U256::from(305), H256::repeat_byte(1));
+		ring.insert(U256::from(310), H256::repeat_byte(2));
+		assert_eq!(ring.block_hash(U256::from(305)), H256::repeat_byte(1));
+		assert_eq!(ring.block_hash(U256::from(310)), H256::default());
+	}
+}
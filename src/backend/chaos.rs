@@ -0,0 +1,264 @@
+use alloc::vec::Vec;
+
+use evm_interpreter::runtime::{
+	BlockEnv, RuntimeBaseBackend, RuntimeBlockHashes, RuntimeEnvironment,
+};
+use primitive_types::{H160, H256, U256};
+use sha3::{Digest, Keccak256};
+
+/// Failures injected by a [ChaosBackend].
+#[derive(Clone, Debug, Default)]
+pub struct ChaosFailures {
+	/// Chance, out of 1000, that a read returns an extreme value instead, such
+	/// as `U256::MAX` for balances and nonces, or empty code.
+	pub extreme_rate: u16,
+	/// Whether `code_size` and `code_hash` disagree with `code`.
+	pub inconsistent_code: bool,
+	/// Whether accounts with balance, nonce or code may report that they do
+	/// not exist.
+	pub phantom_accounts: bool,
+}
+
+/// Backend returning pseudo-random, but seed-deterministic, account, storage
+/// and block data, for fuzzing the executor against unexpected backend
+/// responses.
+///
+/// Every read is derived from the seed and its arguments only, so the same
+/// query always returns the same value regardless of the execution order. It
+/// is meant to be used as the base of an
+/// [OverlayedBackend](crate::backend::OverlayedBackend).
+#[derive(Clone, Debug)]
+pub struct ChaosBackend {
+	seed: u64,
+	max_code_len: usize,
+	failures: ChaosFailures,
+}
+
+impl ChaosBackend {
+	/// Create a new chaos backend from the seed, without injected failures.
+	pub fn new(seed: u64) -> Self {
+		Self {
+			seed,
+			max_code_len: 64,
+			failures: ChaosFailures::default(),
+		}
+	}
+
+	/// Set the maximum length of generated code.
+	pub fn with_max_code_len(mut self, max_code_len: usize) -> Self {
+		self.max_code_len = max_code_len;
+		self
+	}
+
+	/// Set the injected failures.
+	pub fn with_failures(mut self, failures: ChaosFailures) -> Self {
+		self.failures = failures;
+		self
+	}
+
+	fn random(&self, domain: u8, address: H160, index: H256) -> H256 {
+		let mut hasher = Keccak256::new();
+		hasher.update(self.seed.to_be_bytes());
+		hasher.update([domain]);
+		hasher.update(address.as_bytes());
+		hasher.update(index.as_bytes());
+		H256::from_slice(hasher.finalize().as_slice())
+	}
+
+	fn random_u64(&self, domain: u8, address: H160, index: H256) -> u64 {
+		let mut bytes = [0u8; 8];
+		bytes.copy_from_slice(&self.random(domain, address, index)[..8]);
+		u64::from_be_bytes(bytes)
+	}
+
+	fn extreme(&self, domain: u8, address: H160, index: H256) -> bool {
+		let rate = u64::from(self.failures.extreme_rate);
+		rate > 0 && self.random_u64(domain | 0x80, address, index) % 1000 < rate
+	}
+
+	fn block_value(&self, domain: u8) -> H256 {
+		self.random(domain, H160::default(), H256::default())
+	}
+}
+
+const BALANCE: u8 = 0x01;
+const NONCE: u8 = 0x02;
+const CODE: u8 = 0x03;
+const CODE_LEN: u8 = 0x04;
+const CODE_HASH: u8 = 0x05;
+const STORAGE: u8 = 0x06;
+const EXISTS: u8 = 0x07;
+const BLOCK_HASH: u8 = 0x10;
+const BLOCK_NUMBER: u8 = 0x11;
+const BLOCK_COINBASE: u8 = 0x12;
+const BLOCK_TIMESTAMP: u8 = 0x13;
+const BLOCK_DIFFICULTY: u8 = 0x14;
+const BLOCK_RANDOMNESS: u8 = 0x15;
+const BLOCK_GAS_LIMIT: u8 = 0x16;
+const BLOCK_BASE_FEE: u8 = 0x17;
+const CHAIN_ID: u8 = 0x18;
+
+impl RuntimeBlockHashes for ChaosBackend {
+	fn block_hash(&self, number: U256) -> H256 {
+		let mut index = H256::default();
+		number.to_big_endian(&mut index[..]);
+		self.random(BLOCK_HASH, H160::default(), index)
+	}
+}
+
+impl RuntimeEnvironment for ChaosBackend {
+	fn block_env(&self) -> BlockEnv {
+		BlockEnv {
+			number: self.block_number(),
+			coinbase: self.block_coinbase(),
+			timestamp: self.block_timestamp(),
+			difficulty: self.block_difficulty(),
+			randomness: self.block_randomness(),
+			gas_limit: self.block_gas_limit(),
+			base_fee_per_gas: self.block_base_fee_per_gas(),
+			chain_id: self.chain_id(),
+			blob_base_fee: U256::zero(),
+		}
+	}
+
+	fn block_number(&self) -> U256 {
+		U256::from(self.random_u64(BLOCK_NUMBER, H160::default(), H256::default()) >> 32)
+	}
+
+	fn block_coinbase(&self) -> H160 {
+		self.block_value(BLOCK_COINBASE).into()
+	}
+
+	fn block_timestamp(&self) -> U256 {
+		U256::from(self.random_u64(BLOCK_TIMESTAMP, H160::default(), H256::default()) >> 24)
+	}
+
+	fn block_difficulty(&self) -> U256 {
+		U256::from_big_endian(&self.block_value(BLOCK_DIFFICULTY)[..])
+	}
+
+	fn block_randomness(&self) -> Option<H256> {
+		Some(self.block_value(BLOCK_RANDOMNESS))
+	}
+
+	fn block_gas_limit(&self) -> U256 {
+		U256::from(self.random_u64(BLOCK_GAS_LIMIT, H160::default(), H256::default()) >> 34)
+	}
+
+	fn block_base_fee_per_gas(&self) -> U256 {
+		U256::from(self.random_u64(BLOCK_BASE_FEE, H160::default(), H256::default()) >> 30)
+	}
+
+	fn chain_id(&self) -> U256 {
+		U256::from(self.random_u64(CHAIN_ID, H160::default(), H256::default()))
+	}
+}
+
+impl RuntimeBaseBackend for ChaosBackend {
+	fn balance(&self, address: H160) -> U256 {
+		if self.extreme(BALANCE, address, H256::default()) {
+			return U256::MAX;
+		}
+
+		U256::from(self.random_u64(BALANCE, address, H256::default()))
+	}
+
+	fn code_size(&self, address: H160) -> U256 {
+		if self.failures.inconsistent_code {
+			U256::from(self.random_u64(CODE_LEN, address, H256::default()) % 0x6000)
+		} else {
+			U256::from(self.code(address).len())
+		}
+	}
+
+	fn code_hash(&self, address: H160) -> H256 {
+		if self.failures.inconsistent_code {
+			self.random(CODE_HASH, address, H256::default())
+		} else {
+			H256::from_slice(&Keccak256::digest(&self.code(address)[..]))
+		}
+	}
+
+	fn code(&self, address: H160) -> Vec<u8> {
+		if self.max_code_len == 0 || self.extreme(CODE, address, H256::default()) {
+			return Vec::new();
+		}
+
+		let len =
+			self.random_u64(CODE_LEN, address, H256::default()) as usize % (self.max_code_len + 1);
+		let mut code = Vec::with_capacity(len);
+		let mut chunk = 0u64;
+		while code.len() < len {
+			let mut index = H256::default();
+			index[..8].copy_from_slice(&chunk.to_be_bytes());
+			let bytes = self.random(CODE, address, index);
+			let remaining = len - code.len();
+			code.extend_from_slice(&bytes[..core::cmp::min(32, remaining)]);
+			chunk += 1;
+		}
+		code
+	}
+
+	fn storage(&self, address: H160, index: H256) -> H256 {
+		let value = self.random(STORAGE, address, index);
+		// Half of the slots are empty, to exercise zero transitions.
+		if value[0] & 1 == 0 {
+			H256::default()
+		} else {
+			value
+		}
+	}
+
+	fn transient_storage(&self, _address: H160, _index: H256) -> H256 {
+		H256::default()
+	}
+
+	fn exists(&self, address: H160) -> bool {
+		let exists = self.random(EXISTS, address, H256::default())[0] & 1 == 1;
+		if self.failures.phantom_accounts {
+			exists
+		} else {
+			exists
+				|| !self.balance(address).is_zero()
+				|| !self.nonce(address).is_zero()
+				|| !self.code(address).is_empty()
+		}
+	}
+
+	fn nonce(&self, address: H160) -> U256 {
+		if self.extreme(NONCE, address, H256::default()) {
+			return U256::MAX;
+		}
+
+		U256::from(self.random_u64(NONCE, address, H256::default()) >> 48)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn reads_are_seed_deterministic() {
+		let address = H160::repeat_byte(0x11);
+		let index = H256::repeat_byte(0x22);
+
+		let a = ChaosBackend::new(1);
+		let b = ChaosBackend::new(1);
+		let c = ChaosBackend::new(2);
+
+		assert_eq!(a.balance(address), b.balance(address));
+		assert_eq!(a.code(address), b.code(address));
+		assert_eq!(a.storage(address, index), b.storage(address, index));
+		assert_eq!(a.block_hash(U256::one()), b.block_hash(U256::one()));
+		assert_ne!(a.block_coinbase(), c.block_coinbase());
+		assert_eq!(a.code_size(address), U256::from(a.code(address).len()));
+
+		let extreme = ChaosBackend::new(1).with_failures(ChaosFailures {
+			extreme_rate: 1000,
+			..Default::default()
+		});
+		assert_eq!(extreme.balance(address), U256::MAX);
+		assert!(extreme.code(address).is_empty());
+	}
+}
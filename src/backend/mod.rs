@@ -11,11 +11,23 @@
 //! pushing/poping layers are dealt by extern functions), layers are handled
 //! internally inside a backend.
 
+mod access;
+mod block_hashes;
+mod chaos;
 mod overlayed;
+mod witness;
 
-pub use evm_interpreter::runtime::{RuntimeBackend, RuntimeBaseBackend, RuntimeEnvironment};
+pub use evm_interpreter::runtime::{
+	RuntimeBackend, RuntimeBaseBackend, RuntimeBlockHashes, RuntimeEnvironment,
+};
 
-pub use self::overlayed::{OverlayedBackend, OverlayedChangeSet};
+pub use self::{
+	access::{AccessRecorder, StateAccess},
+	block_hashes::{BlockHashRing, BLOCK_HASH_HISTORY},
+	chaos::{ChaosBackend, ChaosFailures},
+	overlayed::{OverlayedBackend, OverlayedChangeSet},
+	witness::{ExecutionWitness, WitnessAccount, WitnessBackend, WitnessRecorder},
+};
 
 /// Backend with layers that can transactionally be committed or discarded.
 pub trait TransactionalBackend {
@@ -3,17 +3,29 @@ use alloc::{
 	collections::{BTreeMap, BTreeSet},
 	vec::Vec,
 };
-use core::mem;
+use core::{cell::RefCell, mem, ops::RangeInclusive};
 
 use evm_interpreter::{
 	error::{ExitError, ExitException},
-	runtime::{Log, RuntimeBackend, RuntimeBaseBackend, RuntimeEnvironment, SetCodeOrigin},
+	runtime::{
+		Basic, BlockEnv, Log, RuntimeBackend, RuntimeBaseBackend, RuntimeBlockHashes,
+		RuntimeEnvironment, SelfDestruct, SetCodeOrigin,
+	},
 };
 use primitive_types::{H160, H256, U256};
+use sha3::{Digest, Keccak256};
 
 use crate::{backend::TransactionalBackend, MergeStrategy};
 
-#[derive(Clone, Debug)]
+/// Changes of an [OverlayedBackend], to be applied to the underlying state.
+///
+/// With the `scale` feature, it is SCALE encoded field by field, in
+/// declaration order, so that it can be passed across a runtime boundary.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(
+	feature = "scale",
+	derive(scale_codec::Encode, scale_codec::Decode, scale_info::TypeInfo)
+)]
 pub struct OverlayedChangeSet {
 	pub logs: Vec<Log>,
 	pub balances: BTreeMap<H160, U256>,
@@ -22,13 +34,115 @@ pub struct OverlayedChangeSet {
 	pub storage_resets: BTreeSet<H160>,
 	pub storages: BTreeMap<(H160, H256), H256>,
 	pub transient_storage: BTreeMap<(H160, H256), H256>,
+	/// Deleted accounts, removed before the other changes are applied, which
+	/// recreate them.
 	pub deletes: BTreeSet<H160>,
+	/// Executed `SELFDESTRUCT`s, in execution order.
+	pub selfdestructs: Vec<SelfDestruct>,
+}
+
+impl OverlayedChangeSet {
+	/// Merge change sets applied one after the other into a single one,
+	/// whose application has the same effect.
+	pub fn merge_all<I: IntoIterator<Item = Self>>(changesets: I) -> Self {
+		let mut merged = Self::default();
+		for changeset in changesets {
+			merged.merge(changeset);
+		}
+		merged
+	}
+
+	/// Merge a change set applied after this one. Later writes win. An
+	/// account deleted here and modified again in `later` is recreated empty,
+	/// with its storage reset, before the modifications are applied.
+	pub fn merge(&mut self, later: Self) {
+		for address in &later.deletes {
+			self.forget_account(*address);
+			self.storage_resets.remove(address);
+		}
+
+		for address in &later.storage_resets {
+			remove_account_storage(&mut self.storages, *address);
+		}
+
+		let recreated = self
+			.deletes
+			.iter()
+			.copied()
+			.filter(|address| later.touches(*address))
+			.collect::<Vec<_>>();
+		for address in recreated {
+			self.deletes.remove(&address);
+			self.forget_account(address);
+			self.balances.insert(address, U256::zero());
+			self.codes.insert(address, Vec::new());
+			self.nonces.insert(address, U256::zero());
+			self.storage_resets.insert(address);
+		}
+
+		self.logs.extend(later.logs);
+		self.balances.extend(later.balances);
+		self.codes.extend(later.codes);
+		self.nonces.extend(later.nonces);
+		self.storage_resets.extend(later.storage_resets);
+		self.storages.extend(later.storages);
+		self.transient_storage.extend(later.transient_storage);
+		self.deletes.extend(later.deletes);
+		self.selfdestructs.extend(later.selfdestructs);
+	}
+
+	fn touches(&self, address: H160) -> bool {
+		self.balances.contains_key(&address)
+			|| self.codes.contains_key(&address)
+			|| self.nonces.contains_key(&address)
+			|| self.storage_resets.contains(&address)
+			|| self
+				.storages
+				.range(account_storage(address))
+				.next()
+				.is_some()
+	}
+
+	fn forget_account(&mut self, address: H160) {
+		self.balances.remove(&address);
+		self.codes.remove(&address);
+		self.nonces.remove(&address);
+		remove_account_storage(&mut self.storages, address);
+	}
+}
+
+/// Range of the keys of all storage slots of an account, in maps keyed by
+/// `(address, index)`.
+fn account_storage(address: H160) -> RangeInclusive<(H160, H256)> {
+	(address, H256::zero())..=(address, H256::repeat_byte(0xff))
+}
+
+/// Remove the storage slots of an account from a map keyed by
+/// `(address, index)`, without visiting the slots of other accounts.
+fn remove_account_storage<V>(storages: &mut BTreeMap<(H160, H256), V>, address: H160) {
+	let keys = storages
+		.range(account_storage(address))
+		.map(|(key, _)| *key)
+		.collect::<Vec<_>>();
+	for key in keys {
+		storages.remove(&key);
+	}
 }
 
 pub struct OverlayedBackend<B> {
 	backend: B,
 	substate: Box<Substate>,
+	/// Accounts deleted by previous transactions.
+	deletes: BTreeSet<H160>,
+	/// Addresses and storage slots warm at the start of every transaction.
+	initial_accessed: BTreeSet<(H160, Option<H256>)>,
 	accessed: BTreeSet<(H160, Option<H256>)>,
+	code_hashes: RefCell<BTreeMap<H160, H256>>,
+	/// Caches of code and code sizes read from the backend, which the overlay
+	/// never changes, for contracts accessed repeatedly in a transaction. They
+	/// are cleared with [OverlayedBackend::begin_transaction].
+	backend_codes: RefCell<BTreeMap<H160, Vec<u8>>>,
+	backend_code_sizes: RefCell<BTreeMap<H160, U256>>,
 }
 
 impl<B> OverlayedBackend<B> {
@@ -36,11 +150,90 @@ impl<B> OverlayedBackend<B> {
 		Self {
 			backend,
 			substate: Box::new(Substate::new()),
+			deletes: Default::default(),
+			initial_accessed: accessed.clone(),
 			accessed,
+			code_hashes: Default::default(),
+			backend_codes: Default::default(),
+			backend_code_sizes: Default::default(),
 		}
 	}
 
-	pub fn deconstruct(self) -> (B, OverlayedChangeSet) {
+	/// Mark the given addresses as warm for the current transaction, in
+	/// addition to precompiles and transaction targets that are warmed by the
+	/// invoker.
+	pub fn warm_addresses<I: IntoIterator<Item = H160>>(&mut self, addresses: I) {
+		for address in addresses {
+			self.accessed.insert((address, None));
+		}
+	}
+
+	/// Mark the given storage slots as warm for the current transaction, in
+	/// addition to those in the transaction access list.
+	pub fn warm_storages<I: IntoIterator<Item = (H160, H256)>>(&mut self, storages: I) {
+		for (address, index) in storages {
+			self.accessed.insert((address, Some(index)));
+		}
+	}
+
+	/// Start a new transaction on the same overlay, for block building.
+	///
+	/// Committed state changes are kept, while transient storage, deletable
+	/// accounts and cached backend code are cleared, and the accessed set is
+	/// reset to the one given to [OverlayedBackend::new]. Logs emitted by the
+	/// previous transaction are returned. Must not be called in the middle of
+	/// a transaction.
+	pub fn begin_transaction(&mut self) -> Vec<Log> {
+		assert!(
+			self.substate.parent.is_none(),
+			"begin transaction with pending substate"
+		);
+
+		self.settle_deletes();
+		self.accessed.clone_from(&self.initial_accessed);
+		self.backend_codes.get_mut().clear();
+		self.backend_code_sizes.get_mut().clear();
+		self.substate.deletables.clear();
+		self.clear_transient_storage();
+		mem::take(&mut self.substate.logs)
+	}
+
+	/// Drop the changes of the current transaction to the accounts it deleted,
+	/// which all precede the deletion, and move the deletions out of the
+	/// substate, so that changes of later transactions recreate the accounts.
+	fn settle_deletes(&mut self) {
+		let deletes = mem::take(&mut self.substate.deletes);
+		let code_hashes = self.code_hashes.get_mut();
+		for address in &deletes {
+			self.substate.forget_account(*address);
+			code_hashes.remove(address);
+		}
+		self.deletes.extend(deletes);
+	}
+
+	/// Transient storage slots written in the current transaction, with their
+	/// current values. Writes of pending nested calls are included, while
+	/// those of reverted calls are not.
+	pub fn transient_storage_writes(&self) -> BTreeMap<(H160, H256), H256> {
+		let mut writes = BTreeMap::new();
+		self.substate.collect_transient_storage(&mut writes);
+		writes
+	}
+
+	/// Clear transient storage at the end of a transaction. Must not be called
+	/// in the middle of a transaction.
+	pub fn clear_transient_storage(&mut self) {
+		assert!(
+			self.substate.parent.is_none(),
+			"clear transient storage with pending substate"
+		);
+
+		self.substate.transient_storage.clear();
+	}
+
+	pub fn deconstruct(mut self) -> (B, OverlayedChangeSet) {
+		self.settle_deletes();
+
 		(
 			self.backend,
 			OverlayedChangeSet {
@@ -51,47 +244,52 @@ impl<B> OverlayedBackend<B> {
 				storage_resets: self.substate.storage_resets,
 				storages: self.substate.storages,
 				transient_storage: self.substate.transient_storage,
-				deletes: self.substate.deletes,
+				deletes: self.deletes,
+				selfdestructs: self.substate.selfdestructs,
 			},
 		)
 	}
 }
 
-impl<B: RuntimeEnvironment> RuntimeEnvironment for OverlayedBackend<B> {
-	fn block_hash(&self, number: U256) -> H256 {
-		self.backend.block_hash(number)
+impl<B: RuntimeBaseBackend> OverlayedBackend<B> {
+	/// Whether an account was deleted by a previous transaction, so that its
+	/// fields unknown to the substate are empty rather than read from the
+	/// backend.
+	fn deleted_before(&self, address: H160) -> bool {
+		self.deletes.contains(&address)
 	}
 
-	fn block_number(&self) -> U256 {
-		self.backend.block_number()
+	/// Storage value at the start of the current transaction, unaffected by
+	/// any changes made in the transaction, including those already
+	/// committed from nested calls.
+	pub fn original_storage(&self, address: H160, index: H256) -> H256 {
+		RuntimeBackend::original_storage(self, address, index)
 	}
 
-	fn block_coinbase(&self) -> H160 {
-		self.backend.block_coinbase()
-	}
-
-	fn block_timestamp(&self) -> U256 {
-		self.backend.block_timestamp()
-	}
-
-	fn block_difficulty(&self) -> U256 {
-		self.backend.block_difficulty()
+	/// Current storage value, including all pending changes of the current
+	/// transaction.
+	pub fn current_storage(&self, address: H160, index: H256) -> H256 {
+		RuntimeBaseBackend::storage(self, address, index)
 	}
+}
 
-	fn block_randomness(&self) -> Option<H256> {
-		self.backend.block_randomness()
+impl<B: RuntimeBlockHashes> RuntimeBlockHashes for OverlayedBackend<B> {
+	fn block_hash(&self, number: U256) -> H256 {
+		self.backend.block_hash(number)
 	}
+}
 
-	fn block_gas_limit(&self) -> U256 {
-		self.backend.block_gas_limit()
+impl<B: RuntimeEnvironment> RuntimeEnvironment for OverlayedBackend<B> {
+	fn block_env(&self) -> BlockEnv {
+		self.backend.block_env()
 	}
 
-	fn block_base_fee_per_gas(&self) -> U256 {
-		self.backend.block_base_fee_per_gas()
+	fn blob_hash(&self, index: U256) -> H256 {
+		self.backend.blob_hash(index)
 	}
 
-	fn chain_id(&self) -> U256 {
-		self.backend.chain_id()
+	fn recover_signer(&self, message: H256, y_parity: bool, r: H256, s: H256) -> Option<H160> {
+		self.backend.recover_signer(message, y_parity, r, s)
 	}
 }
 
@@ -99,25 +297,99 @@ impl<B: RuntimeBaseBackend> RuntimeBaseBackend for OverlayedBackend<B> {
 	fn balance(&self, address: H160) -> U256 {
 		if let Some(balance) = self.substate.known_balance(address) {
 			balance
+		} else if self.deleted_before(address) {
+			U256::zero()
 		} else {
 			self.backend.balance(address)
 		}
 	}
 
+	fn code_size(&self, address: H160) -> U256 {
+		if let Some(code) = self.substate.known_code_ref(address) {
+			return U256::from(code.len());
+		}
+		if self.deleted_before(address) {
+			return U256::zero();
+		}
+		if let Some(code) = self.backend_codes.borrow().get(&address) {
+			return U256::from(code.len());
+		}
+
+		*self
+			.backend_code_sizes
+			.borrow_mut()
+			.entry(address)
+			.or_insert_with(|| self.backend.code_size(address))
+	}
+
+	fn code_hash(&self, address: H160) -> H256 {
+		if let Some(hash) = self.code_hashes.borrow().get(&address) {
+			return *hash;
+		}
+
+		let hash = if let Some(code) = self.substate.known_code_ref(address) {
+			H256::from_slice(&Keccak256::digest(code))
+		} else if self.deleted_before(address) {
+			H256::from_slice(&Keccak256::digest([]))
+		} else {
+			self.backend.code_hash(address)
+		};
+		self.code_hashes.borrow_mut().insert(address, hash);
+		hash
+	}
+
 	fn code(&self, address: H160) -> Vec<u8> {
 		if let Some(code) = self.substate.known_code(address) {
 			code
+		} else if self.deleted_before(address) {
+			Vec::new()
 		} else {
-			self.backend.code(address)
+			self.backend_codes
+				.borrow_mut()
+				.entry(address)
+				.or_insert_with(|| self.backend.code(address))
+				.clone()
+		}
+	}
+
+	fn code_slice(&self, address: H160, offset: usize, len: usize) -> Vec<u8> {
+		let slice = |code: &Vec<u8>| {
+			code.get(offset..)
+				.map(|code| code[..core::cmp::min(len, code.len())].to_vec())
+				.unwrap_or_default()
+		};
+
+		if let Some(code) = self.substate.known_code_ref(address) {
+			slice(code)
+		} else if self.deleted_before(address) {
+			Vec::new()
+		} else if let Some(code) = self.backend_codes.borrow().get(&address) {
+			slice(code)
+		} else {
+			self.backend.code_slice(address, offset, len)
 		}
 	}
 
 	fn storage(&self, address: H160, index: H256) -> H256 {
-		if let Some(value) = self.substate.known_storage(address, index) {
+		if let Some(value) = self.substate.storages.get(&(address, index)) {
+			return *value;
+		}
+		if let Some(value) = self.substate.storage_reads.borrow().get(&(address, index)) {
+			return *value;
+		}
+
+		let value = if let Some(value) = self.substate.known_storage(address, index) {
 			value
+		} else if self.deleted_before(address) {
+			H256::default()
 		} else {
 			self.backend.storage(address, index)
-		}
+		};
+		self.substate
+			.storage_reads
+			.borrow_mut()
+			.insert((address, index), value);
+		value
 	}
 
 	fn transient_storage(&self, address: H160, index: H256) -> H256 {
@@ -131,6 +403,8 @@ impl<B: RuntimeBaseBackend> RuntimeBaseBackend for OverlayedBackend<B> {
 	fn exists(&self, address: H160) -> bool {
 		if let Some(exists) = self.substate.known_exists(address) {
 			exists
+		} else if self.deleted_before(address) {
+			false
 		} else {
 			self.backend.exists(address)
 		}
@@ -139,29 +413,62 @@ impl<B: RuntimeBaseBackend> RuntimeBaseBackend for OverlayedBackend<B> {
 	fn nonce(&self, address: H160) -> U256 {
 		if let Some(nonce) = self.substate.known_nonce(address) {
 			nonce
+		} else if self.deleted_before(address) {
+			U256::zero()
 		} else {
 			self.backend.nonce(address)
 		}
 	}
+
+	fn basic(&self, address: H160) -> Basic {
+		if self.substate.known_exists(address).is_none() && !self.deleted_before(address) {
+			return self.backend.basic(address);
+		}
+
+		Basic {
+			balance: self.balance(address),
+			nonce: self.nonce(address),
+			code_size: self.code_size(address),
+			code_hash: self.code_hash(address),
+		}
+	}
 }
 
 impl<B: RuntimeBaseBackend> RuntimeBackend for OverlayedBackend<B> {
 	fn original_storage(&self, address: H160, index: H256) -> H256 {
-		self.backend.storage(address, index)
+		if let Some(value) = self.substate.root().known_storage(address, index) {
+			value
+		} else if self.deleted_before(address) {
+			H256::default()
+		} else {
+			self.backend.storage(address, index)
+		}
 	}
 
 	fn deleted(&self, address: H160) -> bool {
 		self.substate.deleted(address)
+			|| (self.deleted_before(address) && self.substate.known_exists(address).is_none())
 	}
 
 	fn is_cold(&self, address: H160, index: Option<H256>) -> bool {
 		!self.accessed.contains(&(address, index))
 	}
 
+	fn is_cold_batch(&self, accesses: &[(H160, Option<H256>)]) -> Vec<bool> {
+		accesses
+			.iter()
+			.map(|access| !self.accessed.contains(access))
+			.collect()
+	}
+
 	fn mark_hot(&mut self, address: H160, index: Option<H256>) {
 		self.accessed.insert((address, index));
 	}
 
+	fn mark_hot_batch(&mut self, accesses: &[(H160, Option<H256>)]) {
+		self.accessed.extend(accesses.iter().copied());
+	}
+
 	fn set_storage(&mut self, address: H160, index: H256, value: H256) -> Result<(), ExitError> {
 		self.substate.storages.insert((address, index), value);
 		Ok(())
@@ -188,8 +495,20 @@ impl<B: RuntimeBaseBackend> RuntimeBackend for OverlayedBackend<B> {
 		self.substate.deletes.insert(address);
 	}
 
+	fn record_selfdestruct(&mut self, selfdestruct: SelfDestruct) {
+		self.substate.selfdestructs.push(selfdestruct);
+	}
+
+	fn mark_deletable(&mut self, address: H160) {
+		self.substate.deletables.insert(address);
+	}
+
+	fn deletable(&self, address: H160) -> bool {
+		self.substate.deletable(address)
+	}
+
 	fn reset_storage(&mut self, address: H160) {
-		self.substate.storage_resets.insert(address);
+		self.substate.reset_storage(address);
 	}
 
 	fn set_code(
@@ -199,6 +518,7 @@ impl<B: RuntimeBaseBackend> RuntimeBackend for OverlayedBackend<B> {
 		_origin: SetCodeOrigin,
 	) -> Result<(), ExitError> {
 		self.substate.codes.insert(address, code);
+		self.code_hashes.get_mut().remove(&address);
 		Ok(())
 	}
 
@@ -265,7 +585,7 @@ impl<B: RuntimeBaseBackend> TransactionalBackend for OverlayedBackend<B> {
 					self.substate.nonces.insert(address, nonce);
 				}
 				for address in child.storage_resets {
-					self.substate.storage_resets.insert(address);
+					self.substate.reset_storage(address);
 				}
 				for ((address, key), value) in child.storages {
 					self.substate.storages.insert((address, key), value);
@@ -278,8 +598,15 @@ impl<B: RuntimeBaseBackend> TransactionalBackend for OverlayedBackend<B> {
 				for address in child.deletes {
 					self.substate.deletes.insert(address);
 				}
+				self.substate.selfdestructs.extend(child.selfdestructs);
+				self.substate.deletables.extend(child.deletables);
+			}
+			MergeStrategy::Revert | MergeStrategy::Discard => {
+				let code_hashes = self.code_hashes.get_mut();
+				for address in child.codes.keys() {
+					code_hashes.remove(address);
+				}
 			}
-			MergeStrategy::Revert | MergeStrategy::Discard => {}
 		}
 	}
 }
@@ -294,6 +621,12 @@ struct Substate {
 	storages: BTreeMap<(H160, H256), H256>,
 	transient_storage: BTreeMap<(H160, H256), H256>,
 	deletes: BTreeSet<H160>,
+	selfdestructs: Vec<SelfDestruct>,
+	/// Accounts `SELFDESTRUCT` may delete in the current transaction.
+	deletables: BTreeSet<H160>,
+	/// Cache of storage reads resolved through parents or the backend. It is
+	/// dropped with the substate, so it only lives as long as the frame.
+	storage_reads: RefCell<BTreeMap<(H160, H256), H256>>,
 }
 
 impl Substate {
@@ -308,6 +641,39 @@ impl Substate {
 			storages: Default::default(),
 			transient_storage: Default::default(),
 			deletes: Default::default(),
+			selfdestructs: Vec::new(),
+			deletables: Default::default(),
+			storage_reads: Default::default(),
+		}
+	}
+
+	/// Reset the storage of an account. Storage is tracked as per-slot
+	/// writes on top of a reset flag, so only the slots written or cached in
+	/// this substate are dropped, and the storage of the account is never
+	/// copied. The flag shadows the values of parents and of the backend.
+	fn reset_storage(&mut self, address: H160) {
+		self.storage_resets.insert(address);
+		remove_account_storage(&mut self.storages, address);
+		remove_account_storage(self.storage_reads.get_mut(), address);
+	}
+
+	/// Forget all changes to an account.
+	fn forget_account(&mut self, address: H160) {
+		self.balances.remove(&address);
+		self.codes.remove(&address);
+		self.nonces.remove(&address);
+		self.storage_resets.remove(&address);
+		remove_account_storage(&mut self.storages, address);
+		remove_account_storage(self.storage_reads.get_mut(), address);
+	}
+
+	/// The root substate, which holds changes committed by previous
+	/// transactions. Its state is the transaction-start state.
+	pub fn root(&self) -> &Substate {
+		if let Some(parent) = self.parent.as_ref() {
+			parent.root()
+		} else {
+			self
 		}
 	}
 
@@ -322,10 +688,14 @@ impl Substate {
 	}
 
 	pub fn known_code(&self, address: H160) -> Option<Vec<u8>> {
+		self.known_code_ref(address).cloned()
+	}
+
+	pub fn known_code_ref(&self, address: H160) -> Option<&Vec<u8>> {
 		if let Some(code) = self.codes.get(&address) {
-			Some(code.clone())
+			Some(code)
 		} else if let Some(parent) = self.parent.as_ref() {
-			parent.known_code(address)
+			parent.known_code_ref(address)
 		} else {
 			None
 		}
@@ -353,6 +723,13 @@ impl Substate {
 		}
 	}
 
+	fn collect_transient_storage(&self, writes: &mut BTreeMap<(H160, H256), H256>) {
+		if let Some(parent) = self.parent.as_ref() {
+			parent.collect_transient_storage(writes);
+		}
+		writes.extend(self.transient_storage.iter().map(|(k, v)| (*k, *v)));
+	}
+
 	pub fn known_transient_storage(&self, address: H160, key: H256) -> Option<H256> {
 		if let Some(value) = self.transient_storage.get(&(address, key)) {
 			Some(*value)
@@ -385,4 +762,380 @@ impl Substate {
 			false
 		}
 	}
+
+	pub fn deletable(&self, address: H160) -> bool {
+		if self.deletables.contains(&address) {
+			true
+		} else if let Some(parent) = self.parent.as_ref() {
+			parent.deletable(address)
+		} else {
+			false
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	struct ConstantBackend;
+
+	impl RuntimeBaseBackend for ConstantBackend {
+		fn balance(&self, _address: H160) -> U256 {
+			U256::zero()
+		}
+
+		fn code(&self, _address: H160) -> Vec<u8> {
+			Vec::new()
+		}
+
+		fn storage(&self, _address: H160, _index: H256) -> H256 {
+			H256::repeat_byte(1)
+		}
+
+		fn transient_storage(&self, _address: H160, _index: H256) -> H256 {
+			H256::default()
+		}
+
+		fn exists(&self, _address: H160) -> bool {
+			false
+		}
+
+		fn nonce(&self, _address: H160) -> U256 {
+			U256::zero()
+		}
+	}
+
+	#[test]
+	fn original_storage_is_transaction_start_value() {
+		let address = H160::repeat_byte(0xaa);
+		let index = H256::zero();
+		let mut backend = OverlayedBackend::new(ConstantBackend, Default::default());
+
+		// Previous transaction.
+		backend.push_substate();
+		backend
+			.set_storage(address, index, H256::repeat_byte(2))
+			.unwrap();
+		backend.pop_substate(MergeStrategy::Commit);
+		backend.begin_transaction();
+
+		// Current transaction, with a committed and a reverted nested call.
+		backend.push_substate();
+		backend.push_substate();
+		backend
+			.set_storage(address, index, H256::repeat_byte(3))
+			.unwrap();
+		backend.pop_substate(MergeStrategy::Commit);
+		backend.push_substate();
+		backend
+			.set_storage(address, index, H256::repeat_byte(4))
+			.unwrap();
+		backend.pop_substate(MergeStrategy::Revert);
+
+		assert_eq!(
+			backend.original_storage(address, index),
+			H256::repeat_byte(2)
+		);
+		assert_eq!(
+			backend.current_storage(address, index),
+			H256::repeat_byte(3)
+		);
+	}
+
+	#[test]
+	fn begin_transaction_restores_initial_accessed_set() {
+		let precompile = H160::from_low_u64_be(1);
+		let address = H160::repeat_byte(0xaa);
+		let index = H256::zero();
+		let mut backend =
+			OverlayedBackend::new(ConstantBackend, [(precompile, None)].into_iter().collect());
+
+		backend.mark_hot(address, None);
+		backend.warm_storages([(address, index)]);
+		backend.begin_transaction();
+
+		assert!(!backend.is_cold(precompile, None));
+		assert!(backend.is_cold(address, None));
+		assert!(backend.is_cold(address, Some(index)));
+	}
+
+	#[test]
+	fn changes_before_deletion_are_dropped() {
+		let address = H160::repeat_byte(0xaa);
+		let mut backend = OverlayedBackend::new(ConstantBackend, Default::default());
+
+		// Account created and deleted by the previous transaction.
+		backend.push_substate();
+		backend
+			.set_code(address, vec![0x00], SetCodeOrigin::Transaction)
+			.unwrap();
+		backend.mark_delete(address);
+		backend.pop_substate(MergeStrategy::Commit);
+		backend.begin_transaction();
+
+		// Account read as empty, with its backend storage shadowed.
+		assert!(backend.deleted(address));
+		assert!(!backend.exists(address));
+		assert!(backend.code(address).is_empty());
+		assert_eq!(backend.storage(address, H256::zero()), H256::zero());
+		assert_eq!(
+			backend.original_storage(address, H256::zero()),
+			H256::zero()
+		);
+
+		// Account recreated by the current transaction.
+		backend.push_substate();
+		backend.deposit(address, U256::one());
+		backend.pop_substate(MergeStrategy::Commit);
+		assert!(!backend.deleted(address));
+		assert!(backend.exists(address));
+		assert_eq!(backend.balance(address), U256::one());
+		assert_eq!(backend.storage(address, H256::repeat_byte(1)), H256::zero());
+
+		let (_, changeset) = backend.deconstruct();
+		assert!(changeset.deletes.contains(&address));
+		assert_eq!(changeset.codes.get(&address), None);
+		assert_eq!(changeset.balances.get(&address), Some(&U256::one()));
+	}
+
+	#[test]
+	fn cached_storage_reads_follow_writes_and_resets() {
+		let address = H160::repeat_byte(0xaa);
+		let index = H256::zero();
+		let mut backend = OverlayedBackend::new(ConstantBackend, Default::default());
+
+		backend.push_substate();
+		assert_eq!(backend.storage(address, index), H256::repeat_byte(1));
+
+		backend.push_substate();
+		backend
+			.set_storage(address, index, H256::repeat_byte(2))
+			.unwrap();
+		backend.pop_substate(MergeStrategy::Commit);
+		assert_eq!(backend.storage(address, index), H256::repeat_byte(2));
+
+		let other = H160::repeat_byte(0xbb);
+		backend.push_substate();
+		assert_eq!(backend.storage(other, index), H256::repeat_byte(1));
+		backend.reset_storage(other);
+		assert_eq!(backend.storage(other, index), H256::zero());
+		backend.pop_substate(MergeStrategy::Revert);
+		backend.pop_substate(MergeStrategy::Commit);
+	}
+
+	#[test]
+	fn committed_storage_reset_shadows_earlier_writes() {
+		let address = H160::repeat_byte(0xaa);
+		let other = H160::repeat_byte(0xbb);
+		let index = H256::zero();
+		let mut backend = OverlayedBackend::new(ConstantBackend, Default::default());
+
+		backend.push_substate();
+		backend
+			.set_storage(address, index, H256::repeat_byte(2))
+			.unwrap();
+		backend
+			.set_storage(other, index, H256::repeat_byte(3))
+			.unwrap();
+
+		backend.push_substate();
+		backend.reset_storage(address);
+		backend.pop_substate(MergeStrategy::Commit);
+
+		assert_eq!(backend.storage(address, index), H256::zero());
+		assert_eq!(backend.storage(other, index), H256::repeat_byte(3));
+		backend.pop_substate(MergeStrategy::Commit);
+	}
+
+	#[test]
+	fn reverted_selfdestructs_are_dropped() {
+		let selfdestruct = |byte| SelfDestruct {
+			address: H160::repeat_byte(byte),
+			beneficiary: H160::repeat_byte(0xff),
+			balance: U256::from(byte),
+		};
+		let mut backend = OverlayedBackend::new(ConstantBackend, Default::default());
+
+		backend.push_substate();
+		backend.record_selfdestruct(selfdestruct(1));
+		backend.pop_substate(MergeStrategy::Commit);
+
+		backend.push_substate();
+		backend.record_selfdestruct(selfdestruct(2));
+		backend.pop_substate(MergeStrategy::Revert);
+
+		let (_, changeset) = backend.deconstruct();
+		assert_eq!(changeset.selfdestructs, vec![selfdestruct(1)]);
+	}
+
+	#[test]
+	fn reverted_deletables_are_dropped() {
+		let created = H160::repeat_byte(1);
+		let reverted = H160::repeat_byte(2);
+		let mut backend = OverlayedBackend::new(ConstantBackend, Default::default());
+
+		backend.push_substate();
+		backend.mark_deletable(created);
+		backend.push_substate();
+		assert!(backend.deletable(created));
+		backend.mark_deletable(reverted);
+		backend.pop_substate(MergeStrategy::Revert);
+		backend.pop_substate(MergeStrategy::Commit);
+
+		assert!(backend.deletable(created));
+		assert!(!backend.deletable(reverted));
+
+		backend.begin_transaction();
+		assert!(!backend.deletable(created));
+	}
+
+	#[test]
+	fn backend_code_reads_are_cached() {
+		struct CountingBackend(core::cell::Cell<usize>);
+
+		impl RuntimeBaseBackend for CountingBackend {
+			fn balance(&self, _address: H160) -> U256 {
+				U256::zero()
+			}
+
+			fn code(&self, _address: H160) -> Vec<u8> {
+				self.0.set(self.0.get() + 1);
+				vec![0x60, 0x00]
+			}
+
+			fn storage(&self, _address: H160, _index: H256) -> H256 {
+				H256::zero()
+			}
+
+			fn transient_storage(&self, _address: H160, _index: H256) -> H256 {
+				H256::zero()
+			}
+
+			fn exists(&self, _address: H160) -> bool {
+				true
+			}
+
+			fn nonce(&self, _address: H160) -> U256 {
+				U256::zero()
+			}
+		}
+
+		let address = H160::repeat_byte(0xaa);
+		let mut backend = OverlayedBackend::new(
+			CountingBackend(core::cell::Cell::new(0)),
+			Default::default(),
+		);
+
+		assert_eq!(backend.code(address), vec![0x60, 0x00]);
+		assert_eq!(backend.code(address), vec![0x60, 0x00]);
+		assert_eq!(backend.code_size(address), U256::from(2));
+		assert_eq!(backend.code_slice(address, 1, 4), vec![0x00]);
+		assert_eq!(backend.backend.0.get(), 1);
+
+		backend
+			.set_code(address, Vec::new(), SetCodeOrigin::Transaction)
+			.unwrap();
+		assert_eq!(backend.code(address), Vec::<u8>::new());
+
+		backend.begin_transaction();
+		backend.code(H160::repeat_byte(0xbb));
+		assert_eq!(backend.backend.0.get(), 2);
+	}
+
+	#[test]
+	fn cached_code_hash_follows_code_changes() {
+		let address = H160::repeat_byte(0xaa);
+		let empty = H256::from_slice(&Keccak256::digest([]));
+		let code = vec![0x60, 0x00];
+		let code_hash = H256::from_slice(&Keccak256::digest(&code));
+		let mut backend = OverlayedBackend::new(ConstantBackend, Default::default());
+
+		assert_eq!(backend.code_hash(address), empty);
+
+		backend.push_substate();
+		backend
+			.set_code(address, code, SetCodeOrigin::Transaction)
+			.unwrap();
+		assert_eq!(backend.code_hash(address), code_hash);
+		backend.pop_substate(MergeStrategy::Revert);
+
+		assert_eq!(backend.code_hash(address), empty);
+	}
+
+	#[test]
+	fn code_slice_reads_overlayed_code() {
+		let address = H160::repeat_byte(0xaa);
+		let mut backend = OverlayedBackend::new(ConstantBackend, Default::default());
+
+		backend.push_substate();
+		backend
+			.set_code(address, vec![1, 2, 3, 4], SetCodeOrigin::Transaction)
+			.unwrap();
+		assert_eq!(backend.code_slice(address, 1, 2), vec![2, 3]);
+		assert_eq!(backend.code_slice(address, 3, 10), vec![4]);
+		assert!(backend.code_slice(address, 4, 1).is_empty());
+		assert!(backend.code_slice(H160::repeat_byte(0xbb), 0, 1).is_empty());
+	}
+
+	#[test]
+	fn transient_storage_journal() {
+		let address = H160::repeat_byte(0xaa);
+		let mut backend = OverlayedBackend::new(ConstantBackend, Default::default());
+
+		backend.push_substate();
+		backend
+			.set_transient_storage(address, H256::zero(), H256::repeat_byte(1))
+			.unwrap();
+		backend.push_substate();
+		backend
+			.set_transient_storage(address, H256::repeat_byte(1), H256::repeat_byte(2))
+			.unwrap();
+		assert_eq!(backend.transient_storage_writes().len(), 2);
+		backend.pop_substate(MergeStrategy::Revert);
+		backend.pop_substate(MergeStrategy::Commit);
+
+		assert_eq!(
+			backend
+				.transient_storage_writes()
+				.into_iter()
+				.collect::<Vec<_>>(),
+			vec![((address, H256::zero()), H256::repeat_byte(1))]
+		);
+
+		backend.clear_transient_storage();
+		assert!(backend.transient_storage_writes().is_empty());
+		assert_eq!(
+			backend.transient_storage(address, H256::zero()),
+			H256::default()
+		);
+	}
+
+	#[test]
+	fn merge_handles_delete_then_modify() {
+		let a = H160::repeat_byte(0xaa);
+		let b = H160::repeat_byte(0xbb);
+		let index = H256::repeat_byte(1);
+
+		let mut first = OverlayedChangeSet::default();
+		first.balances.insert(a, U256::from(1));
+		first.balances.insert(b, U256::from(1));
+		first.storages.insert((a, index), H256::repeat_byte(2));
+		first.storages.insert((b, index), H256::repeat_byte(2));
+		first.deletes.insert(a);
+
+		let mut second = OverlayedChangeSet::default();
+		second.balances.insert(b, U256::from(3));
+		second.nonces.insert(a, U256::from(1));
+		second.deletes.insert(b);
+
+		let merged = OverlayedChangeSet::merge_all([first, second]);
+		assert!(!merged.deletes.contains(&a));
+		assert!(merged.storage_resets.contains(&a));
+		assert_eq!(merged.balances.get(&a), Some(&U256::zero()));
+		assert_eq!(merged.nonces.get(&a), Some(&U256::from(1)));
+		assert!(!merged.storages.contains_key(&(a, index)));
+		assert!(merged.deletes.contains(&b));
+		assert!(!merged.storages.contains_key(&(b, index)));
+	}
 }
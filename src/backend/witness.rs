@@ -0,0 +1,286 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::cell::RefCell;
+
+use evm_interpreter::runtime::{
+	BlockEnv, RuntimeBaseBackend, RuntimeBlockHashes, RuntimeEnvironment,
+};
+use primitive_types::{H160, H256, U256};
+
+/// Account state read during execution.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WitnessAccount {
+	/// Balance of the account, if read.
+	pub balance: Option<U256>,
+	/// Nonce of the account, if read.
+	pub nonce: Option<U256>,
+	/// Code of the account, if read.
+	pub code: Option<Vec<u8>>,
+	/// Whether the account exists, if read.
+	pub exists: Option<bool>,
+	/// Storage slots read.
+	pub storage: BTreeMap<H256, H256>,
+}
+
+/// All state read during execution, as needed for stateless validation.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExecutionWitness {
+	/// Accounts read.
+	pub accounts: BTreeMap<H160, WitnessAccount>,
+	/// Block hashes read.
+	pub block_hashes: BTreeMap<U256, H256>,
+}
+
+/// Backend wrapper recording every state read into an [ExecutionWitness].
+///
+/// It is meant to wrap the base backend under an
+/// [OverlayedBackend](crate::backend::OverlayedBackend), so that only reads
+/// of the pre-state are recorded.
+pub struct WitnessRecorder<B> {
+	backend: B,
+	witness: RefCell<ExecutionWitness>,
+}
+
+impl<B> WitnessRecorder<B> {
+	/// Wrap a backend.
+	pub fn new(backend: B) -> Self {
+		Self {
+			backend,
+			witness: RefCell::new(ExecutionWitness::default()),
+		}
+	}
+
+	/// Inner backend.
+	pub fn inner(&self) -> &B {
+		&self.backend
+	}
+
+	/// Witness recorded so far.
+	pub fn witness(&self) -> ExecutionWitness {
+		self.witness.borrow().clone()
+	}
+
+	/// Deconstruct into the inner backend and the recorded witness.
+	pub fn deconstruct(self) -> (B, ExecutionWitness) {
+		(self.backend, self.witness.into_inner())
+	}
+
+	fn record<F: FnOnce(&mut WitnessAccount)>(&self, address: H160, f: F) {
+		f(self
+			.witness
+			.borrow_mut()
+			.accounts
+			.entry(address)
+			.or_default())
+	}
+}
+
+impl<B: RuntimeBlockHashes> RuntimeBlockHashes for WitnessRecorder<B> {
+	fn block_hash(&self, number: U256) -> H256 {
+		let hash = self.backend.block_hash(number);
+		self.witness.borrow_mut().block_hashes.insert(number, hash);
+		hash
+	}
+}
+
+impl<B: RuntimeEnvironment> RuntimeEnvironment for WitnessRecorder<B> {
+	fn block_env(&self) -> BlockEnv {
+		self.backend.block_env()
+	}
+
+	fn blob_hash(&self, index: U256) -> H256 {
+		self.backend.blob_hash(index)
+	}
+
+	fn recover_signer(&self, message: H256, y_parity: bool, r: H256, s: H256) -> Option<H160> {
+		self.backend.recover_signer(message, y_parity, r, s)
+	}
+}
+
+impl<B: RuntimeBaseBackend> RuntimeBaseBackend for WitnessRecorder<B> {
+	fn balance(&self, address: H160) -> U256 {
+		let balance = self.backend.balance(address);
+		self.record(address, |account| account.balance = Some(balance));
+		balance
+	}
+
+	fn code(&self, address: H160) -> Vec<u8> {
+		let code = self.backend.code(address);
+		self.record(address, |account| account.code = Some(code.clone()));
+		code
+	}
+
+	fn storage(&self, address: H160, index: H256) -> H256 {
+		let value = self.backend.storage(address, index);
+		self.record(address, |account| {
+			account.storage.insert(index, value);
+		});
+		value
+	}
+
+	fn transient_storage(&self, address: H160, index: H256) -> H256 {
+		self.backend.transient_storage(address, index)
+	}
+
+	fn exists(&self, address: H160) -> bool {
+		let exists = self.backend.exists(address);
+		self.record(address, |account| account.exists = Some(exists));
+		exists
+	}
+
+	fn nonce(&self, address: H160) -> U256 {
+		let nonce = self.backend.nonce(address);
+		self.record(address, |account| account.nonce = Some(nonce));
+		nonce
+	}
+}
+
+/// Backend serving state reads exclusively from a previously recorded
+/// [ExecutionWitness], for stateless re-execution.
+///
+/// Block information other than block hashes is served by the given
+/// environment. Any read that is not in the witness panics, as it means the
+/// witness is incomplete.
+pub struct WitnessBackend<E> {
+	environment: E,
+	witness: ExecutionWitness,
+}
+
+impl<E> WitnessBackend<E> {
+	/// Create a new witness backend.
+	pub fn new(environment: E, witness: ExecutionWitness) -> Self {
+		Self {
+			environment,
+			witness,
+		}
+	}
+
+	/// The witness used by the backend.
+	pub fn witness(&self) -> &ExecutionWitness {
+		&self.witness
+	}
+
+	fn account(&self, address: H160) -> &WitnessAccount {
+		self.witness
+			.accounts
+			.get(&address)
+			.unwrap_or_else(|| panic!("witness missing account {:?}", address))
+	}
+}
+
+impl<E> RuntimeBlockHashes for WitnessBackend<E> {
+	fn block_hash(&self, number: U256) -> H256 {
+		*self
+			.witness
+			.block_hashes
+			.get(&number)
+			.unwrap_or_else(|| panic!("witness missing block hash {}", number))
+	}
+}
+
+impl<E: RuntimeEnvironment> RuntimeEnvironment for WitnessBackend<E> {
+	fn block_env(&self) -> BlockEnv {
+		self.environment.block_env()
+	}
+
+	fn blob_hash(&self, index: U256) -> H256 {
+		self.environment.blob_hash(index)
+	}
+
+	fn recover_signer(&self, message: H256, y_parity: bool, r: H256, s: H256) -> Option<H160> {
+		self.environment.recover_signer(message, y_parity, r, s)
+	}
+}
+
+impl<E> RuntimeBaseBackend for WitnessBackend<E> {
+	fn balance(&self, address: H160) -> U256 {
+		self.account(address)
+			.balance
+			.unwrap_or_else(|| panic!("witness missing balance of {:?}", address))
+	}
+
+	fn code(&self, address: H160) -> Vec<u8> {
+		self.account(address)
+			.code
+			.clone()
+			.unwrap_or_else(|| panic!("witness missing code of {:?}", address))
+	}
+
+	fn storage(&self, address: H160, index: H256) -> H256 {
+		*self
+			.account(address)
+			.storage
+			.get(&index)
+			.unwrap_or_else(|| panic!("witness missing storage {:?} of {:?}", index, address))
+	}
+
+	fn transient_storage(&self, _address: H160, _index: H256) -> H256 {
+		H256::default()
+	}
+
+	fn exists(&self, address: H160) -> bool {
+		self.account(address)
+			.exists
+			.unwrap_or_else(|| panic!("witness missing existence of {:?}", address))
+	}
+
+	fn nonce(&self, address: H160) -> U256 {
+		self.account(address)
+			.nonce
+			.unwrap_or_else(|| panic!("witness missing nonce of {:?}", address))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	struct ConstantBackend;
+
+	impl RuntimeBaseBackend for ConstantBackend {
+		fn balance(&self, _address: H160) -> U256 {
+			U256::from(100)
+		}
+
+		fn code(&self, _address: H160) -> Vec<u8> {
+			vec![0x00]
+		}
+
+		fn storage(&self, _address: H160, index: H256) -> H256 {
+			index
+		}
+
+		fn transient_storage(&self, _address: H160, _index: H256) -> H256 {
+			H256::default()
+		}
+
+		fn exists(&self, _address: H160) -> bool {
+			true
+		}
+
+		fn nonce(&self, _address: H160) -> U256 {
+			U256::one()
+		}
+	}
+
+	#[test]
+	fn replay_recorded_witness() {
+		let address = H160::repeat_byte(0xaa);
+		let index = H256::repeat_byte(0x01);
+
+		let recorder = WitnessRecorder::new(ConstantBackend);
+		recorder.balance(address);
+		recorder.code_hash(address);
+		recorder.storage(address, index);
+		let (_, witness) = recorder.deconstruct();
+
+		let replay = WitnessBackend::new((), witness);
+		assert_eq!(replay.balance(address), U256::from(100));
+		assert_eq!(replay.code(address), vec![0x00]);
+		assert_eq!(replay.storage(address, index), index);
+
+		let missing = std::panic::catch_unwind(|| replay.nonce(address));
+		assert!(missing.is_err());
+	}
+}
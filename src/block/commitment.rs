@@ -0,0 +1,147 @@
+use alloc::collections::BTreeMap;
+
+use evm_interpreter::utils::h256_to_u256;
+use primitive_types::{H160, H256, U256};
+use sha3::{Digest, Keccak256};
+
+use crate::{backend::OverlayedChangeSet, block::trie::sec_trie_root};
+
+/// A commitment scheme of the world state.
+///
+/// The block builder feeds the change set of each transaction into the
+/// commitment, and queries the state root when sealing the block. Chains
+/// using a commitment other than the Merkle Patricia trie implement this
+/// trait.
+pub trait StateCommitment {
+	/// Apply a change set on top of the committed state.
+	fn apply(&mut self, changeset: &OverlayedChangeSet);
+	/// Current state root.
+	fn root(&self) -> H256;
+}
+
+/// Account as committed in [MptStateCommitment].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MptAccount {
+	/// Nonce of the account.
+	pub nonce: U256,
+	/// Balance of the account.
+	pub balance: U256,
+	/// Code hash of the account.
+	pub code_hash: H256,
+	/// Non-zero storage values of the account.
+	pub storage: BTreeMap<H256, H256>,
+}
+
+impl MptAccount {
+	/// A new account with the given nonce, balance and code.
+	pub fn new(nonce: U256, balance: U256, code: &[u8]) -> Self {
+		Self {
+			nonce,
+			balance,
+			code_hash: H256::from_slice(&Keccak256::digest(code)),
+			storage: BTreeMap::new(),
+		}
+	}
+}
+
+impl Default for MptAccount {
+	fn default() -> Self {
+		Self::new(U256::zero(), U256::zero(), &[])
+	}
+}
+
+/// The Ethereum Merkle Patricia trie state commitment.
+#[derive(Clone, Debug, Default)]
+pub struct MptStateCommitment {
+	/// All accounts of the state.
+	pub accounts: BTreeMap<H160, MptAccount>,
+}
+
+impl MptStateCommitment {
+	/// Start from the given state.
+	pub fn new(accounts: BTreeMap<H160, MptAccount>) -> Self {
+		Self { accounts }
+	}
+
+	fn account_mut(&mut self, address: H160) -> &mut MptAccount {
+		self.accounts.entry(address).or_default()
+	}
+}
+
+impl StateCommitment for MptStateCommitment {
+	fn apply(&mut self, changeset: &OverlayedChangeSet) {
+		for address in &changeset.deletes {
+			self.accounts.remove(address);
+		}
+
+		for (address, balance) in &changeset.balances {
+			self.account_mut(*address).balance = *balance;
+		}
+
+		for (address, code) in &changeset.codes {
+			self.account_mut(*address).code_hash = H256::from_slice(&Keccak256::digest(code));
+		}
+
+		for (address, nonce) in &changeset.nonces {
+			self.account_mut(*address).nonce = *nonce;
+		}
+
+		for address in &changeset.storage_resets {
+			self.account_mut(*address).storage = BTreeMap::new();
+		}
+
+		for ((address, key), value) in &changeset.storages {
+			let account = self.account_mut(*address);
+
+			if *value == H256::default() {
+				account.storage.remove(key);
+			} else {
+				account.storage.insert(*key, *value);
+			}
+		}
+	}
+
+	fn root(&self) -> H256 {
+		sec_trie_root(self.accounts.iter().map(|(address, account)| {
+			let storage_root = sec_trie_root(
+				account
+					.storage
+					.iter()
+					.map(|(k, v)| (k, rlp::encode(&h256_to_u256(*v)))),
+			);
+
+			let mut stream = rlp::RlpStream::new_list(4);
+			stream.append(&account.nonce);
+			stream.append(&account.balance);
+			stream.append(&storage_root);
+			stream.append(&account.code_hash);
+
+			(address, stream.out())
+		}))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn deleted_accounts_are_recreated_by_changes() {
+		let address = H160::repeat_byte(0xaa);
+		let mut account = MptAccount::new(U256::one(), U256::from(7), &[0x00]);
+		account
+			.storage
+			.insert(H256::zero(), H256::from_low_u64_be(1));
+		let mut commitment = MptStateCommitment::new([(address, account)].into_iter().collect());
+
+		let mut changeset = OverlayedChangeSet::default();
+		changeset.deletes.insert(address);
+		changeset.balances.insert(address, U256::from(3));
+		commitment.apply(&changeset);
+
+		assert_eq!(
+			commitment.accounts.get(&address),
+			Some(&MptAccount::new(U256::zero(), U256::from(3), &[]))
+		);
+	}
+}
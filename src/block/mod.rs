@@ -0,0 +1,16 @@
+//! # Block building helpers
+//!
+//! Utilities needed to assemble a block from executed transactions, such as
+//! the receipts trie root, cumulative gas, the logs bloom and the state root.
+//! They are available with the `block` feature.
+
+mod commitment;
+mod receipt;
+pub mod trie;
+
+pub use ethbloom::Bloom;
+
+pub use self::{
+	commitment::{MptAccount, MptStateCommitment, StateCommitment},
+	receipt::{build_receipts, encode_receipt, logs_bloom, BlockReceipts, ExecutedReceipt},
+};
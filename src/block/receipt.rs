@@ -0,0 +1,106 @@
+use alloc::vec::Vec;
+
+use ethbloom::{Bloom, Input as BloomInput};
+use evm_interpreter::runtime::Log;
+use primitive_types::{H256, U256};
+use rlp::RlpStream;
+
+use crate::block::trie::ordered_trie_root;
+
+/// Data of an executed transaction needed to build its receipt.
+#[derive(Clone, Debug)]
+pub struct ExecutedReceipt {
+	/// EIP-2718 transaction type. `None` for legacy transactions.
+	pub transaction_type: Option<u8>,
+	/// Whether the transaction succeeded (EIP-658 status code).
+	pub succeed: bool,
+	/// Gas used by the transaction, after refunds.
+	pub used_gas: U256,
+	/// Logs emitted by the transaction.
+	pub logs: Vec<Log>,
+}
+
+/// Receipts of a block, along with the aggregated block gas and bloom.
+#[derive(Clone, Debug)]
+pub struct BlockReceipts {
+	/// Cumulative gas used after each transaction.
+	pub cumulative_gas: Vec<U256>,
+	/// Encoded receipts, in the format inserted into the receipts trie.
+	pub encoded: Vec<Vec<u8>>,
+	/// Logs bloom of each receipt.
+	pub blooms: Vec<Bloom>,
+	/// Total gas used by the block.
+	pub used_gas: U256,
+	/// Receipts trie root.
+	pub receipts_root: H256,
+	/// Aggregated logs bloom of the block.
+	pub logs_bloom: Bloom,
+}
+
+/// Logs bloom of the given logs.
+pub fn logs_bloom<'a, I: IntoIterator<Item = &'a Log>>(logs: I) -> Bloom {
+	let mut bloom = Bloom::default();
+	for log in logs {
+		bloom.accrue(BloomInput::Raw(&log.address[..]));
+		for topic in &log.topics {
+			bloom.accrue(BloomInput::Raw(&topic[..]));
+		}
+	}
+	bloom
+}
+
+/// Encode a receipt with the given cumulative gas and bloom.
+pub fn encode_receipt(receipt: &ExecutedReceipt, cumulative_gas: U256, bloom: &Bloom) -> Vec<u8> {
+	let mut stream = RlpStream::new_list(4);
+	stream.append(&u8::from(receipt.succeed));
+	stream.append(&cumulative_gas);
+	stream.append(bloom);
+	stream.begin_list(receipt.logs.len());
+	for log in &receipt.logs {
+		stream.begin_list(3);
+		stream.append(&log.address);
+		stream.append_list(&log.topics);
+		stream.append(&log.data);
+	}
+
+	let payload = stream.out();
+	match receipt.transaction_type {
+		Some(transaction_type) => {
+			let mut encoded = Vec::with_capacity(payload.len() + 1);
+			encoded.push(transaction_type);
+			encoded.extend_from_slice(&payload);
+			encoded
+		}
+		None => payload.to_vec(),
+	}
+}
+
+/// Build receipts of a block from executed transactions, in block order.
+pub fn build_receipts(receipts: &[ExecutedReceipt]) -> BlockReceipts {
+	let mut cumulative_gas = Vec::with_capacity(receipts.len());
+	let mut encoded = Vec::with_capacity(receipts.len());
+	let mut blooms = Vec::with_capacity(receipts.len());
+	let mut used_gas = U256::zero();
+	let mut block_bloom = Bloom::default();
+
+	for receipt in receipts {
+		used_gas = used_gas.saturating_add(receipt.used_gas);
+		let bloom = logs_bloom(&receipt.logs);
+		block_bloom.accrue_bloom(&bloom);
+
+		encoded.push(encode_receipt(receipt, used_gas, &bloom));
+		cumulative_gas.push(used_gas);
+		blooms.push(bloom);
+	}
+
+	let receipts_root = ordered_trie_root(&encoded);
+
+	BlockReceipts {
+		cumulative_gas,
+		encoded,
+		blooms,
+		used_gas,
+		receipts_root,
+		logs_bloom: block_bloom,
+	}
+}
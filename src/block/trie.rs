@@ -0,0 +1,167 @@
+//! Merkle Patricia trie root calculation.
+
+use alloc::vec::Vec;
+
+use hash256_std_hasher::Hash256StdHasher;
+use hash_db::Hasher;
+use primitive_types::H256;
+use sha3::{Digest, Keccak256};
+use trie_root::Value as TrieStreamValue;
+
+/// Keccak-256 hasher used by the Ethereum trie.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct KeccakHasher;
+
+impl Hasher for KeccakHasher {
+	type Out = H256;
+	type StdHasher = Hash256StdHasher;
+	const LENGTH: usize = 32;
+
+	fn hash(x: &[u8]) -> Self::Out {
+		H256::from_slice(Keccak256::digest(x).as_slice())
+	}
+}
+
+/// RLP trie stream of the Ethereum trie.
+#[derive(Default)]
+pub struct RlpTrieStream {
+	stream: rlp::RlpStream,
+}
+
+impl trie_root::TrieStream for RlpTrieStream {
+	fn new() -> Self {
+		Self {
+			stream: rlp::RlpStream::new(),
+		}
+	}
+
+	fn append_empty_data(&mut self) {
+		self.stream.append_empty_data();
+	}
+
+	fn begin_branch(
+		&mut self,
+		_maybe_key: Option<&[u8]>,
+		_maybe_value: Option<TrieStreamValue>,
+		_has_children: impl Iterator<Item = bool>,
+	) {
+		// One item for every nibble, plus one for data.
+		self.stream.begin_list(17);
+	}
+
+	fn append_empty_child(&mut self) {
+		self.stream.append_empty_data();
+	}
+
+	fn end_branch(&mut self, value: Option<TrieStreamValue>) {
+		match value {
+			Some(TrieStreamValue::Inline(value)) => self.stream.append(&value),
+			Some(TrieStreamValue::Node(value)) => self.stream.append(&value),
+			None => self.stream.append_empty_data(),
+		};
+	}
+
+	fn append_leaf(&mut self, key: &[u8], value: TrieStreamValue) {
+		self.stream.begin_list(2);
+		self.stream.append_iter(hex_prefix_encode(key, true));
+		match value {
+			TrieStreamValue::Inline(value) => self.stream.append(&value),
+			TrieStreamValue::Node(value) => self.stream.append(&value),
+		};
+	}
+
+	fn append_extension(&mut self, key: &[u8]) {
+		self.stream.begin_list(2);
+		self.stream.append_iter(hex_prefix_encode(key, false));
+	}
+
+	fn append_substream<H: Hasher>(&mut self, other: Self) {
+		let out = other.out();
+		match out.len() {
+			0..=31 => self.stream.append_raw(&out, 1),
+			_ => self.stream.append(&H::hash(&out).as_ref()),
+		};
+	}
+
+	fn out(self) -> Vec<u8> {
+		self.stream.out().to_vec()
+	}
+}
+
+/// Hex-prefix encoding of nibbles. The first nibble has flags: oddness is
+/// `2^0` and termination (leaf) is `2^1`.
+fn hex_prefix_encode(nibbles: &[u8], leaf: bool) -> impl Iterator<Item = u8> + '_ {
+	let inlen = nibbles.len();
+	let oddness_factor = inlen % 2;
+
+	let first_byte = {
+		let mut bits = ((inlen as u8 & 1) + (2 * leaf as u8)) << 4;
+		if oddness_factor == 1 {
+			bits += nibbles[0];
+		}
+		bits
+	};
+	core::iter::once(first_byte).chain(
+		nibbles[oddness_factor..]
+			.chunks(2)
+			.map(|ch| ch[0] << 4 | ch[1]),
+	)
+}
+
+/// Trie root of the given key-value pairs.
+pub fn trie_root<I, K, V>(input: I) -> H256
+where
+	I: IntoIterator<Item = (K, V)>,
+	K: AsRef<[u8]> + Ord,
+	V: AsRef<[u8]>,
+{
+	trie_root::trie_root::<KeccakHasher, RlpTrieStream, _, _, _>(input, None)
+}
+
+/// Secure trie root of the given key-value pairs, where keys are hashed.
+pub fn sec_trie_root<I, K, V>(input: I) -> H256
+where
+	I: IntoIterator<Item = (K, V)>,
+	K: AsRef<[u8]>,
+	V: AsRef<[u8]>,
+{
+	trie_root::sec_trie_root::<KeccakHasher, RlpTrieStream, _, _, _>(input, None)
+}
+
+/// Trie root of the given values, keyed by the RLP encoding of their index.
+pub fn ordered_trie_root<I, V>(input: I) -> H256
+where
+	I: IntoIterator<Item = V>,
+	V: AsRef<[u8]>,
+{
+	trie_root(
+		input
+			.into_iter()
+			.enumerate()
+			.map(|(i, v)| (rlp::encode(&i), v)),
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn known_roots() {
+		let root = trie_root(vec![
+			("doe", "reindeer"),
+			("dog", "puppy"),
+			("dogglesworth", "cat"),
+		]);
+		assert_eq!(
+			hex::encode(root),
+			"8aad789dff2f538bca5d8ea56e8abe10f4c7ba3a5dea95fea4cd6e7c3a1168d3"
+		);
+
+		let empty = ordered_trie_root(Vec::<Vec<u8>>::new());
+		assert_eq!(
+			hex::encode(empty),
+			"56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"
+		);
+	}
+}
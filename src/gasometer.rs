@@ -3,6 +3,14 @@
 use evm_interpreter::{error::ExitError, runtime::GasState};
 use primitive_types::U256;
 
+/// Gas state that can be charged during execution, in particular by
+/// precompiles whose cost depends on the work they do. Costs and refunds can
+/// be recorded incrementally, checking [GasState::gas] in between.
 pub trait GasMutState: GasState {
+	/// Record a cost, failing with out of gas if it exceeds the gas left.
 	fn record_gas(&mut self, gas: U256) -> Result<(), ExitError>;
+	/// Gas refunded so far, before the refund cap is applied.
+	fn refunded_gas(&self) -> u64;
+	/// Record a refund. A negative refund removes previously refunded gas.
+	fn record_refund(&mut self, refund: i64);
 }
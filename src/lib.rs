@@ -26,6 +26,9 @@
 //!   given a code address for call or an init code for create. You may want
 //!   [standard::EtableResolver], which accepts a precompile set.
 //!
+//! To simulate a transaction without any risk of committing its state
+//! changes, use [dry_run], which only takes a shared reference of the backend.
+//!
 //! ## Debugging
 //!
 //! Rust EVM supports two different methods for debugging. You can either single
@@ -66,11 +69,15 @@
 extern crate alloc;
 
 pub mod backend;
+#[cfg(feature = "block")]
+pub mod block;
+pub mod revert;
 pub mod standard;
 
 mod call_stack;
 mod gasometer;
 mod invoker;
+mod simulation;
 
 pub use evm_interpreter as interpreter;
 
@@ -79,6 +86,7 @@ pub use crate::{
 	call_stack::{transact, HeapTransact},
 	gasometer::GasMutState,
 	invoker::{Invoker, InvokerControl},
+	simulation::{dry_run, simulate_bundle, BundleResult},
 };
 
 /// Merge strategy of a backend substate layer or a call stack gasometer layer.
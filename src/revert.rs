@@ -0,0 +1,152 @@
+//! # Revert reason decoding
+//!
+//! Decode Solidity revert payloads returned by `REVERT` into a structured
+//! form.
+
+use alloc::{string::String, vec::Vec};
+
+use primitive_types::U256;
+
+/// Selector of `Error(string)`.
+pub const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+/// Selector of `Panic(uint256)`.
+pub const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+/// Decoded revert reason.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RevertReason {
+	/// Empty revert payload.
+	Empty,
+	/// `Error(string)`, emitted by `require` and `revert` with a message.
+	Error(String),
+	/// `Panic(uint256)`, emitted by failed assertions and checked arithmetic.
+	Panic(U256),
+	/// Custom error, with its selector and the ABI-encoded arguments.
+	Custom {
+		/// Error selector.
+		selector: [u8; 4],
+		/// ABI-encoded error arguments.
+		data: Vec<u8>,
+	},
+	/// Payload that is too short to carry a selector.
+	Raw(Vec<u8>),
+}
+
+impl RevertReason {
+	/// Decode a revert payload.
+	pub fn decode(payload: &[u8]) -> Self {
+		if payload.is_empty() {
+			return Self::Empty;
+		}
+
+		if payload.len() < 4 {
+			return Self::Raw(payload.to_vec());
+		}
+
+		let mut selector = [0u8; 4];
+		selector.copy_from_slice(&payload[..4]);
+		let data = &payload[4..];
+
+		match selector {
+			ERROR_SELECTOR => {
+				if let Some(message) = decode_string(data) {
+					return Self::Error(message);
+				}
+			}
+			PANIC_SELECTOR => {
+				if data.len() == 32 {
+					return Self::Panic(U256::from_big_endian(data));
+				}
+			}
+			_ => (),
+		}
+
+		Self::Custom {
+			selector,
+			data: data.to_vec(),
+		}
+	}
+}
+
+/// Description of a Solidity panic code.
+pub fn panic_description(code: U256) -> Option<&'static str> {
+	if code > U256::from(u8::MAX) {
+		return None;
+	}
+
+	match code.as_u32() {
+		0x00 => Some("generic compiler panic"),
+		0x01 => Some("assertion failed"),
+		0x11 => Some("arithmetic overflow or underflow"),
+		0x12 => Some("division or modulo by zero"),
+		0x21 => Some("invalid enum value"),
+		0x22 => Some("invalid storage byte array encoding"),
+		0x31 => Some("pop on empty array"),
+		0x32 => Some("array index out of bounds"),
+		0x41 => Some("out of memory"),
+		0x51 => Some("call to uninitialized internal function"),
+		_ => None,
+	}
+}
+
+/// Decode an ABI-encoded single `string` argument.
+fn decode_string(data: &[u8]) -> Option<String> {
+	fn read_usize(data: &[u8], offset: usize) -> Option<usize> {
+		let word = data.get(offset..offset.checked_add(32)?)?;
+		let value = U256::from_big_endian(word);
+		if value > U256::from(usize::MAX) {
+			None
+		} else {
+			Some(value.as_usize())
+		}
+	}
+
+	let offset = read_usize(data, 0)?;
+	let len = read_usize(data, offset)?;
+	let start = offset.checked_add(32)?;
+	let bytes = data.get(start..start.checked_add(len)?)?;
+
+	String::from_utf8(bytes.to_vec()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn decode_reasons() {
+		let error = hex::decode(concat!(
+			"08c379a0",
+			"0000000000000000000000000000000000000000000000000000000000000020",
+			"0000000000000000000000000000000000000000000000000000000000000005",
+			"68656c6c6f000000000000000000000000000000000000000000000000000000",
+		))
+		.unwrap();
+		assert_eq!(
+			RevertReason::decode(&error),
+			RevertReason::Error("hello".into())
+		);
+
+		let panic = hex::decode(concat!(
+			"4e487b71",
+			"0000000000000000000000000000000000000000000000000000000000000011",
+		))
+		.unwrap();
+		assert_eq!(
+			RevertReason::decode(&panic),
+			RevertReason::Panic(U256::from(0x11))
+		);
+
+		let custom = hex::decode("deadbeef01").unwrap();
+		assert_eq!(
+			RevertReason::decode(&custom),
+			RevertReason::Custom {
+				selector: [0xde, 0xad, 0xbe, 0xef],
+				data: vec![0x01],
+			}
+		);
+
+		assert_eq!(RevertReason::decode(&[]), RevertReason::Empty);
+		assert_eq!(RevertReason::decode(&[1, 2]), RevertReason::Raw(vec![1, 2]));
+	}
+}
@@ -0,0 +1,364 @@
+use alloc::{collections::BTreeSet, vec::Vec};
+use core::convert::Infallible;
+
+use evm_interpreter::{
+	error::ExitError,
+	runtime::{Log, RuntimeBaseBackend},
+	RunInterpreter,
+};
+use primitive_types::{H160, H256};
+
+use crate::{
+	backend::{OverlayedBackend, OverlayedChangeSet, TransactionalBackend},
+	call_stack::transact,
+	invoker::Invoker,
+	MergeStrategy,
+};
+
+/// Execute a transaction with full metering, without committing anything to
+/// the backend.
+///
+/// The transaction runs on an [OverlayedBackend] over a shared reference of
+/// `backend`, so the backend cannot be modified even if the transaction
+/// succeeds. The result and the state changes are returned for inspection.
+///
+/// All mutable state lives in the overlay, so a `Sync` backend, such as one
+/// shared through an `Arc`, can serve dry runs on several threads at once.
+pub fn dry_run<'backend, B, Tr, I>(
+	args: I::TransactArgs,
+	heap_depth: Option<usize>,
+	backend: &'backend B,
+	accessed: BTreeSet<(H160, Option<H256>)>,
+	invoker: &I,
+) -> (Result<I::TransactValue, ExitError>, OverlayedChangeSet)
+where
+	I: Invoker<OverlayedBackend<&'backend B>, Tr, Interrupt = Infallible>,
+	I::Interpreter: RunInterpreter<OverlayedBackend<&'backend B>, Tr>,
+{
+	let mut overlayed = OverlayedBackend::new(backend, accessed);
+	let result = transact(args, heap_depth, &mut overlayed, invoker);
+	let (_, changeset) = overlayed.deconstruct();
+
+	(result, changeset)
+}
+
+/// Result of a transaction of a bundle simulated by [simulate_bundle].
+pub struct BundleResult<V> {
+	/// Result of the transaction.
+	pub result: Result<V, ExitError>,
+	/// Logs emitted by the transaction.
+	pub logs: Vec<Log>,
+}
+
+/// Execute an ordered bundle of transactions on a single ephemeral state,
+/// without committing anything to the backend.
+///
+/// Each transaction sees the changes of the previous ones, as in a block,
+/// while the accessed set and transient storage start afresh, with
+/// `accessed` warm. A transaction returning `Err` is left out, as a block
+/// would not include it: none of its changes, including the fee and the nonce
+/// of its caller, are seen by the following transactions or returned. The
+/// result of each transaction is returned along with the changes of the whole
+/// bundle, whose logs are those of all transactions in order.
+pub fn simulate_bundle<'backend, B, Tr, I, T>(
+	bundle: T,
+	heap_depth: Option<usize>,
+	backend: &'backend B,
+	accessed: BTreeSet<(H160, Option<H256>)>,
+	invoker: &I,
+) -> (Vec<BundleResult<I::TransactValue>>, OverlayedChangeSet)
+where
+	T: IntoIterator<Item = I::TransactArgs>,
+	B: RuntimeBaseBackend,
+	I: Invoker<OverlayedBackend<&'backend B>, Tr, Interrupt = Infallible>,
+	I::Interpreter: RunInterpreter<OverlayedBackend<&'backend B>, Tr>,
+{
+	let mut overlayed = OverlayedBackend::new(backend, accessed);
+	let mut results = Vec::new();
+	let mut all_logs = Vec::new();
+
+	for args in bundle {
+		overlayed.push_substate();
+		let result = transact(args, heap_depth, &mut overlayed, invoker);
+		overlayed.pop_substate(if result.is_ok() {
+			MergeStrategy::Commit
+		} else {
+			MergeStrategy::Discard
+		});
+		let logs = overlayed.begin_transaction();
+
+		all_logs.extend(logs.iter().cloned());
+		results.push(BundleResult { result, logs });
+	}
+
+	let (_, mut changeset) = overlayed.deconstruct();
+	changeset.logs = all_logs;
+
+	(results, changeset)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{sync::Arc, thread};
+
+	#[cfg(feature = "arena")]
+	use evm_interpreter::machine::FrameArena;
+	use evm_interpreter::{
+		error::{CreateScheme, ExitException},
+		runtime::BlockEnv,
+	};
+	use primitive_types::U256;
+
+	use super::*;
+	use crate::{
+		backend::{
+			ChaosBackend, ExecutionWitness, OverlayedBackend, RuntimeEnvironment, WitnessAccount,
+			WitnessBackend,
+		},
+		standard::{
+			self, testing, Config, DynamicPrecompileSet, EtableResolver, InternalCallScheme,
+			PureCallCache, TransactArgs,
+		},
+	};
+
+	fn assert_send<T: Send>() {}
+	fn assert_send_sync<T: Send + Sync>() {}
+	fn assert_send_sync_value<T: Send + Sync>(_: &T) {}
+
+	#[test]
+	fn executor_types_are_send() {
+		type Backend = OverlayedBackend<ChaosBackend>;
+
+		assert_send_sync::<standard::State>();
+		assert_send_sync::<standard::Machine>();
+		#[cfg(feature = "arena")]
+		assert_send_sync::<FrameArena>();
+		assert_send_sync::<DynamicPrecompileSet<standard::State, Backend>>();
+		assert_send::<Backend>();
+		assert_send::<PureCallCache>();
+
+		let config = Config::cancun();
+		let etable = testing::etable::<Backend>();
+		let precompiles = DynamicPrecompileSet::<standard::State, Backend>::new(());
+		let resolver = EtableResolver::new(&config, &precompiles, &etable);
+		#[cfg(feature = "arena")]
+		let resolver = resolver.with_arena(FrameArena::new());
+		let invoker = standard::Invoker::new(&config, &resolver);
+		assert_send_sync_value(&invoker);
+	}
+
+	#[test]
+	fn concurrent_dry_runs_over_shared_backend() {
+		assert_send_sync::<ChaosBackend>();
+		assert_send_sync::<Config>();
+
+		let backend = Arc::new(ChaosBackend::new(7));
+		let handles = (0..4)
+			.map(|_| {
+				let backend = backend.clone();
+				thread::spawn(move || {
+					let config = Config::london();
+					let etable = testing::etable();
+					let resolver = EtableResolver::new(&config, &(), &etable);
+					let invoker = standard::Invoker::new(&config, &resolver);
+					let args = testing::normal(testing::call(
+						H160::repeat_byte(0x01),
+						H160::repeat_byte(0x02),
+					));
+
+					let (result, changeset) =
+						dry_run(args, None, &backend, BTreeSet::new(), &invoker);
+					(result.map(|value| value.used_gas), changeset.storages)
+				})
+			})
+			.collect::<Vec<_>>();
+
+		let results = handles
+			.into_iter()
+			.map(|handle| handle.join().unwrap())
+			.collect::<Vec<_>>();
+		assert!(results.windows(2).all(|pair| pair[0] == pair[1]));
+	}
+
+	/// Backend where the given accounts and the coinbase are empty.
+	fn empty_backend(addresses: &[H160]) -> WitnessBackend<BlockEnv> {
+		let env = BlockEnv::default();
+		let empty = WitnessAccount {
+			exists: Some(false),
+			..testing::account(&[])
+		};
+		let mut witness = ExecutionWitness::default();
+		for address in addresses.iter().chain([&env.coinbase]) {
+			witness.accounts.insert(*address, empty.clone());
+		}
+		WitnessBackend::new(env, witness)
+	}
+
+	#[test]
+	fn create_reports_created_accounts_and_calls() {
+		let caller = H160::repeat_byte(0x01);
+		// PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 CREATE2 POP PUSH1 0 PUSH1 0 RETURN
+		let init_code = hex::decode("6000600060006000f55060006000f3").unwrap();
+		let salt = H256::repeat_byte(0x02);
+		let address = testing::create2_address(caller, salt, &init_code);
+		let inner = testing::create2_address(address, H256::zero(), &[]);
+		let backend = empty_backend(&[caller, address, inner]);
+		let coinbase = backend.block_coinbase();
+
+		let config = Config::london();
+		let etable = testing::etable();
+		let resolver = EtableResolver::new(&config, &(), &etable);
+		let invoker = standard::Invoker::new(&config, &resolver).with_call_tree(true);
+		let args = testing::normal(testing::create2(caller, init_code, salt));
+
+		let (result, _) = dry_run(args, None, &backend, BTreeSet::new(), &invoker);
+		let value = result.unwrap();
+		assert_eq!(value.outcome.created_address(), Some(address));
+		assert_eq!(value.created, vec![address, inner]);
+		for account in [caller, address, inner, coinbase] {
+			assert!(value.touched.contains(&account));
+		}
+
+		assert_eq!(value.calls.len(), 1);
+		let call = &value.calls[0];
+		assert_eq!(call.depth, 1);
+		assert!(matches!(
+			call.scheme,
+			InternalCallScheme::Create(CreateScheme::Create2 { .. })
+		));
+		assert_eq!(call.callee, inner);
+		assert!(call.result.is_ok());
+		assert!(call.used_gas <= call.gas_limit);
+	}
+
+	#[test]
+	fn nonce_increase_can_be_disabled() {
+		let caller = H160::repeat_byte(0x01);
+		// PUSH1 0 PUSH1 0 RETURN
+		let init_code = hex::decode("60006000f3").unwrap();
+		let salt = H256::repeat_byte(0x02);
+		let address = testing::create2_address(caller, salt, &init_code);
+		let backend = empty_backend(&[caller, address]);
+
+		let config = Config::cancun();
+		let etable = testing::etable();
+		let resolver = EtableResolver::new(&config, &(), &etable);
+		let invoker = standard::Invoker::new(&config, &resolver).with_nonce_increase(false);
+		let args = testing::normal(testing::create2(caller, init_code, salt));
+
+		let (result, changeset) = dry_run(args, None, &backend, BTreeSet::new(), &invoker);
+		assert!(result.is_ok());
+		assert_eq!(changeset.nonces.get(&caller), None);
+		assert_eq!(changeset.nonces.get(&address), Some(&U256::one()));
+	}
+
+	#[test]
+	fn bundle_transactions_see_previous_changes() {
+		let caller = H160::repeat_byte(0x01);
+		// PUSH1 0 PUSH1 0 RETURN
+		let init_code = hex::decode("60006000f3").unwrap();
+		let salt = H256::repeat_byte(0x02);
+		let address = testing::create2_address(caller, salt, &init_code);
+		let backend = empty_backend(&[caller, address]);
+
+		let config = Config::cancun();
+		let etable = testing::etable();
+		let resolver = EtableResolver::new(&config, &(), &etable);
+		let invoker = standard::Invoker::new(&config, &resolver);
+		let args = testing::normal(testing::create2(caller, init_code, salt));
+
+		// The second transaction collides with the contract of the first.
+		let (results, changeset) = simulate_bundle(
+			[args.clone(), args],
+			None,
+			&backend,
+			BTreeSet::new(),
+			&invoker,
+		);
+		assert_eq!(results.len(), 2);
+		let value = results[0].result.as_ref().ok().unwrap();
+		assert_eq!(value.outcome.created_address(), Some(address));
+		assert_eq!(
+			results[1].result.as_ref().err(),
+			Some(&ExitException::CreateCollision.into())
+		);
+		assert!(changeset.codes.contains_key(&address));
+	}
+
+	#[test]
+	fn bundle_transactions_do_not_see_deleted_contracts() {
+		let caller = H160::repeat_byte(0x01);
+		let destructed = H160::repeat_byte(0x02);
+		let reader = H160::repeat_byte(0x03);
+		let env = BlockEnv::default();
+		let empty = WitnessAccount {
+			exists: Some(false),
+			..testing::account(&[])
+		};
+		let mut witness = ExecutionWitness::default();
+		witness.accounts.insert(caller, empty.clone());
+		witness.accounts.insert(env.coinbase, empty);
+		// ADDRESS SELFDESTRUCT
+		witness
+			.accounts
+			.insert(destructed, testing::account(&hex::decode("30ff").unwrap()));
+		// PUSH20 destructed EXTCODESIZE PUSH1 0 SSTORE
+		let code = hex::decode(format!("73{}3b600055", hex::encode(destructed))).unwrap();
+		witness.accounts.insert(
+			reader,
+			WitnessAccount {
+				storage: testing::zero_slots(&[0]),
+				..testing::account(&code)
+			},
+		);
+		let backend = WitnessBackend::new(env, witness);
+
+		let config = Config::london();
+		let etable = testing::etable();
+		let resolver = EtableResolver::new(&config, &(), &etable);
+		let invoker = standard::Invoker::new(&config, &resolver);
+
+		let (results, changeset) = simulate_bundle(
+			[
+				testing::call(caller, destructed),
+				testing::call(caller, reader),
+			],
+			None,
+			&backend,
+			BTreeSet::new(),
+			&invoker,
+		);
+		assert!(results.iter().all(|result| result.result.is_ok()));
+		assert!(changeset.deletes.contains(&destructed));
+		assert_eq!(
+			changeset.storages.get(&(reader, H256::zero())),
+			Some(&H256::zero())
+		);
+	}
+
+	#[test]
+	fn rejected_bundle_transactions_leave_no_changes() {
+		let caller = H160::repeat_byte(0x01);
+		let address = H160::repeat_byte(0x02);
+		let backend = empty_backend(&[caller, address]);
+
+		let config = Config::cancun();
+		let etable = testing::etable();
+		let resolver = EtableResolver::new(&config, &(), &etable);
+		let invoker = standard::Invoker::new(&config, &resolver);
+		let args = testing::normal(testing::call(caller, address));
+		let mut rejected = args.clone();
+		if let TransactArgs::Call { gas_limit, .. } = &mut rejected {
+			*gas_limit = U256::from(1000);
+		}
+
+		let (results, changeset) =
+			simulate_bundle([rejected, args], None, &backend, BTreeSet::new(), &invoker);
+		assert_eq!(
+			results[0].result.as_ref().err(),
+			Some(&ExitException::OutOfGas.into())
+		);
+		assert!(results[1].result.is_ok());
+		assert_eq!(changeset.nonces.get(&caller), Some(&U256::one()));
+	}
+}
@@ -0,0 +1,110 @@
+use evm_interpreter::error::ExitException;
+use primitive_types::U256;
+
+/// Blob gas of a single blob, as in EIP-4844.
+pub const GAS_PER_BLOB: u64 = 1 << 17;
+
+/// Minimum blob base fee, as in EIP-4844.
+pub const MIN_BASE_FEE_PER_BLOB_GAS: u64 = 1;
+
+/// Blob throughput parameters of a fork, as in
+/// [EIP-7691](https://eips.ethereum.org/EIPS/eip-7691). Blob-parameter-only
+/// forks only change these, and can be configured without code changes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BlobSchedule {
+	/// Target number of blobs per block.
+	pub target_blobs_per_block: u64,
+	/// Maximum number of blobs per block.
+	pub max_blobs_per_block: u64,
+	/// Denominator of the blob base fee exponent.
+	pub base_fee_update_fraction: u64,
+}
+
+impl BlobSchedule {
+	/// Cancun parameters, from EIP-4844.
+	pub const CANCUN: BlobSchedule = BlobSchedule {
+		target_blobs_per_block: 3,
+		max_blobs_per_block: 6,
+		base_fee_update_fraction: 3_338_477,
+	};
+
+	/// Prague parameters, from EIP-7691.
+	pub const PRAGUE: BlobSchedule = BlobSchedule {
+		target_blobs_per_block: 6,
+		max_blobs_per_block: 9,
+		base_fee_update_fraction: 5_007_716,
+	};
+
+	/// Target blob gas per block.
+	pub const fn target_blob_gas_per_block(&self) -> u64 {
+		self.target_blobs_per_block * GAS_PER_BLOB
+	}
+
+	/// Maximum blob gas per block.
+	pub const fn max_blob_gas_per_block(&self) -> u64 {
+		self.max_blobs_per_block * GAS_PER_BLOB
+	}
+
+	/// Blob base fee of a block with the given excess blob gas, that is
+	/// `fake_exponential(MIN_BASE_FEE_PER_BLOB_GAS, excess_blob_gas,
+	/// base_fee_update_fraction)`.
+	pub fn blob_base_fee(&self, excess_blob_gas: U256) -> U256 {
+		let factor = U256::from(MIN_BASE_FEE_PER_BLOB_GAS);
+		let denominator = U256::from(self.base_fee_update_fraction);
+
+		let mut i = U256::one();
+		let mut output = U256::zero();
+		let mut accumulator = factor * denominator;
+		while !accumulator.is_zero() {
+			output = output.saturating_add(accumulator);
+			accumulator = accumulator.saturating_mul(excess_blob_gas) / (denominator * i);
+			i += U256::one();
+		}
+		output / denominator
+	}
+
+	/// Excess blob gas of the block following a block with the given excess
+	/// and used blob gas.
+	pub fn next_excess_blob_gas(&self, excess_blob_gas: U256, blob_gas_used: U256) -> U256 {
+		excess_blob_gas
+			.saturating_add(blob_gas_used)
+			.saturating_sub(U256::from(self.target_blob_gas_per_block()))
+	}
+
+	/// Check the number of blobs of a blob transaction, which must carry at
+	/// least one blob and fit in a block.
+	pub fn validate_blob_count(&self, blob_count: usize) -> Result<(), ExitException> {
+		if blob_count == 0 || blob_count as u64 > self.max_blobs_per_block {
+			return Err(ExitException::InvalidBlobCount);
+		}
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn blob_fees_follow_schedule() {
+		let excess = U256::from(100 * GAS_PER_BLOB);
+		assert_eq!(
+			BlobSchedule::CANCUN.blob_base_fee(U256::zero()),
+			U256::one()
+		);
+		assert!(
+			BlobSchedule::PRAGUE.blob_base_fee(excess) < BlobSchedule::CANCUN.blob_base_fee(excess)
+		);
+		assert_eq!(
+			BlobSchedule::PRAGUE.next_excess_blob_gas(excess, U256::from(2 * GAS_PER_BLOB)),
+			U256::from(96 * GAS_PER_BLOB)
+		);
+
+		assert!(BlobSchedule::CANCUN.validate_blob_count(6).is_ok());
+		assert!(BlobSchedule::CANCUN.validate_blob_count(7).is_err());
+		assert!(BlobSchedule::PRAGUE.validate_blob_count(9).is_ok());
+		assert!(BlobSchedule::PRAGUE.validate_blob_count(0).is_err());
+	}
+}
@@ -1,5 +1,11 @@
+use alloc::{format, string::String, vec::Vec};
+use core::{fmt, str::FromStr};
+
+use crate::standard::BlobSchedule;
+
 /// Runtime configuration.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Config {
 	/// Gas paid for extcode.
 	pub gas_ext_code: u64,
@@ -51,8 +57,12 @@ pub struct Config {
 	pub increase_state_access_gas: bool,
 	/// EIP-3529
 	pub decrease_clears_refund: bool,
-	/// EIP-3541
+	/// EIP-3541, rejecting deployed code starting with `0xEF`.
 	pub disallow_executable_format: bool,
+	/// Exempt deployed code starting with the EOF magic `0xEF00` (EIP-3540)
+	/// from the EIP-3541 rejection, for chains trialing EOF deployment. Such
+	/// code must be a valid EOF container (EIP-3670).
+	pub eof_deployment: bool,
 	/// EIP-3651
 	pub warm_coinbase_address: bool,
 	/// Whether to throw out of gas error when
@@ -61,6 +71,10 @@ pub struct Config {
 	pub err_on_call_with_more_gas: bool,
 	/// Take l64 for callcreate after gas.
 	pub call_l64_after_gas: bool,
+	/// Divisor of the gas retained by the caller of a call or create, when
+	/// [Config::call_l64_after_gas] is enabled. EIP-150 sets it to 64, so that
+	/// at most 63/64 of the available gas is forwarded. Zero forwards all of it.
+	pub call_gas_retention_divisor: u64,
 	/// Whether empty account is considered exists.
 	pub empty_considered_exists: bool,
 	/// Whether create transactions and create opcode increases nonce by one.
@@ -69,12 +83,28 @@ pub struct Config {
 	pub stack_limit: usize,
 	/// Memory limit.
 	pub memory_limit: usize,
+	/// Cap on the total memory of all frames, in bytes, regardless of gas.
+	pub total_memory_limit: usize,
 	/// Call limit.
 	pub call_stack_limit: usize,
 	/// Create contract limit.
 	pub create_contract_limit: Option<usize>,
 	/// EIP-3860, maximum size limit of init_code.
 	pub max_initcode_size: Option<usize>,
+	/// EIP-7825, maximum gas limit of a transaction. Transactions above it
+	/// are rejected before execution, like those whose gas limit does not
+	/// cover the intrinsic gas.
+	pub max_transaction_gas_limit: Option<u64>,
+	/// EIP-7907, code size above which loading the code of a cold account
+	/// through a call or `EXTCODECOPY` costs [Config::gas_large_code_word]
+	/// per word of the excess. Chains raising [Config::create_contract_limit]
+	/// set it to the previous limit.
+	pub large_code_threshold: Option<usize>,
+	/// EIP-7907, gas per word of code above [Config::large_code_threshold].
+	pub gas_large_code_word: u64,
+	/// Blob throughput parameters. `None` before Cancun, when blob
+	/// transactions are not supported.
+	pub blob_schedule: Option<BlobSchedule>,
 	/// Call stipend.
 	pub call_stipend: u64,
 	/// Has delegate call.
@@ -95,17 +125,131 @@ pub struct Config {
 	pub has_ext_code_hash: bool,
 	/// Has ext block fee. See [EIP-3198](https://github.com/ethereum/EIPs/blob/master/EIPS/eip-3198.md)
 	pub has_base_fee: bool,
+	/// Has PREVRANDAO, returning the block randomness in place of
+	/// DIFFICULTY. See [EIP-4399](https://eips.ethereum.org/EIPS/eip-4399)
+	pub has_prevrandao: bool,
 	/// Has PUSH0 opcode. See [EIP-3855](https://github.com/ethereum/EIPs/blob/master/EIPS/eip-3855.md)
 	pub has_push0: bool,
 	/// Enables transient storage. See [EIP-1153](https://github.com/ethereum/EIPs/blob/master/EIPS/eip-1153.md)
 	pub eip_1153_enabled: bool,
 	/// Enables MCOPY instruction. See [EIP-5656](https://github.com/ethereum/EIPs/blob/master/EIPS/eip-5656.md)
 	pub eip_5656_enabled: bool,
+	/// Enables BLOBHASH instruction. See [EIP-4844](https://eips.ethereum.org/EIPS/eip-4844)
+	pub eip_4844_enabled: bool,
+	/// Enables BLOBBASEFEE instruction. See [EIP-7516](https://eips.ethereum.org/EIPS/eip-7516)
+	pub eip_7516_enabled: bool,
+	/// Only lets SELFDESTRUCT delete accounts created in the same transaction. See [EIP-6780](https://eips.ethereum.org/EIPS/eip-6780)
+	pub eip_6780_enabled: bool,
+	/// Enables AUTH and AUTHCALL instructions. See [EIP-3074](https://eips.ethereum.org/EIPS/eip-3074)
+	pub eip_3074_enabled: bool,
+	/// Executes the code delegated to by accounts set through set-code transactions. See [EIP-7702](https://eips.ethereum.org/EIPS/eip-7702)
+	pub eip_7702_enabled: bool,
 	/// Uses EIP-1559 (Base fee is burned when this flag is enabled) [EIP-1559](https://github.com/ethereum/EIPs/blob/master/EIPS/eip-1559.md)
 	pub eip_1559_enabled: bool,
 }
 
+/// A field differing between two configurations, see [Config::diff].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConfigChange {
+	/// Field name.
+	pub field: &'static str,
+	/// Debug rendering of the old value.
+	pub old: String,
+	/// Debug rendering of the new value.
+	pub new: String,
+}
+
+macro_rules! diff_fields {
+	($old:expr, $new:expr, $($field:ident),* $(,)?) => {{
+		// Fails to compile if a field is missing from the list.
+		let Config { $($field: _),* } = $old;
+		let mut changes = Vec::new();
+		$(
+			if $old.$field != $new.$field {
+				changes.push(ConfigChange {
+					field: stringify!($field),
+					old: format!("{:?}", $old.$field),
+					new: format!("{:?}", $new.$field),
+				});
+			}
+		)*
+		changes
+	}};
+}
+
 impl Config {
+	/// Fields whose value in `new` differs from this configuration, in
+	/// declaration order.
+	pub fn diff(&self, new: &Config) -> Vec<ConfigChange> {
+		diff_fields!(
+			self,
+			new,
+			gas_ext_code,
+			gas_ext_code_hash,
+			gas_sstore_set,
+			gas_sstore_reset,
+			refund_sstore_clears,
+			max_refund_quotient,
+			gas_balance,
+			gas_sload,
+			gas_sload_cold,
+			gas_suicide,
+			gas_suicide_new_account,
+			gas_call,
+			gas_expbyte,
+			gas_transaction_create,
+			gas_transaction_call,
+			gas_transaction_zero_data,
+			gas_transaction_non_zero_data,
+			gas_access_list_address,
+			gas_access_list_storage_key,
+			gas_account_access_cold,
+			gas_storage_read_warm,
+			sstore_gas_metering,
+			sstore_revert_under_stipend,
+			increase_state_access_gas,
+			decrease_clears_refund,
+			disallow_executable_format,
+			eof_deployment,
+			warm_coinbase_address,
+			err_on_call_with_more_gas,
+			call_l64_after_gas,
+			call_gas_retention_divisor,
+			empty_considered_exists,
+			create_increase_nonce,
+			stack_limit,
+			memory_limit,
+			total_memory_limit,
+			call_stack_limit,
+			create_contract_limit,
+			max_initcode_size,
+			max_transaction_gas_limit,
+			large_code_threshold,
+			gas_large_code_word,
+			blob_schedule,
+			call_stipend,
+			has_delegate_call,
+			has_create2,
+			has_revert,
+			has_return_data,
+			has_bitwise_shifting,
+			has_chain_id,
+			has_self_balance,
+			has_ext_code_hash,
+			has_base_fee,
+			has_prevrandao,
+			has_push0,
+			eip_1153_enabled,
+			eip_5656_enabled,
+			eip_4844_enabled,
+			eip_7516_enabled,
+			eip_6780_enabled,
+			eip_3074_enabled,
+			eip_7702_enabled,
+			eip_1559_enabled,
+		)
+	}
+
 	/// Frontier hard fork configuration.
 	pub const fn frontier() -> Config {
 		Config {
@@ -135,16 +279,23 @@ impl Config {
 			increase_state_access_gas: false,
 			decrease_clears_refund: false,
 			disallow_executable_format: false,
+			eof_deployment: false,
 			warm_coinbase_address: false,
 			err_on_call_with_more_gas: true,
 			empty_considered_exists: true,
 			create_increase_nonce: false,
 			call_l64_after_gas: false,
+			call_gas_retention_divisor: 64,
 			stack_limit: 1024,
 			memory_limit: usize::MAX,
+			total_memory_limit: usize::MAX,
 			call_stack_limit: 1024,
 			create_contract_limit: None,
 			max_initcode_size: None,
+			max_transaction_gas_limit: None,
+			large_code_threshold: None,
+			gas_large_code_word: 2,
+			blob_schedule: None,
 			call_stipend: 2300,
 			has_delegate_call: false,
 			has_create2: false,
@@ -155,9 +306,15 @@ impl Config {
 			has_self_balance: false,
 			has_ext_code_hash: false,
 			has_base_fee: false,
+			has_prevrandao: false,
 			has_push0: false,
 			eip_1153_enabled: false,
 			eip_5656_enabled: false,
+			eip_4844_enabled: false,
+			eip_7516_enabled: false,
+			eip_6780_enabled: false,
+			eip_3074_enabled: false,
+			eip_7702_enabled: false,
 			eip_1559_enabled: false,
 		}
 	}
@@ -191,16 +348,23 @@ impl Config {
 			increase_state_access_gas: false,
 			decrease_clears_refund: false,
 			disallow_executable_format: false,
+			eof_deployment: false,
 			warm_coinbase_address: false,
 			err_on_call_with_more_gas: false,
 			empty_considered_exists: false,
 			create_increase_nonce: true,
 			call_l64_after_gas: true,
+			call_gas_retention_divisor: 64,
 			stack_limit: 1024,
 			memory_limit: usize::MAX,
+			total_memory_limit: usize::MAX,
 			call_stack_limit: 1024,
 			create_contract_limit: Some(0x6000),
 			max_initcode_size: None,
+			max_transaction_gas_limit: None,
+			large_code_threshold: None,
+			gas_large_code_word: 2,
+			blob_schedule: None,
 			call_stipend: 2300,
 			has_delegate_call: true,
 			has_create2: true,
@@ -211,9 +375,15 @@ impl Config {
 			has_self_balance: true,
 			has_ext_code_hash: true,
 			has_base_fee: false,
+			has_prevrandao: false,
 			has_push0: false,
 			eip_1153_enabled: false,
 			eip_5656_enabled: false,
+			eip_4844_enabled: false,
+			eip_7516_enabled: false,
+			eip_6780_enabled: false,
+			eip_3074_enabled: false,
+			eip_7702_enabled: false,
 			eip_1559_enabled: false,
 		}
 	}
@@ -243,6 +413,36 @@ impl Config {
 		Self::config_with_derived_values(DerivedConfigInputs::cancun())
 	}
 
+	/// Prague hard fork configuration, with EIP-7702 code delegation and the
+	/// EIP-7691 blob schedule.
+	pub const fn prague() -> Config {
+		Self::config_with_derived_values(DerivedConfigInputs::prague())
+	}
+
+	/// Configuration of the hard fork with the given name, such as `"london"`.
+	/// See [Fork] for the accepted names.
+	pub fn by_name(name: &str) -> Option<Config> {
+		name.parse::<Fork>().ok().map(Fork::config)
+	}
+
+	/// Builder of a configuration toggling individual EIPs on top of a fork.
+	/// See [ConfigBuilder].
+	pub fn builder() -> ConfigBuilder {
+		ConfigBuilder::new()
+	}
+
+	/// Check that every enabled EIP of [Eip::DEPENDENCIES] also has the EIP
+	/// it depends on enabled.
+	pub fn validate(&self) -> Result<(), UnmetEipDependency> {
+		for (eip, requires) in Eip::DEPENDENCIES {
+			if eip.is_enabled(self) && !requires.is_enabled(self) {
+				return Err(UnmetEipDependency { eip, requires });
+			}
+		}
+
+		Ok(())
+	}
+
 	const fn config_with_derived_values(inputs: DerivedConfigInputs) -> Config {
 		let DerivedConfigInputs {
 			gas_storage_read_warm,
@@ -250,13 +450,19 @@ impl Config {
 			gas_access_list_storage_key,
 			decrease_clears_refund,
 			has_base_fee,
+			has_prevrandao,
 			has_push0,
 			disallow_executable_format,
 			warm_coinbase_address,
 			max_initcode_size,
 			eip_1153_enabled,
 			eip_5656_enabled,
+			eip_4844_enabled,
+			eip_7516_enabled,
+			eip_6780_enabled,
+			eip_7702_enabled,
 			eip_1559_enabled,
+			blob_schedule,
 		} = inputs;
 
 		// See https://eips.ethereum.org/EIPS/eip-2929
@@ -298,16 +504,23 @@ impl Config {
 			increase_state_access_gas: true,
 			decrease_clears_refund,
 			disallow_executable_format,
+			eof_deployment: false,
 			warm_coinbase_address,
 			err_on_call_with_more_gas: false,
 			empty_considered_exists: false,
 			create_increase_nonce: true,
 			call_l64_after_gas: true,
+			call_gas_retention_divisor: 64,
 			stack_limit: 1024,
 			memory_limit: usize::MAX,
+			total_memory_limit: usize::MAX,
 			call_stack_limit: 1024,
 			create_contract_limit: Some(0x6000),
 			max_initcode_size,
+			max_transaction_gas_limit: None,
+			large_code_threshold: None,
+			gas_large_code_word: 2,
+			blob_schedule,
 			call_stipend: 2300,
 			has_delegate_call: true,
 			has_create2: true,
@@ -318,9 +531,15 @@ impl Config {
 			has_self_balance: true,
 			has_ext_code_hash: true,
 			has_base_fee,
+			has_prevrandao,
 			has_push0,
 			eip_1153_enabled,
 			eip_5656_enabled,
+			eip_4844_enabled,
+			eip_7516_enabled,
+			eip_6780_enabled,
+			eip_3074_enabled: false,
+			eip_7702_enabled,
 			eip_1559_enabled,
 		}
 	}
@@ -337,13 +556,19 @@ struct DerivedConfigInputs {
 	gas_access_list_storage_key: u64,
 	decrease_clears_refund: bool,
 	has_base_fee: bool,
+	has_prevrandao: bool,
 	has_push0: bool,
 	disallow_executable_format: bool,
 	warm_coinbase_address: bool,
 	max_initcode_size: Option<usize>,
 	eip_1153_enabled: bool,
 	eip_5656_enabled: bool,
+	eip_4844_enabled: bool,
+	eip_7516_enabled: bool,
+	eip_6780_enabled: bool,
+	eip_7702_enabled: bool,
 	eip_1559_enabled: bool,
+	blob_schedule: Option<BlobSchedule>,
 }
 
 impl DerivedConfigInputs {
@@ -354,13 +579,19 @@ impl DerivedConfigInputs {
 			gas_access_list_storage_key: 1900,
 			decrease_clears_refund: false,
 			has_base_fee: false,
+			has_prevrandao: false,
 			has_push0: false,
 			disallow_executable_format: false,
 			warm_coinbase_address: false,
 			max_initcode_size: None,
 			eip_1153_enabled: false,
 			eip_5656_enabled: false,
+			eip_4844_enabled: false,
+			eip_7516_enabled: false,
+			eip_6780_enabled: false,
+			eip_7702_enabled: false,
 			eip_1559_enabled: false,
+			blob_schedule: None,
 		}
 	}
 
@@ -371,13 +602,19 @@ impl DerivedConfigInputs {
 			gas_access_list_storage_key: 1900,
 			decrease_clears_refund: true,
 			has_base_fee: true,
+			has_prevrandao: false,
 			has_push0: false,
 			disallow_executable_format: true,
 			warm_coinbase_address: false,
 			max_initcode_size: None,
 			eip_1153_enabled: false,
 			eip_5656_enabled: false,
+			eip_4844_enabled: false,
+			eip_7516_enabled: false,
+			eip_6780_enabled: false,
+			eip_7702_enabled: false,
 			eip_1559_enabled: true,
+			blob_schedule: None,
 		}
 	}
 
@@ -388,13 +625,19 @@ impl DerivedConfigInputs {
 			gas_access_list_storage_key: 1900,
 			decrease_clears_refund: true,
 			has_base_fee: true,
+			has_prevrandao: true,
 			has_push0: false,
 			disallow_executable_format: true,
 			warm_coinbase_address: false,
 			max_initcode_size: None,
 			eip_1153_enabled: false,
 			eip_5656_enabled: false,
+			eip_4844_enabled: false,
+			eip_7516_enabled: false,
+			eip_6780_enabled: false,
+			eip_7702_enabled: false,
 			eip_1559_enabled: true,
+			blob_schedule: None,
 		}
 	}
 
@@ -405,6 +648,7 @@ impl DerivedConfigInputs {
 			gas_access_list_storage_key: 1900,
 			decrease_clears_refund: true,
 			has_base_fee: true,
+			has_prevrandao: true,
 			has_push0: true,
 			disallow_executable_format: true,
 			warm_coinbase_address: true,
@@ -412,7 +656,12 @@ impl DerivedConfigInputs {
 			max_initcode_size: Some(0xC000),
 			eip_1153_enabled: false,
 			eip_5656_enabled: false,
+			eip_4844_enabled: false,
+			eip_7516_enabled: false,
+			eip_6780_enabled: false,
+			eip_7702_enabled: false,
 			eip_1559_enabled: true,
+			blob_schedule: None,
 		}
 	}
 
@@ -423,6 +672,7 @@ impl DerivedConfigInputs {
 			gas_access_list_storage_key: 1900,
 			decrease_clears_refund: true,
 			has_base_fee: true,
+			has_prevrandao: true,
 			has_push0: true,
 			disallow_executable_format: true,
 			warm_coinbase_address: true,
@@ -430,7 +680,452 @@ impl DerivedConfigInputs {
 			max_initcode_size: Some(0xC000),
 			eip_1153_enabled: true,
 			eip_5656_enabled: true,
+			eip_4844_enabled: true,
+			eip_7516_enabled: true,
+			eip_6780_enabled: true,
+			eip_7702_enabled: false,
 			eip_1559_enabled: true,
+			blob_schedule: Some(BlobSchedule::CANCUN),
+		}
+	}
+
+	const fn prague() -> Self {
+		Self {
+			eip_7702_enabled: true,
+			blob_schedule: Some(BlobSchedule::PRAGUE),
+			..Self::cancun()
+		}
+	}
+}
+
+/// Hard fork with a configuration preset.
+///
+/// Forks are parsed from their lowercase names, case-insensitively, with
+/// `paris` accepted as an alias of `merge`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Fork {
+	/// Frontier.
+	Frontier,
+	/// Istanbul.
+	Istanbul,
+	/// Berlin.
+	Berlin,
+	/// London.
+	London,
+	/// The Merge (Paris).
+	Merge,
+	/// Shanghai.
+	Shanghai,
+	/// Cancun.
+	Cancun,
+	/// Prague.
+	Prague,
+}
+
+impl Fork {
+	/// All forks, in activation order.
+	pub const ALL: [Fork; 8] = [
+		Fork::Frontier,
+		Fork::Istanbul,
+		Fork::Berlin,
+		Fork::London,
+		Fork::Merge,
+		Fork::Shanghai,
+		Fork::Cancun,
+		Fork::Prague,
+	];
+
+	/// Lowercase name of the fork.
+	pub const fn name(self) -> &'static str {
+		match self {
+			Fork::Frontier => "frontier",
+			Fork::Istanbul => "istanbul",
+			Fork::Berlin => "berlin",
+			Fork::London => "london",
+			Fork::Merge => "merge",
+			Fork::Shanghai => "shanghai",
+			Fork::Cancun => "cancun",
+			Fork::Prague => "prague",
+		}
+	}
+
+	/// Configuration of the fork.
+	pub const fn config(self) -> Config {
+		match self {
+			Fork::Frontier => Config::frontier(),
+			Fork::Istanbul => Config::istanbul(),
+			Fork::Berlin => Config::berlin(),
+			Fork::London => Config::london(),
+			Fork::Merge => Config::merge(),
+			Fork::Shanghai => Config::shanghai(),
+			Fork::Cancun => Config::cancun(),
+			Fork::Prague => Config::prague(),
+		}
+	}
+}
+
+impl fmt::Display for Fork {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(self.name())
+	}
+}
+
+/// Error parsing an unknown fork name.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UnknownFork(pub String);
+
+impl fmt::Display for UnknownFork {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "unknown fork {:?}", self.0)
+	}
+}
+
+impl FromStr for Fork {
+	type Err = UnknownFork;
+
+	fn from_str(name: &str) -> Result<Self, Self::Err> {
+		if name.eq_ignore_ascii_case("paris") {
+			return Ok(Fork::Merge);
+		}
+
+		Fork::ALL
+			.iter()
+			.copied()
+			.find(|fork| fork.name().eq_ignore_ascii_case(name))
+			.ok_or_else(|| UnknownFork(name.into()))
+	}
+}
+
+/// EIP that can be toggled through [ConfigBuilder].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Eip {
+	/// Transient storage.
+	Eip1153,
+	/// Fee market, burning the base fee.
+	Eip1559,
+	/// Gas cost increases for state access opcodes, tracking accessed
+	/// addresses and storage keys.
+	Eip2929,
+	/// Access list costs.
+	Eip2930,
+	/// AUTH and AUTHCALL opcodes.
+	Eip3074,
+	/// BASEFEE opcode.
+	Eip3198,
+	/// Reduction in refunds.
+	Eip3529,
+	/// Rejection of new code starting with `0xEF`.
+	Eip3541,
+	/// Warm coinbase address.
+	Eip3651,
+	/// PUSH0 opcode.
+	Eip3855,
+	/// Limit on the size of init code.
+	Eip3860,
+	/// PREVRANDAO in place of DIFFICULTY.
+	Eip4399,
+	/// Blob transactions and the BLOBHASH opcode.
+	Eip4844,
+	/// MCOPY opcode.
+	Eip5656,
+	/// SELFDESTRUCT only in the same transaction.
+	Eip6780,
+	/// BLOBBASEFEE opcode.
+	Eip7516,
+	/// Set-code transactions and code delegation.
+	Eip7702,
+}
+
+impl Eip {
+	/// Pairs of an EIP and the EIP it depends on, checked by
+	/// [Config::validate].
+	pub const DEPENDENCIES: [(Eip, Eip); 6] = [
+		(Eip::Eip2930, Eip::Eip2929),
+		(Eip::Eip3074, Eip::Eip2929),
+		(Eip::Eip3651, Eip::Eip2929),
+		(Eip::Eip3198, Eip::Eip1559),
+		(Eip::Eip7516, Eip::Eip4844),
+		(Eip::Eip7702, Eip::Eip2929),
+	];
+
+	/// Number of the EIP.
+	pub const fn number(self) -> u32 {
+		match self {
+			Eip::Eip1153 => 1153,
+			Eip::Eip1559 => 1559,
+			Eip::Eip2929 => 2929,
+			Eip::Eip2930 => 2930,
+			Eip::Eip3074 => 3074,
+			Eip::Eip3198 => 3198,
+			Eip::Eip3529 => 3529,
+			Eip::Eip3541 => 3541,
+			Eip::Eip3651 => 3651,
+			Eip::Eip3855 => 3855,
+			Eip::Eip3860 => 3860,
+			Eip::Eip4399 => 4399,
+			Eip::Eip4844 => 4844,
+			Eip::Eip5656 => 5656,
+			Eip::Eip6780 => 6780,
+			Eip::Eip7516 => 7516,
+			Eip::Eip7702 => 7702,
+		}
+	}
+
+	/// Whether the EIP is enabled in the configuration.
+	pub fn is_enabled(self, config: &Config) -> bool {
+		match self {
+			Eip::Eip1153 => config.eip_1153_enabled,
+			Eip::Eip1559 => config.eip_1559_enabled,
+			Eip::Eip2929 => config.increase_state_access_gas,
+			Eip::Eip2930 => {
+				config.gas_access_list_address != 0 || config.gas_access_list_storage_key != 0
+			}
+			Eip::Eip3074 => config.eip_3074_enabled,
+			Eip::Eip3198 => config.has_base_fee,
+			Eip::Eip3529 => config.decrease_clears_refund,
+			Eip::Eip3541 => config.disallow_executable_format,
+			Eip::Eip3651 => config.warm_coinbase_address,
+			Eip::Eip3855 => config.has_push0,
+			Eip::Eip3860 => config.max_initcode_size.is_some(),
+			Eip::Eip4399 => config.has_prevrandao,
+			Eip::Eip4844 => config.eip_4844_enabled,
+			Eip::Eip5656 => config.eip_5656_enabled,
+			Eip::Eip6780 => config.eip_6780_enabled,
+			Eip::Eip7516 => config.eip_7516_enabled,
+			Eip::Eip7702 => config.eip_7702_enabled,
+		}
+	}
+
+	/// Set the fields of the configuration governed by the EIP, to their
+	/// value with the EIP enabled or, otherwise, with it disabled. Gas costs
+	/// changed by EIP-2929 fall back to their Istanbul values.
+	fn apply(self, config: &mut Config, enabled: bool) {
+		match self {
+			Eip::Eip1153 => config.eip_1153_enabled = enabled,
+			Eip::Eip1559 => config.eip_1559_enabled = enabled,
+			Eip::Eip2929 => {
+				let (legacy_cost, warm, cold_sload, cold_account) = if enabled {
+					(0, 100, 2100, 2600)
+				} else {
+					(700, 0, 0, 0)
+				};
+				config.gas_ext_code = legacy_cost;
+				config.gas_ext_code_hash = legacy_cost;
+				config.gas_balance = legacy_cost;
+				config.gas_call = legacy_cost;
+				config.gas_sload = if enabled { warm } else { 800 };
+				config.gas_sload_cold = cold_sload;
+				config.gas_sstore_reset = 5000 - cold_sload;
+				config.gas_account_access_cold = cold_account;
+				config.gas_storage_read_warm = warm;
+				config.increase_state_access_gas = enabled;
+			}
+			Eip::Eip2930 => {
+				let (address, storage_key) = if enabled { (2400, 1900) } else { (0, 0) };
+				config.gas_access_list_address = address;
+				config.gas_access_list_storage_key = storage_key;
+			}
+			Eip::Eip3074 => config.eip_3074_enabled = enabled,
+			Eip::Eip3198 => config.has_base_fee = enabled,
+			Eip::Eip3529 => config.decrease_clears_refund = enabled,
+			Eip::Eip3541 => config.disallow_executable_format = enabled,
+			Eip::Eip3651 => config.warm_coinbase_address = enabled,
+			Eip::Eip3855 => config.has_push0 = enabled,
+			Eip::Eip3860 => {
+				// 2 * (MAX_CODE_SIZE = `24576`) = (0xC000 = 49152) as per EIP-3860
+				config.max_initcode_size = if enabled { Some(0xC000) } else { None };
+			}
+			Eip::Eip4399 => config.has_prevrandao = enabled,
+			Eip::Eip4844 => {
+				config.eip_4844_enabled = enabled;
+				config.blob_schedule = if enabled {
+					config.blob_schedule.or(Some(BlobSchedule::CANCUN))
+				} else {
+					None
+				};
+			}
+			Eip::Eip5656 => config.eip_5656_enabled = enabled,
+			Eip::Eip6780 => config.eip_6780_enabled = enabled,
+			Eip::Eip7516 => config.eip_7516_enabled = enabled,
+			Eip::Eip7702 => config.eip_7702_enabled = enabled,
+		}
+
+		// See https://eips.ethereum.org/EIPS/eip-3529
+		config.refund_sstore_clears = if config.decrease_clears_refund {
+			(config.gas_sstore_reset + config.gas_access_list_storage_key) as i64
+		} else {
+			15000
+		};
+		config.max_refund_quotient = if config.decrease_clears_refund { 5 } else { 2 };
+	}
+}
+
+impl fmt::Display for Eip {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "EIP-{}", self.number())
+	}
+}
+
+/// Error of an EIP enabled without the EIP it depends on, see
+/// [Config::validate].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct UnmetEipDependency {
+	/// The enabled EIP.
+	pub eip: Eip,
+	/// The disabled EIP it depends on.
+	pub requires: Eip,
+}
+
+impl fmt::Display for UnmetEipDependency {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{} requires {}", self.eip, self.requires)
+	}
+}
+
+/// Builder of a [Config], starting from the preset of a fork and toggling
+/// individual EIPs on top of it.
+#[derive(Clone, Debug)]
+pub struct ConfigBuilder {
+	base: Fork,
+	changes: Vec<(Eip, bool)>,
+}
+
+impl ConfigBuilder {
+	/// Builder based on the latest fork, without changes.
+	pub fn new() -> Self {
+		Self {
+			base: Fork::Prague,
+			changes: Vec::new(),
+		}
+	}
+
+	/// Set the fork whose preset the changes are applied to.
+	pub fn base(mut self, fork: Fork) -> Self {
+		self.base = fork;
+		self
+	}
+
+	/// Enable an EIP.
+	pub fn enable(mut self, eip: Eip) -> Self {
+		self.changes.push((eip, true));
+		self
+	}
+
+	/// Disable an EIP.
+	pub fn disable(mut self, eip: Eip) -> Self {
+		self.changes.push((eip, false));
+		self
+	}
+
+	/// Apply the changes in order, and validate the resulting configuration.
+	pub fn build(self) -> Result<Config, UnmetEipDependency> {
+		let mut config = self.base.config();
+		for (eip, enabled) in self.changes {
+			eip.apply(&mut config, enabled);
 		}
+		config.validate()?;
+
+		Ok(config)
+	}
+}
+
+impl Default for ConfigBuilder {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn diff_lists_changed_fields() {
+		assert!(Config::cancun().diff(&Config::cancun()).is_empty());
+
+		let changes = Config::shanghai().diff(&Config::cancun());
+		let fields = changes.iter().map(|c| c.field).collect::<Vec<_>>();
+		assert_eq!(
+			fields,
+			[
+				"blob_schedule",
+				"eip_1153_enabled",
+				"eip_5656_enabled",
+				"eip_4844_enabled",
+				"eip_7516_enabled",
+				"eip_6780_enabled"
+			]
+		);
+		assert_eq!(changes[1].old, "false");
+		assert_eq!(changes[1].new, "true");
+
+		let changes = Config::cancun().diff(&Config::prague());
+		let fields = changes.iter().map(|c| c.field).collect::<Vec<_>>();
+		assert_eq!(fields, ["blob_schedule", "eip_7702_enabled"]);
+	}
+
+	#[test]
+	fn forks_parse_from_names() {
+		for fork in Fork::ALL {
+			assert_eq!(fork.name().parse::<Fork>(), Ok(fork));
+		}
+		assert_eq!("Paris".parse::<Fork>(), Ok(Fork::Merge));
+		assert_eq!("LONDON".parse::<Fork>(), Ok(Fork::London));
+		assert_eq!("osaka".parse::<Fork>(), Err(UnknownFork("osaka".into())));
+
+		let config = Config::by_name("cancun").unwrap();
+		assert!(config.diff(&Config::cancun()).is_empty());
+		assert!(Config::by_name("unknown").is_none());
+	}
+
+	#[test]
+	fn builder_toggles_eips() {
+		for fork in Fork::ALL {
+			let config = Config::builder().base(fork).build().unwrap();
+			assert!(config.diff(&fork.config()).is_empty());
+		}
+
+		let config = Config::builder()
+			.base(Fork::London)
+			.enable(Eip::Eip3855)
+			.disable(Eip::Eip3529)
+			.build()
+			.unwrap();
+		let fields = config
+			.diff(&Config::london())
+			.iter()
+			.map(|c| c.field)
+			.collect::<Vec<_>>();
+		assert_eq!(
+			fields,
+			[
+				"refund_sstore_clears",
+				"max_refund_quotient",
+				"decrease_clears_refund",
+				"has_push0"
+			]
+		);
+		assert_eq!(config.refund_sstore_clears, 15000);
+
+		let config = Config::builder()
+			.base(Fork::Berlin)
+			.disable(Eip::Eip2929)
+			.disable(Eip::Eip2930)
+			.build()
+			.unwrap();
+		assert!(config.diff(&Config::istanbul()).is_empty());
+
+		assert_eq!(
+			Config::builder().disable(Eip::Eip2929).build().unwrap_err(),
+			UnmetEipDependency {
+				eip: Eip::Eip2930,
+				requires: Eip::Eip2929,
+			}
+		);
+		assert!(Config::builder()
+			.base(Fork::Istanbul)
+			.enable(Eip::Eip7516)
+			.build()
+			.is_err());
 	}
 }
@@ -0,0 +1,140 @@
+use alloc::vec::Vec;
+
+use evm_interpreter::{
+	error::ExitError,
+	runtime::{RuntimeBackend, RuntimeEnvironment, SetCodeOrigin},
+};
+use primitive_types::{H160, H256, U256};
+use sha3::{Digest, Keccak256};
+
+/// Prefix of the code of an account delegating to another one, as in
+/// [EIP-7702](https://eips.ethereum.org/EIPS/eip-7702).
+pub const DELEGATION_PREFIX: [u8; 3] = [0xef, 0x01, 0x00];
+
+/// Intrinsic gas of each authorization of a set-code transaction.
+pub const PER_EMPTY_ACCOUNT_COST: u64 = 25000;
+
+/// Part of [PER_EMPTY_ACCOUNT_COST] kept when the authority already exists.
+pub const PER_AUTH_BASE_COST: u64 = 12500;
+
+/// Magic prefix of the message signed for an authorization.
+const SET_CODE_MAGIC: u8 = 0x05;
+
+/// Half of the secp256k1 curve order. Signatures with a higher `s` are
+/// rejected, as in EIP-2.
+const SECP256K1N_HALF: H256 = H256([
+	0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+	0x5d, 0x57, 0x6e, 0x73, 0x57, 0xa4, 0x50, 0x1d, 0xdf, 0xe9, 0x2f, 0x46, 0x68, 0x1b, 0x20, 0xa0,
+]);
+
+/// Code of an account delegating to `address`. Delegating to the zero
+/// address clears the code instead.
+pub fn delegation_designator(address: H160) -> Vec<u8> {
+	let mut code = DELEGATION_PREFIX.to_vec();
+	code.extend_from_slice(&address[..]);
+	code
+}
+
+/// Account the code delegates to, if it is a delegation designator.
+pub fn delegation_target(code: &[u8]) -> Option<H160> {
+	if code.len() == DELEGATION_PREFIX.len() + 20 && code.starts_with(&DELEGATION_PREFIX) {
+		Some(H160::from_slice(&code[DELEGATION_PREFIX.len()..]))
+	} else {
+		None
+	}
+}
+
+/// Signed authorization of a set-code transaction, letting `address` run as
+/// the code of the signer.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Authorization {
+	/// Chain the authorization is valid on, or zero for any chain.
+	pub chain_id: U256,
+	/// Account to delegate to.
+	pub address: H160,
+	/// Nonce of the signer.
+	pub nonce: u64,
+	/// Parity of the `y` coordinate of the signature.
+	pub y_parity: bool,
+	/// `r` of the signature.
+	pub r: H256,
+	/// `s` of the signature.
+	pub s: H256,
+}
+
+impl Authorization {
+	/// Signed message, `keccak256(0x05 || rlp([chain_id, address, nonce]))`.
+	pub fn message(&self) -> H256 {
+		let mut stream = rlp::RlpStream::new_list(3);
+		stream.append(&self.chain_id);
+		stream.append(&self.address);
+		stream.append(&self.nonce);
+
+		let mut hasher = Keccak256::new();
+		hasher.update([SET_CODE_MAGIC]);
+		hasher.update(stream.out());
+		H256::from_slice(hasher.finalize().as_slice())
+	}
+
+	/// Signer of the authorization, if the signature is valid.
+	pub fn authority<H: RuntimeEnvironment>(&self, handler: &H) -> Option<H160> {
+		if self.s > SECP256K1N_HALF {
+			return None;
+		}
+
+		handler.recover_signer(self.message(), self.y_parity, self.r, self.s)
+	}
+}
+
+/// Apply the authorizations of a set-code transaction in order, setting the
+/// code of their signers and increasing their nonces. Invalid authorizations
+/// are skipped. Returns the gas refunded for authorities that already
+/// existed.
+pub fn apply_authorizations<H>(
+	authorizations: &[Authorization],
+	handler: &mut H,
+) -> Result<i64, ExitError>
+where
+	H: RuntimeEnvironment + RuntimeBackend,
+{
+	let chain_id = handler.chain_id();
+	let mut refund = 0;
+
+	for authorization in authorizations {
+		if !authorization.chain_id.is_zero() && authorization.chain_id != chain_id {
+			continue;
+		}
+		if authorization.nonce == u64::MAX {
+			continue;
+		}
+		let authority = match authorization.authority(handler) {
+			Some(authority) => authority,
+			None => continue,
+		};
+
+		handler.mark_hot(authority, None);
+
+		let code = handler.code(authority);
+		if !code.is_empty() && delegation_target(&code).is_none() {
+			continue;
+		}
+		if handler.nonce(authority) != U256::from(authorization.nonce) {
+			continue;
+		}
+
+		if handler.exists(authority) {
+			refund += (PER_EMPTY_ACCOUNT_COST - PER_AUTH_BASE_COST) as i64;
+		}
+
+		let code = if authorization.address == H160::zero() {
+			Vec::new()
+		} else {
+			delegation_designator(authorization.address)
+		};
+		handler.set_code(authority, code, SetCodeOrigin::Transaction)?;
+		handler.inc_nonce(authority)?;
+	}
+
+	Ok(refund)
+}
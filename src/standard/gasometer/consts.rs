@@ -7,10 +7,17 @@ pub const G_LOW: u64 = 5;
 pub const G_MID: u64 = 8;
 pub const G_HIGH: u64 = 10;
 pub const G_JUMPDEST: u64 = 1;
+pub const G_DATALOAD: u64 = 4;
+pub const G_RJUMPI: u64 = 4;
+pub const G_CALLF: u64 = 5;
+pub const G_RETF: u64 = 3;
+pub const G_JUMPF: u64 = 5;
 pub const R_SUICIDE: i64 = 24000;
 pub const G_CREATE: u64 = 32000;
 pub const G_CALLVALUE: u64 = 9000;
 pub const G_NEWACCOUNT: u64 = 25000;
+pub const G_AUTH: u64 = 3100;
+pub const G_AUTHCALL_VALUE: u64 = 6700;
 pub const G_EXP: u64 = 10;
 pub const G_MEMORY: u64 = 3;
 pub const G_LOG: u64 = 375;
@@ -21,6 +28,7 @@ pub const G_SHA3WORD: u64 = 6;
 pub const G_COPY: u64 = 3;
 pub const G_BLOCKHASH: u64 = 20;
 pub const G_CODEDEPOSIT: u64 = 200;
+pub const G_INITCODE_WORD: u64 = 2;
 
 pub static STATIC_COST_TABLE: [Option<u64>; 256] = {
 	let mut table = [None; 256];
@@ -137,5 +145,19 @@ pub static STATIC_COST_TABLE: [Option<u64>; 256] = {
 	table[Opcode::JUMPI.as_usize()] = Some(G_HIGH);
 	table[Opcode::JUMPDEST.as_usize()] = Some(G_JUMPDEST);
 
+	table[Opcode::DATALOAD.as_usize()] = Some(G_DATALOAD);
+	table[Opcode::DATALOADN.as_usize()] = Some(G_VERYLOW);
+	table[Opcode::DATASIZE.as_usize()] = Some(G_BASE);
+
+	table[Opcode::RJUMP.as_usize()] = Some(G_BASE);
+	table[Opcode::RJUMPI.as_usize()] = Some(G_RJUMPI);
+	table[Opcode::RJUMPV.as_usize()] = Some(G_RJUMPI);
+	table[Opcode::CALLF.as_usize()] = Some(G_CALLF);
+	table[Opcode::RETF.as_usize()] = Some(G_RETF);
+	table[Opcode::JUMPF.as_usize()] = Some(G_JUMPF);
+	table[Opcode::DUPN.as_usize()] = Some(G_VERYLOW);
+	table[Opcode::SWAPN.as_usize()] = Some(G_VERYLOW);
+	table[Opcode::EXCHANGE.as_usize()] = Some(G_VERYLOW);
+
 	table
 };
@@ -59,8 +59,34 @@ pub fn sstore_refund(original: H256, current: H256, new: H256, config: &Config)
 	}
 }
 
-pub fn create2_cost(len: U256) -> Result<u64, ExitException> {
-	let base = U256::from(G_CREATE);
+/// Cost of the initcode of `CREATE` and `CREATE2`, failing if it exceeds the
+/// size limit (see EIP-3860).
+fn initcode_cost(len: U256, config: &Config) -> Result<U256, ExitException> {
+	let limit = match config.max_initcode_size {
+		Some(limit) => limit,
+		None => return Ok(U256::zero()),
+	};
+	if len > U256::from(limit) {
+		return Err(ExitException::CreateContractLimit);
+	}
+
+	// ceil(len / 32.0), bounded by the limit.
+	let words = (len.as_u64() + 31) / 32;
+	Ok(U256::from(G_INITCODE_WORD * words))
+}
+
+pub fn create_cost(len: U256, config: &Config) -> Result<u64, ExitException> {
+	let gas = U256::from(G_CREATE) + initcode_cost(len, config)?;
+
+	if gas > U256::from(u64::MAX) {
+		return Err(ExitException::OutOfGas);
+	}
+
+	Ok(gas.as_u64())
+}
+
+pub fn create2_cost(len: U256, config: &Config) -> Result<u64, ExitException> {
+	let base = U256::from(G_CREATE) + initcode_cost(len, config)?;
 	// ceil(len / 32.0)
 	let sha_addup_base = len / U256::from(32)
 		+ if len % U256::from(32) == U256::zero() {
@@ -284,6 +310,21 @@ pub fn call_cost(
 		+ new_cost(is_call_or_staticcall, new_account, transfers_value, config)
 }
 
+/// Gas cost of `AUTH`, before memory expansion (see EIP-3074).
+pub fn auth_cost(authority_is_cold: bool, config: &Config) -> u64 {
+	G_AUTH + address_access_cost(authority_is_cold, config.gas_balance, config)
+}
+
+/// Gas cost of `AUTHCALL`, which charges less for value transfers than
+/// `CALL` as it adds no call stipend (see EIP-3074).
+pub fn authcall_cost(value: U256, is_cold: bool, new_account: bool, config: &Config) -> u64 {
+	let transfers_value = value != U256::default();
+	let value_cost = if transfers_value { G_AUTHCALL_VALUE } else { 0 };
+	address_access_cost(is_cold, config.gas_call, config)
+		+ value_cost
+		+ new_cost(true, new_account, transfers_value, config)
+}
+
 pub fn address_access_cost(is_cold: bool, regular_value: u64, config: &Config) -> u64 {
 	if config.increase_state_access_gas {
 		if is_cold {
@@ -328,11 +369,14 @@ fn new_cost(
 	}
 }
 
-pub fn memory_gas(a: usize) -> Result<u64, ExitException> {
-	let a = a as u64;
+/// Gas cost of a memory of the given number of 32-byte words, `3 * words +
+/// words * words / 512` (see the yellow paper). Fails with
+/// [ExitException::MemoryLimit] if the cost does not fit in `u64`.
+pub fn memory_gas(words: usize) -> Result<u64, ExitException> {
+	let a = words as u64;
 	G_MEMORY
 		.checked_mul(a)
-		.ok_or(ExitException::OutOfGas)?
-		.checked_add(a.checked_mul(a).ok_or(ExitException::OutOfGas)? / 512)
-		.ok_or(ExitException::OutOfGas)
+		.ok_or(ExitException::MemoryLimit)?
+		.checked_add(a.checked_mul(a).ok_or(ExitException::MemoryLimit)? / 512)
+		.ok_or(ExitException::MemoryLimit)
 }
@@ -2,8 +2,11 @@ mod consts;
 mod costs;
 mod utils;
 
-use alloc::vec::Vec;
-use core::cmp::{max, min};
+use alloc::{sync::Arc, vec::Vec};
+use core::{
+	cmp::{max, min},
+	sync::atomic::{AtomicUsize, Ordering},
+};
 
 use evm_interpreter::{
 	error::{ExitError, ExitException},
@@ -14,13 +17,21 @@ use evm_interpreter::{
 };
 use primitive_types::{H160, H256, U256};
 
-use crate::{standard::Config, MergeStrategy};
+pub use self::costs::memory_gas;
+use crate::{
+	standard::{delegation_target, Config, DELEGATION_PREFIX, PER_EMPTY_ACCOUNT_COST},
+	MergeStrategy,
+};
 
 pub struct GasometerState<'config> {
 	gas_limit: u64,
 	memory_gas: u64,
+	memory_words: usize,
+	total_memory: Arc<AtomicUsize>,
 	used_gas: u64,
 	refunded_gas: u64,
+	sponsored_gas: u64,
+	depth: u32,
 	pub is_static: bool,
 	pub config: &'config Config,
 }
@@ -54,6 +65,16 @@ impl<'config> GasometerState<'config> {
 		self.used_gas + self.memory_gas
 	}
 
+	/// Call depth of the current interpreter.
+	pub fn depth(&self) -> u32 {
+		self.depth
+	}
+
+	/// Gas limit of the current interpreter.
+	pub fn gas_limit64(&self) -> u64 {
+		self.gas_limit
+	}
+
 	/// Left gas that is supposed to be available to the current interpreter.
 	pub fn gas64(&self) -> u64 {
 		self.gas_limit - self.memory_gas - self.used_gas
@@ -86,6 +107,37 @@ impl<'config> GasometerState<'config> {
 		self.record_gas64(cost.as_u64())
 	}
 
+	/// Gas refunded so far, before the refund cap is applied.
+	pub fn refunded_gas(&self) -> u64 {
+		self.refunded_gas
+	}
+
+	/// Gas granted with [GasometerState::sponsor] by this frame and its
+	/// returning subframes.
+	pub fn sponsored_gas(&self) -> u64 {
+		self.sponsored_gas
+	}
+
+	/// Grant additional gas to the current frame, on behalf of a sponsor
+	/// settling its cost outside of the transaction. The gas limit of the
+	/// frame is raised, and the gas left unused is returned to the parent
+	/// frame as usual. Gas sponsored in a frame exiting with an error is
+	/// consumed with the rest of its gas.
+	pub fn sponsor(&mut self, gas: u64) {
+		self.gas_limit = self.gas_limit.saturating_add(gas);
+		self.sponsored_gas = self.sponsored_gas.saturating_add(gas);
+	}
+
+	/// Record a refund. A negative refund removes previously refunded gas,
+	/// down to zero.
+	pub fn record_refund(&mut self, refund: i64) {
+		if refund >= 0 {
+			self.refunded_gas = self.refunded_gas.saturating_add(refund as u64);
+		} else {
+			self.refunded_gas = self.refunded_gas.saturating_sub(refund.unsigned_abs());
+		}
+	}
+
 	pub fn record_codedeposit(&mut self, len: usize) -> Result<(), ExitError> {
 		self.perform(|gasometer| {
 			let cost = len as u64 * consts::G_CODEDEPOSIT;
@@ -109,13 +161,41 @@ impl<'config> GasometerState<'config> {
 		}
 	}
 
+	/// Record memory expansion to the given number of words, checking the
+	/// total memory of all frames against [Config::total_memory_limit].
+	pub fn record_memory_words(&mut self, words: usize) -> Result<(), ExitError> {
+		if words <= self.memory_words {
+			return Ok(());
+		}
+
+		let expansion = (words - self.memory_words)
+			.checked_mul(32)
+			.ok_or(ExitException::MemoryLimit)?;
+		let total = self
+			.total_memory
+			.load(Ordering::Relaxed)
+			.checked_add(expansion)
+			.ok_or(ExitException::MemoryLimit)?;
+		if total > self.config.total_memory_limit {
+			return Err(ExitException::MemoryLimit.into());
+		}
+
+		self.total_memory.store(total, Ordering::Relaxed);
+		self.memory_words = words;
+		Ok(())
+	}
+
 	/// Create a new gasometer with the given gas limit and chain config.
 	pub fn new(gas_limit: u64, is_static: bool, config: &'config Config) -> Self {
 		Self {
 			gas_limit,
 			memory_gas: 0,
+			memory_words: 0,
+			total_memory: Arc::new(AtomicUsize::new(0)),
 			used_gas: 0,
 			refunded_gas: 0,
+			sponsored_gas: 0,
+			depth: 0,
 			is_static,
 			config,
 		}
@@ -125,16 +205,13 @@ impl<'config> GasometerState<'config> {
 		gas_limit: U256,
 		data: &[u8],
 		access_list: &[(H160, Vec<H256>)],
+		authorization_len: usize,
 		config: &'config Config,
 	) -> Result<Self, ExitError> {
-		let gas_limit = if gas_limit > U256::from(u64::MAX) {
-			return Err(ExitException::OutOfGas.into());
-		} else {
-			gas_limit.as_u64()
-		};
-
+		let gas_limit = transaction_gas_limit(gas_limit, config)?;
 		let mut s = Self::new(gas_limit, false, config);
-		let transaction_cost = TransactionCost::call(data, access_list).cost(config);
+		let transaction_cost =
+			TransactionCost::call(data, access_list, authorization_len).cost(config);
 
 		s.record_gas64(transaction_cost)?;
 		Ok(s)
@@ -146,12 +223,7 @@ impl<'config> GasometerState<'config> {
 		access_list: &[(H160, Vec<H256>)],
 		config: &'config Config,
 	) -> Result<Self, ExitError> {
-		let gas_limit = if gas_limit > U256::from(u64::MAX) {
-			return Err(ExitException::OutOfGas.into());
-		} else {
-			gas_limit.as_u64()
-		};
-
+		let gas_limit = transaction_gas_limit(gas_limit, config)?;
 		let mut s = Self::new(gas_limit, false, config);
 		let transaction_cost = TransactionCost::create(code, access_list).cost(config);
 
@@ -188,21 +260,42 @@ impl<'config> GasometerState<'config> {
 			gas_limit = gas_limit.saturating_add(self.config.call_stipend);
 		}
 
-		Ok(Self::new(gas_limit, is_static, self.config))
+		let mut submeter = Self::new(gas_limit, is_static, self.config);
+		submeter.total_memory = self.total_memory.clone();
+		submeter.depth = self.depth + 1;
+		Ok(submeter)
 	}
 
 	pub fn merge(&mut self, other: Self, strategy: MergeStrategy) {
+		self.total_memory.store(
+			self.total_memory
+				.load(Ordering::Relaxed)
+				.saturating_sub(other.memory_words * 32),
+			Ordering::Relaxed,
+		);
+
 		match strategy {
 			MergeStrategy::Commit => {
+				self.absorb_sponsored(&other);
 				self.used_gas -= other.gas64();
 				self.refunded_gas += other.refunded_gas;
 			}
 			MergeStrategy::Revert => {
+				self.absorb_sponsored(&other);
 				self.used_gas -= other.gas64();
 			}
 			MergeStrategy::Discard => {}
 		}
 	}
+
+	/// Carry the gas sponsored in a returning subframe over to this frame.
+	/// The sponsored gas counts as used on top of the raised limit, so that
+	/// returning the unused gas of the subframe cannot underflow.
+	fn absorb_sponsored(&mut self, other: &Self) {
+		self.gas_limit = self.gas_limit.saturating_add(other.sponsored_gas);
+		self.used_gas = self.used_gas.saturating_add(other.sponsored_gas);
+		self.sponsored_gas = self.sponsored_gas.saturating_add(other.sponsored_gas);
+	}
 }
 
 pub fn eval<'config, S, H, Tr>(
@@ -215,7 +308,25 @@ where
 	S: AsRef<GasometerState<'config>> + AsMut<GasometerState<'config>> + AsRef<RuntimeState>,
 	H: RuntimeBackend,
 {
-	match eval_to_result(machine, handler, opcode, position) {
+	let result = eval_to_result(machine, handler, opcode, position);
+
+	#[cfg(feature = "invariants")]
+	{
+		let gasometer: &GasometerState = machine.state.as_ref();
+		assert!(
+			gasometer
+				.used_gas
+				.checked_add(gasometer.memory_gas)
+				.map_or(false, |used| used <= gasometer.gas_limit),
+			"{:?}: used gas {} and memory gas {} above gas limit {}",
+			opcode,
+			gasometer.used_gas,
+			gasometer.memory_gas,
+			gasometer.gas_limit,
+		);
+	}
+
+	match result {
 		Ok(()) => Control::Continue,
 		Err(err) => Control::Exit(Err(err)),
 	}
@@ -243,7 +354,22 @@ where
 		if let Some(cost) = consts::STATIC_COST_TABLE[opcode.as_usize()] {
 			gasometer.record_gas64(cost)?;
 		} else {
-			let (gas, memory_gas) = dynamic_opcode_cost(
+			if gasometer.is_static && modifies_state(opcode, &machine.stack, gasometer.config)? {
+				return Err(ExitException::StaticModeViolation {
+					opcode,
+					address,
+					depth: gasometer.depth,
+				}
+				.into());
+			}
+
+			let DynamicCost {
+				gas,
+				memory: memory_gas,
+				hot,
+				code_load,
+				delegation,
+			} = dynamic_opcode_cost(
 				address,
 				opcode,
 				&machine.stack,
@@ -251,19 +377,26 @@ where
 				gasometer.config,
 				handler,
 			)?;
-			let cost = gas.cost(gasometer.gas64(), gasometer.config)?;
+			if let Some((address, index)) = hot {
+				handler.mark_hot(address, index);
+			}
+			if let Some((target, _)) = delegation {
+				handler.mark_hot(target, None);
+			}
+			let cost = gas
+				.cost(gasometer.gas64(), gasometer.config)?
+				.checked_add(code_load)
+				.and_then(|cost| cost.checked_add(delegation_cost(delegation, gasometer.config)))
+				.ok_or(ExitException::OutOfGas)?;
 			let refund = gas.refund(gasometer.config);
 
 			gasometer.record_gas64(cost)?;
-			if refund >= 0 {
-				gasometer.refunded_gas += refund as u64;
-			} else {
-				gasometer.refunded_gas = gasometer.refunded_gas.saturating_sub(-refund as u64);
-			}
+			gasometer.record_refund(refund);
 			if let Some(memory_gas) = memory_gas {
-				let memory_cost = memory_gas.cost()?;
-				if let Some(memory_cost) = memory_cost {
+				if let Some(words) = memory_gas.words()? {
+					let memory_cost = memory_words_cost(words, gasometer.config)?;
 					gasometer.set_memory_gas(max(gasometer.memory_gas, memory_cost))?;
+					gasometer.record_memory_words(words)?;
 				}
 			}
 
@@ -275,6 +408,142 @@ where
 	})
 }
 
+/// Gas that evaluating the opcode on the machine would charge, including
+/// memory expansion, without modifying the gasometer or the handler. Accounts
+/// and storage slots accessed by the opcode are not marked as hot.
+///
+/// The cost is returned even if it exceeds the gas left. Errors are the
+/// ones the gasometer would exit with regardless of the gas left, such as
+/// invalid opcodes or static mode violations.
+pub fn preview_opcode_cost<'config, S, H>(
+	machine: &Machine<S>,
+	handler: &H,
+	opcode: Opcode,
+) -> Result<u64, ExitError>
+where
+	S: AsRef<GasometerState<'config>> + AsRef<RuntimeState>,
+	H: RuntimeBackend,
+{
+	if machine.code().is_empty() {
+		return Ok(0);
+	}
+
+	let gasometer: &GasometerState<'config> = machine.state.as_ref();
+	if let Some(cost) = consts::STATIC_COST_TABLE[opcode.as_usize()] {
+		return Ok(cost);
+	}
+
+	let address = AsRef::<RuntimeState>::as_ref(&machine.state)
+		.context
+		.address;
+	if gasometer.is_static && modifies_state(opcode, &machine.stack, gasometer.config)? {
+		return Err(ExitException::StaticModeViolation {
+			opcode,
+			address,
+			depth: gasometer.depth,
+		}
+		.into());
+	}
+
+	let DynamicCost {
+		gas,
+		memory: memory_gas,
+		code_load,
+		delegation,
+		..
+	} = dynamic_opcode_cost(
+		address,
+		opcode,
+		&machine.stack,
+		gasometer.is_static,
+		gasometer.config,
+		handler,
+	)?;
+	let mut cost = gas
+		.cost(gasometer.gas64(), gasometer.config)?
+		.saturating_add(code_load)
+		.saturating_add(delegation_cost(delegation, gasometer.config));
+	if let Some(memory_gas) = memory_gas {
+		if let Some(words) = memory_gas.words()? {
+			let memory_cost = memory_words_cost(words, gasometer.config)?;
+			cost = cost.saturating_add(memory_cost.saturating_sub(gasometer.memory_gas));
+		}
+	}
+
+	Ok(cost)
+}
+
+/// Gas charged for expanding a memory of `current_len` bytes so that it
+/// covers `offset..(offset + len)`. It is zero if the region is empty or
+/// already covered.
+pub fn memory_expansion_cost(
+	current_len: usize,
+	offset: U256,
+	len: U256,
+) -> Result<u64, ExitError> {
+	let rem = current_len % 32;
+	let current_words = if rem == 0 {
+		current_len / 32
+	} else {
+		current_len / 32 + 1
+	};
+	match (MemoryCost { offset, len }).words()? {
+		Some(words) if words > current_words => Ok(memory_gas(words)? - memory_gas(current_words)?),
+		_ => Ok(0),
+	}
+}
+
+/// Memory cost of the given number of words, checked against
+/// [Config::memory_limit].
+fn memory_words_cost(words: usize, config: &Config) -> Result<u64, ExitError> {
+	if words
+		.checked_mul(32)
+		.map_or(true, |len| len > config.memory_limit)
+	{
+		return Err(ExitException::MemoryLimit.into());
+	}
+
+	Ok(costs::memory_gas(words)?)
+}
+
+/// Whether the opcode modifies state, and is thus forbidden in a static
+/// context.
+fn modifies_state(opcode: Opcode, stack: &Stack, config: &Config) -> Result<bool, ExitError> {
+	Ok(match opcode {
+		Opcode::SSTORE
+		| Opcode::LOG0
+		| Opcode::LOG1
+		| Opcode::LOG2
+		| Opcode::LOG3
+		| Opcode::LOG4
+		| Opcode::CREATE
+		| Opcode::EOFCREATE
+		| Opcode::SUICIDE => true,
+		Opcode::TSTORE => config.eip_1153_enabled,
+		Opcode::CREATE2 => config.has_create2,
+		Opcode::CALL => U256::from_big_endian(&stack.peek(2)?[..]) != U256::zero(),
+		Opcode::AUTHCALL if config.eip_3074_enabled => {
+			U256::from_big_endian(&stack.peek(2)?[..]) != U256::zero()
+		}
+		_ => false,
+	})
+}
+
+/// Dynamic cost of an opcode.
+struct DynamicCost {
+	/// Gas cost.
+	gas: GasCost,
+	/// Memory accessed.
+	memory: Option<MemoryCost>,
+	/// Address, or address and storage slot, to be marked as hot.
+	hot: Option<(H160, Option<H256>)>,
+	/// Cold load cost of large code (see EIP-7907).
+	code_load: u64,
+	/// Account the call target delegates its code to, and whether it is cold
+	/// (see EIP-7702).
+	delegation: Option<(H160, bool)>,
+}
+
 /// Calculate the opcode cost.
 #[allow(clippy::nonminimal_bool)]
 fn dynamic_opcode_cost<H: RuntimeBackend>(
@@ -283,10 +552,14 @@ fn dynamic_opcode_cost<H: RuntimeBackend>(
 	stack: &Stack,
 	is_static: bool,
 	config: &Config,
-	handler: &mut H,
-) -> Result<(GasCost, Option<MemoryCost>), ExitError> {
+	handler: &H,
+) -> Result<DynamicCost, ExitError> {
+	let mut hot = None;
+	let mut code_load = 0;
+	let mut delegation = None;
 	let gas_cost = match opcode {
 		Opcode::RETURN => GasCost::Zero,
+		Opcode::RETURNCONTRACT => GasCost::Zero,
 
 		Opcode::MLOAD | Opcode::MSTORE | Opcode::MSTORE8 => GasCost::VeryLow,
 
@@ -305,12 +578,18 @@ fn dynamic_opcode_cost<H: RuntimeBackend>(
 		Opcode::BASEFEE if config.has_base_fee => GasCost::Base,
 		Opcode::BASEFEE => GasCost::Invalid(opcode),
 
+		Opcode::BLOBHASH if config.eip_4844_enabled => GasCost::VeryLow,
+		Opcode::BLOBHASH => GasCost::Invalid(opcode),
+
+		Opcode::BLOBBASEFEE if config.eip_7516_enabled => GasCost::Base,
+		Opcode::BLOBBASEFEE => GasCost::Invalid(opcode),
+
 		Opcode::EXTCODESIZE => {
 			let target = stack.peek(0)?.into();
 
 			// https://eips.ethereum.org/EIPS/eip-2929
 			let target_is_cold = handler.is_cold(target, None);
-			handler.mark_hot(target, None);
+			hot = Some((target, None));
 
 			GasCost::ExtCodeSize { target_is_cold }
 		}
@@ -319,7 +598,7 @@ fn dynamic_opcode_cost<H: RuntimeBackend>(
 
 			// https://eips.ethereum.org/EIPS/eip-2929
 			let target_is_cold = handler.is_cold(target, None);
-			handler.mark_hot(target, None);
+			hot = Some((target, None));
 
 			GasCost::Balance { target_is_cold }
 		}
@@ -330,7 +609,7 @@ fn dynamic_opcode_cost<H: RuntimeBackend>(
 
 			// https://eips.ethereum.org/EIPS/eip-2929
 			let target_is_cold = handler.is_cold(target, None);
-			handler.mark_hot(target, None);
+			hot = Some((target, None));
 
 			GasCost::ExtCodeHash { target_is_cold }
 		}
@@ -341,7 +620,9 @@ fn dynamic_opcode_cost<H: RuntimeBackend>(
 
 			// https://eips.ethereum.org/EIPS/eip-2929
 			let target_is_cold = handler.is_cold(target, None);
-			handler.mark_hot(target, None);
+			hot = Some((target, None));
+			code_load = large_code_cost(target, target_is_cold, config, handler);
+			delegation = delegated_access(target, config, handler);
 
 			GasCost::CallCode {
 				value: U256::from_big_endian(&stack.peek(2)?[..]),
@@ -355,7 +636,9 @@ fn dynamic_opcode_cost<H: RuntimeBackend>(
 
 			// https://eips.ethereum.org/EIPS/eip-2929
 			let target_is_cold = handler.is_cold(target, None);
-			handler.mark_hot(target, None);
+			hot = Some((target, None));
+			code_load = large_code_cost(target, target_is_cold, config, handler);
+			delegation = delegated_access(target, config, handler);
 
 			GasCost::StaticCall {
 				gas: U256::from_big_endian(&stack.peek(0)?[..]),
@@ -371,7 +654,8 @@ fn dynamic_opcode_cost<H: RuntimeBackend>(
 
 			// https://eips.ethereum.org/EIPS/eip-2929
 			let target_is_cold = handler.is_cold(target, None);
-			handler.mark_hot(target, None);
+			hot = Some((target, None));
+			code_load = large_code_cost(target, target_is_cold, config, handler);
 
 			GasCost::ExtCodeCopy {
 				target_is_cold,
@@ -381,7 +665,7 @@ fn dynamic_opcode_cost<H: RuntimeBackend>(
 		Opcode::MCOPY if config.eip_5656_enabled => GasCost::VeryLowCopy {
 			len: U256::from_big_endian(&stack.peek(2)?[..]),
 		},
-		Opcode::CALLDATACOPY | Opcode::CODECOPY => GasCost::VeryLowCopy {
+		Opcode::CALLDATACOPY | Opcode::CODECOPY | Opcode::DATACOPY => GasCost::VeryLowCopy {
 			len: U256::from_big_endian(&stack.peek(2)?[..]),
 		},
 		Opcode::EXP => GasCost::Exp {
@@ -391,8 +675,12 @@ fn dynamic_opcode_cost<H: RuntimeBackend>(
 			let index = stack.peek(0)?;
 
 			// https://eips.ethereum.org/EIPS/eip-2929
+			// Slots are kept as `H256` all the way from the stack to the backend,
+			// and only cold slots need to be inserted into the access set.
 			let target_is_cold = handler.is_cold(address, Some(index));
-			handler.mark_hot(address, Some(index));
+			if target_is_cold {
+				hot = Some((address, Some(index)));
+			}
 
 			GasCost::SLoad { target_is_cold }
 		}
@@ -403,7 +691,9 @@ fn dynamic_opcode_cost<H: RuntimeBackend>(
 
 			// https://eips.ethereum.org/EIPS/eip-2929
 			let target_is_cold = handler.is_cold(target, None);
-			handler.mark_hot(target, None);
+			hot = Some((target, None));
+			code_load = large_code_cost(target, target_is_cold, config, handler);
+			delegation = delegated_access(target, config, handler);
 
 			GasCost::DelegateCall {
 				gas: U256::from_big_endian(&stack.peek(0)?[..]),
@@ -425,7 +715,9 @@ fn dynamic_opcode_cost<H: RuntimeBackend>(
 
 			// https://eips.ethereum.org/EIPS/eip-2929
 			let target_is_cold = handler.is_cold(address, Some(index));
-			handler.mark_hot(address, Some(index));
+			if target_is_cold {
+				hot = Some((address, Some(index)));
+			}
 
 			GasCost::SStore {
 				original: handler.original_storage(address, index),
@@ -455,7 +747,10 @@ fn dynamic_opcode_cost<H: RuntimeBackend>(
 			n: 4,
 			len: U256::from_big_endian(&stack.peek(1)?[..]),
 		},
-		Opcode::CREATE if !is_static => GasCost::Create,
+		Opcode::CREATE if !is_static => GasCost::Create {
+			len: U256::from_big_endian(&stack.peek(2)?[..]),
+		},
+		Opcode::EOFCREATE if !is_static => GasCost::EofCreate,
 		Opcode::CREATE2 if !is_static && config.has_create2 => GasCost::Create2 {
 			len: U256::from_big_endian(&stack.peek(2)?[..]),
 		},
@@ -464,7 +759,7 @@ fn dynamic_opcode_cost<H: RuntimeBackend>(
 
 			// https://eips.ethereum.org/EIPS/eip-2929
 			let target_is_cold = handler.is_cold(target, None);
-			handler.mark_hot(target, None);
+			hot = Some((target, None));
 
 			GasCost::Suicide {
 				value: handler.balance(address),
@@ -481,7 +776,9 @@ fn dynamic_opcode_cost<H: RuntimeBackend>(
 
 			// https://eips.ethereum.org/EIPS/eip-2929
 			let target_is_cold = handler.is_cold(target, None);
-			handler.mark_hot(target, None);
+			hot = Some((target, None));
+			code_load = large_code_cost(target, target_is_cold, config, handler);
+			delegation = delegated_access(target, config, handler);
 
 			GasCost::Call {
 				value: U256::from_big_endian(&stack.peek(2)?[..]),
@@ -491,6 +788,37 @@ fn dynamic_opcode_cost<H: RuntimeBackend>(
 			}
 		}
 
+		Opcode::AUTH if config.eip_3074_enabled => {
+			let authority = stack.peek(0)?.into();
+
+			// https://eips.ethereum.org/EIPS/eip-3074
+			let authority_is_cold = handler.is_cold(authority, None);
+			hot = Some((authority, None));
+
+			GasCost::Auth { authority_is_cold }
+		}
+		Opcode::AUTHCALL
+			if config.eip_3074_enabled
+				&& (!is_static
+					|| (is_static
+						&& U256::from_big_endian(&stack.peek(2)?[..]) == U256::zero())) =>
+		{
+			let target = stack.peek(1)?.into();
+
+			// https://eips.ethereum.org/EIPS/eip-2929
+			let target_is_cold = handler.is_cold(target, None);
+			hot = Some((target, None));
+			code_load = large_code_cost(target, target_is_cold, config, handler);
+			delegation = delegated_access(target, config, handler);
+
+			GasCost::AuthCall {
+				value: U256::from_big_endian(&stack.peek(2)?[..]),
+				gas: U256::from_big_endian(&stack.peek(0)?[..]),
+				target_is_cold,
+				target_exists: { handler.exists(target) },
+			}
+		}
+
 		Opcode::PUSH0 if config.has_push0 => GasCost::Base,
 
 		_ => GasCost::Invalid(opcode),
@@ -499,6 +827,7 @@ fn dynamic_opcode_cost<H: RuntimeBackend>(
 	let memory_cost = match opcode {
 		Opcode::SHA3
 		| Opcode::RETURN
+		| Opcode::RETURNCONTRACT
 		| Opcode::REVERT
 		| Opcode::LOG0
 		| Opcode::LOG1
@@ -519,10 +848,12 @@ fn dynamic_opcode_cost<H: RuntimeBackend>(
 			})
 		}
 
-		Opcode::CODECOPY | Opcode::CALLDATACOPY | Opcode::RETURNDATACOPY => Some(MemoryCost {
-			offset: U256::from_big_endian(&stack.peek(0)?[..]),
-			len: U256::from_big_endian(&stack.peek(2)?[..]),
-		}),
+		Opcode::CODECOPY | Opcode::CALLDATACOPY | Opcode::RETURNDATACOPY | Opcode::DATACOPY => {
+			Some(MemoryCost {
+				offset: U256::from_big_endian(&stack.peek(0)?[..]),
+				len: U256::from_big_endian(&stack.peek(2)?[..]),
+			})
+		}
 
 		Opcode::EXTCODECOPY => Some(MemoryCost {
 			offset: U256::from_big_endian(&stack.peek(1)?[..]),
@@ -544,7 +875,17 @@ fn dynamic_opcode_cost<H: RuntimeBackend>(
 			len: U256::from_big_endian(&stack.peek(2)?[..]),
 		}),
 
-		Opcode::CALL | Opcode::CALLCODE => Some(
+		Opcode::EOFCREATE => Some(MemoryCost {
+			offset: U256::from_big_endian(&stack.peek(2)?[..]),
+			len: U256::from_big_endian(&stack.peek(3)?[..]),
+		}),
+
+		Opcode::AUTH => Some(MemoryCost {
+			offset: U256::from_big_endian(&stack.peek(1)?[..]),
+			len: U256::from_big_endian(&stack.peek(2)?[..]),
+		}),
+
+		Opcode::CALL | Opcode::CALLCODE | Opcode::AUTHCALL => Some(
 			MemoryCost {
 				offset: U256::from_big_endian(&stack.peek(3)?[..]),
 				len: U256::from_big_endian(&stack.peek(4)?[..]),
@@ -569,7 +910,65 @@ fn dynamic_opcode_cost<H: RuntimeBackend>(
 		_ => None,
 	};
 
-	Ok((gas_cost, memory_cost))
+	Ok(DynamicCost {
+		gas: gas_cost,
+		memory: memory_cost,
+		hot,
+		code_load,
+		delegation,
+	})
+}
+
+/// Account `target` delegates its code to, and whether it is cold, when
+/// [Config::eip_7702_enabled] is set.
+fn delegated_access<H: RuntimeBackend>(
+	target: H160,
+	config: &Config,
+	handler: &H,
+) -> Option<(H160, bool)> {
+	if !config.eip_7702_enabled
+		|| handler.code_size(target) != U256::from(DELEGATION_PREFIX.len() + 20)
+	{
+		return None;
+	}
+
+	delegation_target(&handler.code(target))
+		.map(|delegate| (delegate, handler.is_cold(delegate, None)))
+}
+
+/// Access cost of the code delegated to by a call target (see EIP-7702).
+fn delegation_cost(delegation: Option<(H160, bool)>, config: &Config) -> u64 {
+	match delegation {
+		Some((_, is_cold)) => costs::address_access_cost(is_cold, config.gas_call, config),
+		None => 0,
+	}
+}
+
+/// Cost of loading the code of `target` beyond
+/// [Config::large_code_threshold], if the account is cold (see EIP-7907).
+fn large_code_cost<H: RuntimeBackend>(
+	target: H160,
+	target_is_cold: bool,
+	config: &Config,
+	handler: &H,
+) -> u64 {
+	match config.large_code_threshold {
+		Some(threshold) if target_is_cold => {
+			let size = handler.code_size(target);
+			let threshold = U256::from(threshold);
+			if size <= threshold {
+				return 0;
+			}
+
+			let words = (size - threshold).saturating_add(U256::from(31)) / 32;
+			if words > U256::from(u64::MAX) {
+				u64::MAX
+			} else {
+				words.as_u64().saturating_mul(config.gas_large_code_word)
+			}
+		}
+		_ => 0,
+	}
 }
 
 /// Gas cost.
@@ -644,6 +1043,23 @@ enum GasCost {
 		/// Whether the target exists.
 		target_exists: bool,
 	},
+	/// Gas cost for `AUTH`.
+	Auth {
+		/// True if the authority has not been previously accessed in this
+		/// transaction
+		authority_is_cold: bool,
+	},
+	/// Gas cost for `AUTHCALL`.
+	AuthCall {
+		/// Call value.
+		value: U256,
+		/// Call gas.
+		gas: U256,
+		/// True if target has not been previously accessed in this transaction
+		target_is_cold: bool,
+		/// Whether the target exists.
+		target_exists: bool,
+	},
 	/// Gas cost for `SUICIDE`.
 	Suicide {
 		/// Value.
@@ -700,7 +1116,13 @@ enum GasCost {
 		power: U256,
 	},
 	/// Gas cost for `CREATE`.
-	Create,
+	Create {
+		/// Length.
+		len: U256,
+	},
+	/// Gas cost for `EOFCREATE`, whose initcode is a validated subcontainer
+	/// and is not charged per word.
+	EofCreate,
 	/// Gas cost for `CREATE2`.
 	Create2 {
 		/// Length.
@@ -753,6 +1175,13 @@ impl GasCost {
 				!target_exists,
 				config,
 			),
+			GasCost::Auth { authority_is_cold } => costs::auth_cost(authority_is_cold, config),
+			GasCost::AuthCall {
+				value,
+				target_is_cold,
+				target_exists,
+				..
+			} => costs::authcall_cost(value, target_is_cold, !target_exists, config),
 
 			GasCost::Suicide {
 				value,
@@ -772,8 +1201,9 @@ impl GasCost {
 			GasCost::Log { n, len } => costs::log_cost(n, len)?,
 			GasCost::VeryLowCopy { len } => costs::verylowcopy_cost(len)?,
 			GasCost::Exp { power } => costs::exp_cost(power, config)?,
-			GasCost::Create => consts::G_CREATE,
-			GasCost::Create2 { len } => costs::create2_cost(len)?,
+			GasCost::Create { len } => costs::create_cost(len, config)?,
+			GasCost::EofCreate => consts::G_CREATE,
+			GasCost::Create2 { len } => costs::create2_cost(len, config)?,
 			GasCost::SLoad { target_is_cold } => costs::sload_cost(target_is_cold, config),
 
 			GasCost::Zero => consts::G_ZERO,
@@ -822,6 +1252,7 @@ impl GasCost {
 			GasCost::CallCode { gas, .. } => costs::call_extra_check(gas, after_gas, config),
 			GasCost::DelegateCall { gas, .. } => costs::call_extra_check(gas, after_gas, config),
 			GasCost::StaticCall { gas, .. } => costs::call_extra_check(gas, after_gas, config),
+			GasCost::AuthCall { gas, .. } => costs::call_extra_check(gas, after_gas, config),
 			_ => Ok(()),
 		}
 	}
@@ -857,8 +1288,8 @@ impl MemoryCost {
 		}
 	}
 
-	/// Numeric value of the cost.
-	pub fn cost(&self) -> Result<Option<u64>, ExitError> {
+	/// Number of words the memory is expanded to.
+	pub fn words(&self) -> Result<Option<usize>, ExitError> {
 		let from = self.offset;
 		let len = self.len;
 
@@ -866,17 +1297,17 @@ impl MemoryCost {
 			return Ok(None);
 		}
 
-		let end = from.checked_add(len).ok_or(ExitException::OutOfGas)?;
+		let end = from.checked_add(len).ok_or(ExitException::MemoryLimit)?;
 
 		if end > U256::from(usize::MAX) {
-			return Err(ExitException::OutOfGas.into());
+			return Err(ExitException::MemoryLimit.into());
 		}
 		let end = end.as_usize();
 
 		let rem = end % 32;
 		let new = if rem == 0 { end / 32 } else { end / 32 + 1 };
 
-		Ok(Some(costs::memory_gas(new)?))
+		Ok(Some(new))
 	}
 }
 
@@ -893,6 +1324,8 @@ enum TransactionCost {
 		access_list_address_len: usize,
 		/// Total number of storage keys in transaction access list (see EIP-2930)
 		access_list_storage_len: usize,
+		/// Number of authorizations of a set-code transaction (see EIP-7702)
+		authorization_len: usize,
 	},
 	/// Create transaction cost.
 	Create {
@@ -910,7 +1343,11 @@ enum TransactionCost {
 }
 
 impl TransactionCost {
-	pub fn call(data: &[u8], access_list: &[(H160, Vec<H256>)]) -> TransactionCost {
+	pub fn call(
+		data: &[u8],
+		access_list: &[(H160, Vec<H256>)],
+		authorization_len: usize,
+	) -> TransactionCost {
 		let zero_data_len = data.iter().filter(|v| **v == 0).count();
 		let non_zero_data_len = data.len() - zero_data_len;
 		let (access_list_address_len, access_list_storage_len) = count_access_list(access_list);
@@ -920,6 +1357,7 @@ impl TransactionCost {
 			non_zero_data_len,
 			access_list_address_len,
 			access_list_storage_len,
+			authorization_len,
 		}
 	}
 
@@ -945,13 +1383,16 @@ impl TransactionCost {
 				non_zero_data_len,
 				access_list_address_len,
 				access_list_storage_len,
+				authorization_len,
 			} => {
-				#[deny(clippy::let_and_return)]
-				let cost = config.gas_transaction_call
+				let mut cost = config.gas_transaction_call
 					+ *zero_data_len as u64 * config.gas_transaction_zero_data
 					+ *non_zero_data_len as u64 * config.gas_transaction_non_zero_data
 					+ *access_list_address_len as u64 * config.gas_access_list_address
 					+ *access_list_storage_len as u64 * config.gas_access_list_storage_key;
+				if config.eip_7702_enabled {
+					cost += *authorization_len as u64 * PER_EMPTY_ACCOUNT_COST;
+				}
 
 				cost
 			}
@@ -977,6 +1418,20 @@ impl TransactionCost {
 	}
 }
 
+/// Gas limit of a transaction, checked against the cap of
+/// [Config::max_transaction_gas_limit].
+fn transaction_gas_limit(gas_limit: U256, config: &Config) -> Result<u64, ExitError> {
+	if gas_limit > U256::from(u64::MAX) {
+		return Err(ExitException::OutOfGas.into());
+	}
+
+	let gas_limit = gas_limit.as_u64();
+	match config.max_transaction_gas_limit {
+		Some(cap) if gas_limit > cap => Err(ExitException::TransactionGasLimitTooHigh.into()),
+		_ => Ok(gas_limit),
+	}
+}
+
 /// Counts the number of addresses and storage keys in the access list
 fn count_access_list(access_list: &[(H160, Vec<H256>)]) -> (usize, usize) {
 	let access_list_address_len = access_list.len();
@@ -989,5 +1444,179 @@ fn init_code_cost(data: &[u8]) -> u64 {
 	// As per EIP-3860:
 	// > We define initcode_cost(initcode) to equal INITCODE_WORD_COST * ceil(len(initcode) / 32).
 	// where INITCODE_WORD_COST is 2.
-	2 * ((data.len() as u64 + 31) / 32)
+	consts::G_INITCODE_WORD * ((data.len() as u64 + 31) / 32)
+}
+
+#[cfg(test)]
+mod tests {
+	use evm_interpreter::runtime::{Context, SetCodeOrigin, TransactionContext};
+
+	use super::*;
+	use crate::{
+		backend::{ChaosBackend, OverlayedBackend},
+		standard::{InvokerState, State},
+	};
+
+	#[test]
+	fn transaction_gas_limit_cap() {
+		let config = Config {
+			max_transaction_gas_limit: Some(1 << 24),
+			..Config::cancun()
+		};
+
+		assert!(
+			GasometerState::new_transact_call(U256::from(1 << 24), &[], &[], 0, &config).is_ok()
+		);
+		assert_eq!(
+			GasometerState::new_transact_create(U256::from((1 << 24) + 1), &[], &[], &config).err(),
+			Some(ExitException::TransactionGasLimitTooHigh.into())
+		);
+	}
+
+	#[test]
+	fn incremental_costs_and_refunds() {
+		let config = Config::cancun();
+		let mut parent = GasometerState::new(100_000, false, &config);
+		let mut gasometer = parent.submeter(U256::from(1_000), false, false).unwrap();
+
+		gasometer.record_gas64(400).unwrap();
+		assert_eq!(gasometer.gas64(), 600);
+		gasometer.record_refund(300);
+		gasometer.record_refund(-100);
+		assert_eq!(gasometer.refunded_gas(), 200);
+		gasometer.record_refund(-500);
+		assert_eq!(gasometer.refunded_gas(), 0);
+		gasometer.record_refund(50);
+		assert!(gasometer.record_gas64(601).is_err());
+
+		parent.merge(gasometer, MergeStrategy::Commit);
+		assert_eq!(parent.total_used_gas(), 400);
+		assert_eq!(parent.refunded_gas(), 50);
+	}
+
+	#[test]
+	fn sponsored_gas_is_returned_to_parent() {
+		let config = Config::cancun();
+		let mut parent = GasometerState::new(100_000, false, &config);
+
+		let mut gasometer = parent.submeter(U256::from(1_000), false, false).unwrap();
+		gasometer.sponsor(5_000);
+		gasometer.record_gas64(3_000).unwrap();
+		parent.merge(gasometer, MergeStrategy::Revert);
+		assert_eq!(parent.gas64(), 102_000);
+		assert_eq!(parent.sponsored_gas(), 5_000);
+
+		let mut gasometer = parent.submeter(U256::from(1_000), false, false).unwrap();
+		gasometer.sponsor(5_000);
+		parent.merge(gasometer, MergeStrategy::Discard);
+		assert_eq!(parent.gas64(), 101_000);
+		assert_eq!(parent.sponsored_gas(), 5_000);
+	}
+
+	#[test]
+	fn preview_matches_eval_without_marking_hot() {
+		let config = Config::cancun();
+		let address = H160::repeat_byte(0xaa);
+		let runtime = RuntimeState {
+			context: Context {
+				address,
+				caller: H160::default(),
+				apparent_value: U256::zero(),
+				authorized: None,
+			},
+			transaction_context: Arc::new(TransactionContext {
+				gas_price: U256::zero(),
+				origin: H160::default(),
+			}),
+			retbuf: Vec::new(),
+		};
+		let state =
+			State::new_transact_call(runtime, U256::from(100_000), &[], &[], 0, &config).unwrap();
+		let mut machine = Machine::new(
+			Arc::new(vec![Opcode::SLOAD.as_u8()]),
+			Arc::new(Vec::new()),
+			1024,
+			usize::MAX,
+			state,
+		);
+		machine.stack.push(H256::repeat_byte(1)).unwrap();
+		let mut handler = OverlayedBackend::new(ChaosBackend::new(1), Default::default());
+
+		let preview = preview_opcode_cost(&machine, &handler, Opcode::SLOAD).unwrap();
+		assert_eq!(preview, config.gas_sload_cold);
+		assert!(handler.is_cold(address, Some(H256::repeat_byte(1))));
+
+		let before = machine.state.gasometer.gas64();
+		let _ = eval::<_, _, ()>(&mut machine, &mut handler, Opcode::SLOAD, 0);
+		assert_eq!(before - machine.state.gasometer.gas64(), preview);
+		assert_eq!(
+			preview_opcode_cost(&machine, &handler, Opcode::SLOAD).unwrap(),
+			config.gas_storage_read_warm
+		);
+	}
+
+	#[test]
+	fn memory_expansion_cost_is_delta() {
+		assert_eq!(memory_gas(1).unwrap(), 3);
+		assert_eq!(memory_gas(1024).unwrap(), 3 * 1024 + 2048);
+		assert_eq!(
+			memory_expansion_cost(32, U256::zero(), U256::from(33)).unwrap(),
+			3
+		);
+		assert_eq!(
+			memory_expansion_cost(64, U256::zero(), U256::from(33)).unwrap(),
+			0
+		);
+		assert_eq!(
+			memory_expansion_cost(0, U256::MAX, U256::zero()).unwrap(),
+			0
+		);
+	}
+
+	#[test]
+	fn initcode_is_metered_and_capped() {
+		let shanghai = Config::shanghai();
+		let london = Config::london();
+		let limit = U256::from(0xC000);
+
+		// 2 gas for each of the 1536 words of the largest initcode.
+		assert_eq!(costs::create_cost(limit, &shanghai), Ok(32000 + 3072));
+		assert_eq!(
+			costs::create2_cost(limit, &shanghai),
+			Ok(32000 + 6 * 1536 + 3072)
+		);
+		assert_eq!(
+			costs::create_cost(limit + 1, &shanghai),
+			Err(ExitException::CreateContractLimit)
+		);
+		assert_eq!(
+			costs::create2_cost(limit + 1, &shanghai),
+			Err(ExitException::CreateContractLimit)
+		);
+		assert_eq!(costs::create_cost(limit + 1, &london), Ok(32000));
+
+		let init_code = vec![1; 0xC000];
+		let cost = TransactionCost::create(&init_code, &[]);
+		assert_eq!(cost.cost(&shanghai), 53000 + 16 * 0xC000 + 3072);
+		assert_eq!(cost.cost(&london), 53000 + 16 * 0xC000);
+	}
+
+	#[test]
+	fn large_code_is_charged_when_cold() {
+		let mut config = Config::cancun();
+		config.large_code_threshold = Some(0x6000);
+		let target = H160::repeat_byte(0xbb);
+		let mut handler = OverlayedBackend::new(
+			ChaosBackend::new(1).with_max_code_len(0),
+			Default::default(),
+		);
+		handler
+			.set_code(target, vec![0; 0x6000 + 33], SetCodeOrigin::Transaction)
+			.unwrap();
+
+		assert_eq!(large_code_cost(target, true, &config, &handler), 4);
+		assert_eq!(large_code_cost(target, false, &config, &handler), 0);
+		config.large_code_threshold = None;
+		assert_eq!(large_code_cost(target, true, &config, &handler), 0);
+	}
 }
@@ -0,0 +1,65 @@
+use core::cmp::min;
+
+use primitive_types::U256;
+
+/// Effective gas price of a transaction, split into the part paid as the base
+/// fee and the miner tip.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct EffectiveGasPrice {
+	/// Gas price paid by the caller.
+	pub gas_price: U256,
+	/// Part of the gas price paid to the block producer.
+	pub priority_fee: U256,
+}
+
+impl EffectiveGasPrice {
+	/// Compute the effective gas price from the fee cap and, for EIP-1559
+	/// transactions, the priority fee cap. Legacy transactions have no
+	/// priority fee cap, and their whole gas price above the base fee is the
+	/// tip. Fee caps below the base fee are not rejected, and the tip is then
+	/// zero.
+	pub fn new(
+		max_fee_per_gas: U256,
+		max_priority_fee_per_gas: Option<U256>,
+		base_fee_per_gas: U256,
+	) -> Self {
+		let gas_price = match max_priority_fee_per_gas {
+			Some(max_priority_fee_per_gas) => min(
+				max_fee_per_gas,
+				base_fee_per_gas.saturating_add(max_priority_fee_per_gas),
+			),
+			None => max_fee_per_gas,
+		};
+
+		Self {
+			gas_price,
+			priority_fee: gas_price.saturating_sub(base_fee_per_gas),
+		}
+	}
+
+	/// Part of the gas price paid as the base fee.
+	pub fn base_fee(&self) -> U256 {
+		self.gas_price - self.priority_fee
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn effective_gas_price() {
+		let price = EffectiveGasPrice::new(U256::from(100), Some(U256::from(5)), U256::from(90));
+		assert_eq!(price.gas_price, U256::from(95));
+		assert_eq!(price.priority_fee, U256::from(5));
+		assert_eq!(price.base_fee(), U256::from(90));
+
+		let capped = EffectiveGasPrice::new(U256::from(100), Some(U256::from(20)), U256::from(90));
+		assert_eq!(capped.gas_price, U256::from(100));
+		assert_eq!(capped.priority_fee, U256::from(10));
+
+		let legacy = EffectiveGasPrice::new(U256::from(100), None, U256::from(90));
+		assert_eq!(legacy.gas_price, U256::from(100));
+		assert_eq!(legacy.priority_fee, U256::from(10));
+	}
+}
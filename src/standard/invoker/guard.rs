@@ -0,0 +1,95 @@
+use alloc::{borrow::Cow, collections::BTreeMap, vec::Vec};
+
+use evm_interpreter::error::{ExitException, ExitResult};
+use primitive_types::{H160, U256};
+
+use crate::{standard::PrecompileSet, GasMutState};
+
+/// Limits checked before a precompile is invoked.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct PrecompileGuard {
+	/// Maximum input length. Calls with larger inputs fail without invoking
+	/// the precompile.
+	pub max_input_len: Option<usize>,
+	/// Gas charged for every call, before invoking the precompile.
+	pub base_cost: u64,
+	/// Gas charged per 32-byte word of input, before invoking the precompile.
+	pub word_cost: u64,
+}
+
+impl PrecompileGuard {
+	/// Cost pre-charged for an input of the given length.
+	pub fn cost(&self, input_len: usize) -> Option<U256> {
+		let words = U256::from(input_len).checked_add(U256::from(31))? / U256::from(32);
+		U256::from(self.word_cost)
+			.checked_mul(words)?
+			.checked_add(U256::from(self.base_cost))
+	}
+}
+
+/// Guards declared by a precompile set, by address.
+pub trait PrecompileGuards {
+	/// Guard of the precompile at the given address, if any.
+	fn guard(&self, code_address: H160) -> Option<PrecompileGuard>;
+}
+
+impl PrecompileGuards for BTreeMap<H160, PrecompileGuard> {
+	fn guard(&self, code_address: H160) -> Option<PrecompileGuard> {
+		self.get(&code_address).copied()
+	}
+}
+
+/// Precompile set enforcing [PrecompileGuard]s, protecting nodes from
+/// huge-input calls into custom precompiles.
+pub struct GuardedPrecompileSet<G, P> {
+	guards: G,
+	precompiles: P,
+}
+
+impl<G, P> GuardedPrecompileSet<G, P> {
+	/// Wrap a precompile set with its guards.
+	pub fn new(guards: G, precompiles: P) -> Self {
+		Self {
+			guards,
+			precompiles,
+		}
+	}
+}
+
+impl<S, H, G, P> PrecompileSet<S, H> for GuardedPrecompileSet<G, P>
+where
+	S: GasMutState,
+	G: PrecompileGuards,
+	P: PrecompileSet<S, H>,
+{
+	fn execute(
+		&self,
+		code_address: H160,
+		input: &[u8],
+		state: &mut S,
+		handler: &mut H,
+	) -> Option<(ExitResult, Vec<u8>)> {
+		if let Some(guard) = self.guards.guard(code_address) {
+			if guard
+				.max_input_len
+				.map_or(false, |max_input_len| input.len() > max_input_len)
+			{
+				return Some((
+					Err(ExitException::Other(Cow::Borrowed("precompile input too large")).into()),
+					Vec::new(),
+				));
+			}
+
+			let cost = match guard.cost(input.len()) {
+				Some(cost) => cost,
+				None => return Some((Err(ExitException::OutOfGas.into()), Vec::new())),
+			};
+			if let Err(err) = state.record_gas(cost) {
+				return Some((Err(err), Vec::new()));
+			}
+		}
+
+		self.precompiles
+			.execute(code_address, input, state, handler)
+	}
+}
@@ -0,0 +1,236 @@
+use alloc::{collections::BTreeMap, sync::Arc, vec::Vec};
+use core::cell::RefCell;
+
+use evm_interpreter::{
+	error::{Capture, ExitError, ExitFatal, ExitResult},
+	etable::EtableSet,
+	opcode::Opcode,
+	runtime::{GasState, RuntimeBackend, RuntimeState},
+	EtableInterpreter, Interpreter, RunInterpreter,
+};
+use primitive_types::{H160, H256, U256};
+
+use crate::{invoker::InvokerControl, standard::Resolver, GasMutState};
+
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+struct PureCallKey {
+	code_hash: H256,
+	input: Vec<u8>,
+	address: H160,
+	caller: H160,
+	apparent_value: U256,
+}
+
+#[derive(Clone, Debug)]
+struct PureCallEntry {
+	result: ExitResult,
+	retval: Vec<u8>,
+	used_gas: U256,
+}
+
+/// Cache of pure call results, shared by the calls of a simulation session.
+///
+/// A call is pure if its code only consists of opcodes that depend on
+/// nothing but the code, the input and the call context: arithmetic,
+/// hashing, memory, control flow, calldata, `ADDRESS`, `CALLER` and
+/// `CALLVALUE`. Any state, block or transaction access, `GAS`, subcall or
+/// log makes the code impure. Only the standard meaning of opcodes is
+/// considered, so the cache must not be used with etables redefining them.
+///
+/// The cache must be cleared, or a new one used, whenever the
+/// [Config](crate::standard::Config) changes, as gas costs are cached.
+#[derive(Default)]
+pub struct PureCallCache {
+	calls: RefCell<BTreeMap<PureCallKey, PureCallEntry>>,
+	pure_codes: RefCell<BTreeMap<H256, bool>>,
+}
+
+impl PureCallCache {
+	/// Create an empty cache.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Number of cached call results.
+	pub fn len(&self) -> usize {
+		self.calls.borrow().len()
+	}
+
+	/// Whether no call result is cached.
+	pub fn is_empty(&self) -> bool {
+		self.calls.borrow().is_empty()
+	}
+
+	/// Remove all cached results.
+	pub fn clear(&self) {
+		self.calls.borrow_mut().clear();
+		self.pure_codes.borrow_mut().clear();
+	}
+
+	fn is_pure(&self, code_hash: H256, code: &[u8]) -> bool {
+		*self
+			.pure_codes
+			.borrow_mut()
+			.entry(code_hash)
+			.or_insert_with(|| is_pure_code(code))
+	}
+}
+
+/// Whether the code only consists of pure opcodes.
+fn is_pure_code(code: &[u8]) -> bool {
+	let mut position = 0;
+	while position < code.len() {
+		let opcode = Opcode(code[position]);
+		let pure = matches!(opcode.0, 0x00..=0x0b | 0x10..=0x1d | 0x20 | 0x50..=0x53 | 0x56..=0x59 | 0x5b | 0x5e..=0x9f)
+			|| matches!(
+				opcode,
+				Opcode::ADDRESS
+					| Opcode::CALLER
+					| Opcode::CALLVALUE
+					| Opcode::CALLDATALOAD
+					| Opcode::CALLDATASIZE
+					| Opcode::CALLDATACOPY
+					| Opcode::CODESIZE
+					| Opcode::CODECOPY
+					| Opcode::RETURNDATASIZE
+					| Opcode::RETURNDATACOPY
+					| Opcode::RETURN
+					| Opcode::REVERT
+					| Opcode::INVALID
+			);
+		if !pure {
+			return false;
+		}
+
+		position += 1 + opcode.is_push().map(usize::from).unwrap_or(0);
+	}
+	true
+}
+
+/// Resolver wrapper short-circuiting repeated identical pure calls, such as
+/// `STATICCALL`s into hashing or math libraries, with the results of a
+/// [PureCallCache].
+///
+/// Pure calls are executed directly by the resolver, and their result is
+/// cached if they succeed or revert. Later calls with the same code hash,
+/// input and context return the cached result and charge the cached gas, as
+/// long as enough gas is left.
+pub struct MemoizingResolver<'cache, R> {
+	resolver: R,
+	cache: &'cache PureCallCache,
+}
+
+impl<'cache, R> MemoizingResolver<'cache, R> {
+	/// Wrap a resolver.
+	pub fn new(resolver: R, cache: &'cache PureCallCache) -> Self {
+		Self { resolver, cache }
+	}
+}
+
+impl<'cache, 'etable, H, R, ES> Resolver<H> for MemoizingResolver<'cache, R>
+where
+	R: Resolver<H, State = ES::State, Interpreter = EtableInterpreter<'etable, ES>>,
+	ES: EtableSet<Handle = H> + 'etable,
+	ES::State: AsRef<RuntimeState> + GasMutState,
+	H: RuntimeBackend,
+{
+	type State = ES::State;
+	type Interpreter = EtableInterpreter<'etable, ES>;
+
+	fn resolve_call(
+		&self,
+		code_address: H160,
+		input: Arc<Vec<u8>>,
+		state: Self::State,
+		handler: &mut H,
+	) -> Result<InvokerControl<Self::Interpreter, (ExitResult, (Self::State, Vec<u8>))>, ExitError>
+	{
+		let context = state.as_ref().context.clone();
+		let mut interpreter =
+			match self
+				.resolver
+				.resolve_call(code_address, input.clone(), state, handler)?
+			{
+				InvokerControl::Enter(interpreter) => interpreter,
+				control => return Ok(control),
+			};
+
+		let code_hash = handler.code_hash(code_address);
+		if !self.cache.is_pure(code_hash, interpreter.machine().code()) {
+			return Ok(InvokerControl::Enter(interpreter));
+		}
+
+		let key = PureCallKey {
+			code_hash,
+			input: input.as_ref().clone(),
+			address: context.address,
+			caller: context.caller,
+			apparent_value: context.apparent_value,
+		};
+
+		let cached = self.cache.calls.borrow().get(&key).cloned();
+		if let Some(entry) = cached {
+			if interpreter.machine().state.gas() < entry.used_gas {
+				return Ok(InvokerControl::Enter(interpreter));
+			}
+
+			let (mut state, _) = Interpreter::deconstruct(interpreter);
+			state.record_gas(entry.used_gas)?;
+			return Ok(InvokerControl::DirectExit((
+				entry.result,
+				(state, entry.retval),
+			)));
+		}
+
+		let gas = interpreter.machine().state.gas();
+		let result = match interpreter.run(handler) {
+			Capture::Exit(result) => result,
+			Capture::Trap(_) => return Err(ExitFatal::UnhandledInterrupt.into()),
+		};
+		let (state, retval) = Interpreter::deconstruct(interpreter);
+
+		if matches!(result, Ok(_) | Err(ExitError::Reverted)) {
+			self.cache.calls.borrow_mut().insert(
+				key,
+				PureCallEntry {
+					result: result.clone(),
+					retval: retval.clone(),
+					used_gas: gas.saturating_sub(state.gas()),
+				},
+			);
+		}
+
+		Ok(InvokerControl::DirectExit((result, (state, retval))))
+	}
+
+	fn resolve_create(
+		&self,
+		init_code: Vec<u8>,
+		input: Arc<Vec<u8>>,
+		state: Self::State,
+		handler: &mut H,
+	) -> Result<InvokerControl<Self::Interpreter, (ExitResult, (Self::State, Vec<u8>))>, ExitError>
+	{
+		self.resolver
+			.resolve_create(init_code, input, state, handler)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn pure_code_detection() {
+		// PUSH1 0 CALLDATALOAD PUSH1 1 ADD PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+		assert!(is_pure_code(
+			&hex::decode("60003560010160005260206000f3").unwrap()
+		));
+		// PUSH1 0 SLOAD
+		assert!(!is_pure_code(&hex::decode("600054").unwrap()));
+		// PUSH2 0x5454 STOP, where the push data is not an SLOAD.
+		assert!(is_pure_code(&hex::decode("61545400").unwrap()));
+		// GAS
+		assert!(!is_pure_code(&hex::decode("5a").unwrap()));
+	}
+}
@@ -1,14 +1,19 @@
+mod fee;
+mod guard;
+mod memo;
+mod registry;
 mod resolver;
 pub mod routines;
 mod state;
 
-use alloc::{rc::Rc, vec::Vec};
+use alloc::{collections::BTreeSet, sync::Arc, vec::Vec};
 use core::{cmp::min, convert::Infallible};
 
 use evm_interpreter::{
+	eof,
 	error::{
-		CallCreateTrap, CallCreateTrapData, CallTrapData, Capture, CreateScheme, CreateTrapData,
-		ExitError, ExitException, ExitResult, ExitSucceed, TrapConsume,
+		CallCreateTrap, CallCreateTrapData, CallScheme, CallTrapData, Capture, CreateScheme,
+		CreateTrapData, ExitError, ExitException, ExitResult, ExitSucceed, TrapConsume,
 	},
 	opcode::Opcode,
 	runtime::{
@@ -21,14 +26,19 @@ use primitive_types::{H160, H256, U256};
 use sha3::{Digest, Keccak256};
 
 pub use self::{
+	fee::EffectiveGasPrice,
+	guard::{GuardedPrecompileSet, PrecompileGuard, PrecompileGuards},
+	memo::{MemoizingResolver, PureCallCache},
+	registry::{DynamicPrecompileSet, Precompile},
 	resolver::{EtableResolver, PrecompileSet, Resolver},
 	state::InvokerState,
 };
 use crate::{
 	backend::TransactionalBackend,
 	invoker::{Invoker as InvokerT, InvokerControl},
-	standard::Config,
-	MergeStrategy,
+	revert::RevertReason,
+	standard::{apply_authorizations, delegation_target, Authorization, ChainRules, Config},
+	GasMutState, MergeStrategy,
 };
 
 /// A trap that can be turned into either a call/create trap (where we push new
@@ -55,8 +65,52 @@ pub enum SubstackInvoke {
 	Create { trap: CreateTrapData, address: H160 },
 }
 
-/// Return value of a transaction.
-pub enum TransactValue {
+/// Gas usage of a call frame.
+#[derive(Clone, Debug)]
+pub struct FrameGas {
+	/// Depth of the frame. Direct subcalls of the transaction have depth 1.
+	pub depth: usize,
+	/// Execution address of the frame.
+	pub address: H160,
+	/// Gas provided to the frame.
+	pub gas_limit: u64,
+	/// Gas used by the frame, including its subcalls.
+	pub used_gas: u64,
+	/// How the frame was merged into its parent.
+	pub strategy: MergeStrategy,
+}
+
+/// Scheme of an [InternalCall].
+#[derive(Clone, Debug)]
+pub enum InternalCallScheme {
+	/// A call, of any scheme.
+	Call(CallScheme),
+	/// A `CREATE` or `CREATE2`.
+	Create(CreateScheme),
+}
+
+/// Internal call of a transaction, recorded if enabled with
+/// [Invoker::with_call_tree].
+#[derive(Clone, Debug)]
+pub struct InternalCall {
+	/// Depth of the call. Direct subcalls of the transaction have depth 1.
+	pub depth: usize,
+	/// Scheme of the call.
+	pub scheme: InternalCallScheme,
+	/// Address called, or the address created.
+	pub callee: H160,
+	/// Value transferred.
+	pub value: U256,
+	/// Gas provided to the call.
+	pub gas_limit: u64,
+	/// Gas used by the call, including its subcalls.
+	pub used_gas: u64,
+	/// Outcome of the call.
+	pub result: ExitResult,
+}
+
+/// Outcome of a transaction.
+pub enum TransactOutcome {
 	Call {
 		/// The exit result. If we return a value, then it will be an
 		/// `ExitSucceed`.
@@ -70,19 +124,112 @@ pub enum TransactValue {
 		succeed: ExitSucceed,
 		/// The contract address created.
 		address: H160,
+		/// The code deployed at the address.
+		code: Vec<u8>,
+		/// The scheme the address was derived with, including the salt and
+		/// init code hash of `CREATE2`.
+		scheme: CreateScheme,
+	},
+	/// The transaction reverted. State changes are discarded, but the left gas
+	/// is refunded and fees are paid as for any other outcome.
+	///
+	/// A revert is a valid transaction, so it is returned as `Ok`, with its
+	/// used gas, rather than as `Err(ExitError::Reverted)`. Callers treating
+	/// reverts as failures can use [TransactValue::err_on_revert].
+	Revert {
+		/// The revert payload.
+		retval: Vec<u8>,
+		/// Decoded revert reason. Only set if enabled with
+		/// [Invoker::with_revert_reason].
+		reason: Option<RevertReason>,
 	},
 }
 
+impl TransactOutcome {
+	/// The contract address created, if the transaction is a successful
+	/// create.
+	pub fn created_address(&self) -> Option<H160> {
+		match self {
+			Self::Create { address, .. } => Some(*address),
+			_ => None,
+		}
+	}
+}
+
+/// Return value of a transaction, either successful or reverted. Invalid
+/// transactions and transactions failing with an exceptional halt return an
+/// error instead.
+pub struct TransactValue {
+	/// Outcome of the transaction.
+	pub outcome: TransactOutcome,
+	/// Gas used by the transaction, after refunds, including the sponsored
+	/// gas.
+	pub used_gas: U256,
+	/// Part of [TransactValue::used_gas] granted by a sponsor during the
+	/// execution, see
+	/// [GasometerState::sponsor](crate::standard::GasometerState::sponsor).
+	/// The caller is only charged fees for the rest.
+	pub sponsored_gas: U256,
+	/// Extra fee charged to the caller, see [ChainRules::extra_fee].
+	pub extra_fee: U256,
+	/// Gas usage of call frames, in call order. See
+	/// [Invoker::with_frame_gas_depth].
+	pub frames: Vec<FrameGas>,
+	/// Internal calls, in call order, each followed by its subcalls. See
+	/// [Invoker::with_call_tree].
+	pub calls: Vec<InternalCall>,
+	/// Contracts created by the transaction, in creation order, including
+	/// the one created by a create transaction.
+	pub created: Vec<H160>,
+	/// Accounts touched by the transaction, in the EIP-161 sense. If the
+	/// transaction reverted, only the caller and the coinbase are touched.
+	/// `SELFDESTRUCT` beneficiaries are not tracked.
+	pub touched: BTreeSet<H160>,
+}
+
+impl TransactValue {
+	/// The value, or `Err(ExitError::Reverted)` if the transaction reverted.
+	pub fn err_on_revert(self) -> Result<Self, ExitError> {
+		match self.outcome {
+			TransactOutcome::Revert { .. } => Err(ExitError::Reverted),
+			_ => Ok(self),
+		}
+	}
+}
+
+/// Kind of a transaction.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TransactKind {
+	/// A normal transaction. The caller pays the fees and its nonce is
+	/// increased.
+	Normal,
+	/// A protocol-originated transaction, such as a system call or a deposit.
+	/// Gas is still metered, but no fee is deducted or rewarded, and the
+	/// caller nonce is not increased.
+	System,
+}
+
+impl Default for TransactKind {
+	fn default() -> Self {
+		Self::Normal
+	}
+}
+
 /// The invoke used in a top-layer transaction stack.
 pub struct TransactInvoke {
 	pub create_address: Option<H160>,
+	pub create_scheme: Option<CreateScheme>,
 	pub gas_limit: U256,
 	pub gas_price: U256,
 	pub caller: H160,
+	pub kind: TransactKind,
+	pub extra_fee: U256,
 }
 
 /// Transaction arguments.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TransactArgs {
 	/// A call transaction.
 	Call {
@@ -100,6 +247,17 @@ pub enum TransactArgs {
 		gas_price: U256,
 		/// Access list information, in the format of (address, storage keys).
 		access_list: Vec<(H160, Vec<H256>)>,
+		/// Authorizations of a set-code transaction, applied before execution
+		/// when [Config::eip_7702_enabled] is set. Set-code transactions cannot
+		/// create contracts, so create transactions have none.
+		authorization_list: Vec<Authorization>,
+		/// Versioned hashes of the blobs of a blob transaction, whose number is
+		/// checked against [Config::blob_schedule]. Blob transactions cannot
+		/// create contracts either. `BLOBHASH` reads the hashes from the
+		/// environment, see [RuntimeEnvironment::blob_hash].
+		blob_hashes: Vec<H256>,
+		/// Transaction kind.
+		kind: TransactKind,
 	},
 	/// A create transaction.
 	Create {
@@ -117,6 +275,8 @@ pub enum TransactArgs {
 		gas_price: U256,
 		/// Access list information, in the format of (address, storage keys).
 		access_list: Vec<(H160, Vec<H256>)>,
+		/// Transaction kind.
+		kind: TransactKind,
 	},
 }
 
@@ -145,6 +305,15 @@ impl TransactArgs {
 		}
 	}
 
+	/// Versioned hashes of the blobs of a blob transaction, empty for other
+	/// transactions.
+	pub fn blob_hashes(&self) -> &[H256] {
+		match self {
+			Self::Call { blob_hashes, .. } => blob_hashes,
+			Self::Create { .. } => &[],
+		}
+	}
+
 	/// Transaction sender.
 	pub fn caller(&self) -> H160 {
 		match self {
@@ -160,6 +329,14 @@ impl TransactArgs {
 			Self::Create { value, .. } => *value,
 		}
 	}
+
+	/// Transaction kind.
+	pub fn kind(&self) -> TransactKind {
+		match self {
+			Self::Call { kind, .. } => *kind,
+			Self::Create { kind, .. } => *kind,
+		}
+	}
 }
 
 /// Standard invoker.
@@ -170,23 +347,84 @@ impl TransactArgs {
 /// * `R`: Code resolver type, also handle precompiles. Usually
 ///   [EtableResolver] but can be customized.
 /// * `Tr`: Trap type, usually [crate::Opcode] but can be customized.
-pub struct Invoker<'config, 'resolver, R> {
+pub struct Invoker<'config, 'resolver, R, C = ()> {
 	config: &'config Config,
 	resolver: &'resolver R,
+	rules: C,
+	increase_nonce: bool,
+	frame_gas_depth: usize,
+	record_call_tree: bool,
+	decode_revert_reason: bool,
 }
 
 impl<'config, 'resolver, R> Invoker<'config, 'resolver, R> {
 	/// Create a new standard invoker with the given config and resolver.
 	pub fn new(config: &'config Config, resolver: &'resolver R) -> Self {
-		Self { config, resolver }
+		Self {
+			config,
+			resolver,
+			rules: (),
+			increase_nonce: true,
+			frame_gas_depth: 0,
+			record_call_tree: false,
+			decode_revert_reason: false,
+		}
+	}
+}
+
+impl<'config, 'resolver, R, C> Invoker<'config, 'resolver, R, C> {
+	/// Set the chain rules, consulted for fee routing, extra fees and the
+	/// refund policy.
+	pub fn with_chain_rules<C2>(self, rules: C2) -> Invoker<'config, 'resolver, R, C2> {
+		Invoker {
+			config: self.config,
+			resolver: self.resolver,
+			rules,
+			increase_nonce: self.increase_nonce,
+			frame_gas_depth: self.frame_gas_depth,
+			record_call_tree: self.record_call_tree,
+			decode_revert_reason: self.decode_revert_reason,
+		}
+	}
+
+	/// Set whether the nonce of the transaction caller is increased, which is
+	/// the default. Disabling it is not consensus-compatible, but lets
+	/// simulations run repeated what-if transactions from the same account
+	/// state. Nonces of contracts creating other contracts are still
+	/// increased.
+	pub fn with_nonce_increase(mut self, increase_nonce: bool) -> Self {
+		self.increase_nonce = increase_nonce;
+		self
+	}
+
+	/// Set the number of call frame levels whose gas usage is recorded in the
+	/// transaction result. Zero, the default, disables recording.
+	pub fn with_frame_gas_depth(mut self, depth: usize) -> Self {
+		self.frame_gas_depth = depth;
+		self
+	}
+
+	/// Set whether the internal calls of a transaction are recorded, as a
+	/// flat call tree, in the transaction result. Disabled by default.
+	pub fn with_call_tree(mut self, record: bool) -> Self {
+		self.record_call_tree = record;
+		self
+	}
+
+	/// Set whether the revert reason of a reverted transaction is decoded into
+	/// the transaction result. Disabled by default.
+	pub fn with_revert_reason(mut self, decode: bool) -> Self {
+		self.decode_revert_reason = decode;
+		self
 	}
 }
 
-impl<'config, 'resolver, H, R, Tr> InvokerT<H, Tr> for Invoker<'config, 'resolver, R>
+impl<'config, 'resolver, H, R, C, Tr> InvokerT<H, Tr> for Invoker<'config, 'resolver, R, C>
 where
-	R::State: InvokerState<'config> + AsRef<RuntimeState> + AsMut<RuntimeState>,
+	R::State: InvokerState<'config> + GasMutState + AsRef<RuntimeState> + AsMut<RuntimeState>,
 	H: RuntimeEnvironment + RuntimeBackend + TransactionalBackend,
 	R: Resolver<H>,
+	C: ChainRules<H>,
 	Tr: TrapConsume<CallCreateTrap>,
 {
 	type State = R::State;
@@ -210,33 +448,52 @@ where
 	> {
 		let caller = args.caller();
 		let gas_price = args.gas_price();
+		let kind = args.kind();
+
+		let blob_count = args.blob_hashes().len();
+		if blob_count > 0 {
+			self.config
+				.blob_schedule
+				.ok_or(ExitException::InvalidBlobCount)?
+				.validate_blob_count(blob_count)?;
+		}
+
+		let extra_fee = match kind {
+			TransactKind::Normal => self.rules.extra_fee(&args, handler),
+			TransactKind::System => U256::zero(),
+		};
 
-		let gas_fee = args.gas_limit().saturating_mul(gas_price);
-		handler.withdrawal(caller, gas_fee)?;
+		if kind == TransactKind::Normal {
+			let gas_fee = args.gas_limit().saturating_mul(gas_price);
+			self.rules
+				.withdraw_fee(caller, gas_fee.saturating_add(extra_fee), handler)?;
+			if let Some(recipient) = self.rules.extra_fee_recipient(handler) {
+				self.rules.deposit_fee(recipient, extra_fee, handler);
+			}
 
-		handler.inc_nonce(caller)?;
+			if self.increase_nonce {
+				handler.inc_nonce(caller)?;
+			}
+		}
 
-		let address = match &args {
-			TransactArgs::Call { address, .. } => *address,
+		let (address, create_scheme) = match &args {
+			TransactArgs::Call { address, .. } => (*address, None),
 			TransactArgs::Create {
 				caller,
 				salt,
 				init_code,
 				..
-			} => match salt {
-				Some(salt) => {
-					let scheme = CreateScheme::Create2 {
+			} => {
+				let scheme = match salt {
+					Some(salt) => CreateScheme::Create2 {
 						caller: *caller,
 						code_hash: H256::from_slice(Keccak256::digest(init_code).as_slice()),
 						salt: *salt,
-					};
-					scheme.address(handler)
-				}
-				None => {
-					let scheme = CreateScheme::Legacy { caller: *caller };
-					scheme.address(handler)
-				}
-			},
+					},
+					None => CreateScheme::Legacy { caller: *caller },
+				};
+				(scheme.address(handler), Some(scheme))
+			}
 		};
 		let value = args.value();
 
@@ -244,10 +501,10 @@ where
 			gas_limit: args.gas_limit(),
 			gas_price: args.gas_price(),
 			caller: args.caller(),
-			create_address: match &args {
-				TransactArgs::Call { .. } => None,
-				TransactArgs::Create { .. } => Some(address),
-			},
+			kind,
+			extra_fee,
+			create_address: create_scheme.map(|_| address),
+			create_scheme,
 		};
 
 		handler.push_substate();
@@ -256,6 +513,7 @@ where
 			caller,
 			address,
 			apparent_value: value,
+			authorized: None,
 		};
 		let transaction_context = TransactionContext {
 			origin: caller,
@@ -268,54 +526,75 @@ where
 		};
 		let runtime_state = RuntimeState {
 			context,
-			transaction_context: Rc::new(transaction_context),
+			transaction_context: Arc::new(transaction_context),
 			retbuf: Vec::new(),
 		};
 
+		// Addresses and storage keys accessed from the start of the
+		// transaction, for both calls and creates.
+		let accesses = args
+			.access_list()
+			.iter()
+			.flat_map(|(address, keys)| {
+				core::iter::once((*address, None))
+					.chain(keys.iter().map(move |key| (*address, Some(*key))))
+			})
+			.collect::<Vec<_>>();
+		handler.mark_hot_batch(&accesses);
+		if self.config.increase_state_access_gas {
+			if self.config.warm_coinbase_address {
+				let coinbase = handler.block_coinbase();
+				handler.mark_hot(coinbase, None);
+			}
+			handler.mark_hot(caller, None);
+			handler.mark_hot(address, None);
+		}
+
 		let work = || -> Result<(TransactInvoke, _), ExitError> {
 			match args {
 				TransactArgs::Call {
-					caller,
 					address,
 					data,
 					gas_limit,
 					access_list,
+					authorization_list,
 					..
 				} => {
-					for (address, keys) in &access_list {
-						handler.mark_hot(*address, None);
-						for key in keys {
-							handler.mark_hot(*address, Some(*key));
-						}
-					}
-
-					let state = <R::State>::new_transact_call(
+					let mut state = <R::State>::new_transact_call(
 						runtime_state,
 						gas_limit,
 						&data,
 						&access_list,
+						authorization_list.len(),
 						self.config,
 					)?;
+					state.set_recording(self.frame_gas_depth, self.record_call_tree);
+
+					if self.config.eip_7702_enabled && !authorization_list.is_empty() {
+						state.record_refund(apply_authorizations(&authorization_list, handler)?);
+						// Delegations are kept even if the transaction reverts (see
+						// EIP-7702), so they are committed before execution starts in
+						// a fresh substate.
+						handler.pop_substate(MergeStrategy::Commit);
+						handler.push_substate();
+					}
+
+					if self.config.eip_7702_enabled {
+						if let Some(delegate) = delegation_target(&handler.code(address)) {
+							handler.mark_hot(delegate, None);
+						}
+					}
 
 					let machine = routines::make_enter_call_machine(
 						self.config,
 						self.resolver,
 						address,
-						data,
+						Arc::new(data),
 						Some(transfer),
 						state,
 						handler,
 					)?;
 
-					if self.config.increase_state_access_gas {
-						if self.config.warm_coinbase_address {
-							let coinbase = handler.block_coinbase();
-							handler.mark_hot(coinbase, None);
-						}
-						handler.mark_hot(caller, None);
-						handler.mark_hot(address, None);
-					}
-
 					Ok((invoke, machine))
 				}
 				TransactArgs::Create {
@@ -325,20 +604,24 @@ where
 					access_list,
 					..
 				} => {
-					let state = <R::State>::new_transact_create(
+					let mut state = <R::State>::new_transact_create(
 						runtime_state,
 						gas_limit,
 						&init_code,
 						&access_list,
 						self.config,
 					)?;
+					state.set_recording(self.frame_gas_depth, self.record_call_tree);
 
 					let machine = routines::make_enter_create_machine(
 						self.config,
 						self.resolver,
 						caller,
 						init_code,
+						Arc::new(Vec::new()),
+						false,
 						transfer,
+						self.increase_nonce,
 						state,
 						handler,
 					)?;
@@ -362,70 +645,121 @@ where
 		handler: &mut H,
 	) -> Result<Self::TransactValue, ExitError> {
 		let left_gas = substate.effective_gas();
+		let sponsored_gas = substate.sponsored_gas();
 
-		let work = || -> Result<Self::TransactValue, ExitError> {
+		let work = || -> Result<TransactOutcome, ExitError> {
 			match result {
 				Ok(result) => {
-					if let Some(address) = invoke.create_address {
-						let retbuf = retval;
+					if let (Some(address), Some(scheme)) =
+						(invoke.create_address, invoke.create_scheme)
+					{
+						let code = retval;
+						substate.record_created(address);
 
 						routines::deploy_create_code(
 							self.config,
 							address,
-							retbuf,
+							code.clone(),
 							&mut substate,
 							handler,
 							SetCodeOrigin::Transaction,
 						)?;
 
-						Ok(TransactValue::Create {
+						Ok(TransactOutcome::Create {
 							succeed: result,
 							address,
+							code,
+							scheme,
 						})
 					} else {
-						Ok(TransactValue::Call {
+						Ok(TransactOutcome::Call {
 							succeed: result,
 							retval,
 						})
 					}
 				}
+				Err(ExitError::Reverted) => {
+					let reason = if self.decode_revert_reason {
+						Some(RevertReason::decode(&retval))
+					} else {
+						None
+					};
+
+					Ok(TransactOutcome::Revert { retval, reason })
+				}
 				Err(result) => Err(result),
 			}
 		};
 
 		let result = work();
 
-		let refunded_gas = match result {
-			Ok(_) | Err(ExitError::Reverted) => left_gas,
-			Err(_) => U256::zero(),
+		let (refunded_gas, sponsored_gas) = match result {
+			Ok(_) => (self.rules.refunded_gas(invoke, left_gas), sponsored_gas),
+			Err(_) => (U256::zero(), U256::zero()),
 		};
+		// Sponsored gas is used first. The caller is only charged for the gas
+		// used beyond it, and is never refunded more than its gas limit.
+		let total_used_gas = invoke
+			.gas_limit
+			.saturating_add(sponsored_gas)
+			.saturating_sub(refunded_gas);
+		let refunded_gas = min(refunded_gas, invoke.gas_limit);
+		let used_gas = invoke.gas_limit - refunded_gas;
+		let sponsored_gas = total_used_gas - used_gas;
 
 		match &result {
-			Ok(_) => {
+			Ok(TransactOutcome::Call { .. }) | Ok(TransactOutcome::Create { .. }) => {
 				handler.pop_substate(MergeStrategy::Commit);
 			}
-			Err(_) => {
+			Ok(TransactOutcome::Revert { .. }) | Err(_) => {
 				handler.pop_substate(MergeStrategy::Discard);
 			}
 		}
 
+		let mut result = result.map(|outcome| {
+			let (created, touched) = match outcome {
+				TransactOutcome::Revert { .. } => (Vec::new(), [invoke.caller].into()),
+				_ => (substate.take_created(), substate.take_touched()),
+			};
+
+			TransactValue {
+				outcome,
+				used_gas: total_used_gas,
+				sponsored_gas,
+				extra_fee: invoke.extra_fee,
+				frames: substate.take_frames(),
+				calls: substate.take_calls(),
+				created,
+				touched,
+			}
+		});
+
+		if invoke.kind == TransactKind::System {
+			return result;
+		}
+
 		let refunded_fee = refunded_gas.saturating_mul(invoke.gas_price);
-		handler.deposit(invoke.caller, refunded_fee);
+		self.rules.refund_fee(invoke.caller, refunded_fee, handler);
 		// Reward coinbase address
-		// EIP-1559 updated the fee system so that miners only get to keep the priority fee.
-		// The base fee is always burned.
-		let coinbase_gas_price = if substate.config().eip_1559_enabled {
-			invoke
-				.gas_price
-				.saturating_sub(handler.block_base_fee_per_gas())
+		// EIP-1559 updated the fee system so that miners only get to keep the priority fee
+		// of the gas used. The base fee is burned, unless the chain rules route it elsewhere.
+		let base_fee_per_gas = if substate.config().eip_1559_enabled {
+			handler.block_base_fee_per_gas()
 		} else {
-			invoke.gas_price
+			U256::zero()
 		};
-		let coinbase_reward = invoke
-			.gas_limit
-			.saturating_mul(coinbase_gas_price)
-			.saturating_sub(refunded_fee);
-		handler.deposit(handler.block_coinbase(), coinbase_reward);
+		let price = EffectiveGasPrice::new(invoke.gas_price, None, base_fee_per_gas);
+		let coinbase_gas_price = price.priority_fee;
+		let base_fee = used_gas.saturating_mul(price.base_fee());
+		let coinbase_reward = used_gas.saturating_mul(coinbase_gas_price);
+		let coinbase = self.rules.priority_fee_recipient(handler.block_coinbase());
+		self.rules.deposit_fee(coinbase, coinbase_reward, handler);
+		if let Ok(value) = &mut result {
+			value.touched.insert(coinbase);
+		}
+		if let Some(recipient) = self.rules.base_fee_recipient() {
+			self.rules.deposit_fee(recipient, base_fee, handler);
+		}
 
 		result
 	}
@@ -446,10 +780,6 @@ where
 		>,
 		Self::Interrupt,
 	> {
-		fn l64(gas: U256) -> U256 {
-			gas - gas / U256::from(64)
-		}
-
 		let opcode = match trap.consume() {
 			Ok(opcode) => opcode,
 			Err(interrupt) => return Capture::Trap(interrupt),
@@ -465,15 +795,20 @@ where
 		};
 
 		let after_gas = if self.config.call_l64_after_gas {
-			l64(machine.machine().state.gas())
+			let gas = machine.machine().state.gas();
+			let divisor = U256::from(self.config.call_gas_retention_divisor);
+			gas - gas.checked_div(divisor).unwrap_or_default()
 		} else {
 			machine.machine().state.gas()
 		};
 		let target_gas = trap_data.target_gas().unwrap_or(after_gas);
 		let gas_limit = min(after_gas, target_gas);
 
-		let call_has_value =
-			matches!(&trap_data, CallCreateTrapData::Call(call) if call.has_value());
+		// `AUTHCALL` does not add the call stipend (see EIP-3074).
+		let call_has_value = matches!(
+			&trap_data,
+			CallCreateTrapData::Call(call) if call.has_value() && call.scheme != CallScheme::AuthCall
+		);
 
 		let is_static = if machine.machine().state.is_static() {
 			true
@@ -524,6 +859,7 @@ where
 							address,
 							caller,
 							apparent_value: create_trap_data.value,
+							authorized: None,
 						},
 						transaction_context,
 						retbuf: Vec::new(),
@@ -567,7 +903,15 @@ where
 				let retbuf = retval;
 				let caller = trap.scheme.caller();
 
-				let result = result.and_then(|_| {
+				let result = result.and_then(|succeed| {
+					// `EOFCREATE` can only deploy a container, as returned by
+					// `RETURNCONTRACT`.
+					if matches!(trap.scheme, CreateScheme::EofCreate { .. })
+						&& !eof::is_eof(&retbuf)
+					{
+						return Err(ExitException::InvalidContainer.into());
+					}
+
 					routines::deploy_create_code(
 						self.config,
 						address,
@@ -576,19 +920,36 @@ where
 						handler,
 						SetCodeOrigin::Subcall(caller),
 					)?;
+					substate.record_created(address);
 
-					Ok(address)
+					Ok(succeed)
 				});
+				substate.record_call(
+					InternalCallScheme::Create(trap.scheme),
+					address,
+					trap.value,
+					&result,
+				);
 
 				parent.machine_mut().state.merge(substate, strategy);
 				handler.pop_substate(strategy);
 
-				trap.feedback(result, retbuf, parent)?;
+				trap.feedback(result.map(|_| address), retbuf, parent)?;
 
 				Ok(())
 			}
 			SubstackInvoke::Call { trap } => {
 				let retbuf = retval;
+				let value = trap
+					.transfer
+					.as_ref()
+					.map_or(U256::zero(), |transfer| transfer.value);
+				substate.record_call(
+					InternalCallScheme::Call(trap.scheme),
+					trap.target,
+					value,
+					&result,
+				);
 
 				parent.machine_mut().state.merge(substate, strategy);
 				handler.pop_substate(strategy);
@@ -600,3 +961,434 @@ where
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use evm_interpreter::runtime::{BlockEnv, RuntimeBaseBackend, RuntimeBlockHashes};
+
+	use super::*;
+	use crate::{
+		backend::{OverlayedBackend, WitnessAccount, WitnessBackend},
+		standard::{testing, Eip, Fork, TransactArgs},
+	};
+
+	const AUTHORITY: H160 = H160::repeat_byte(0x03);
+
+	/// Block environment recovering [AUTHORITY] from signatures with a zero
+	/// `r`, and no signer otherwise.
+	struct SignerEnv(BlockEnv);
+
+	impl RuntimeBlockHashes for SignerEnv {
+		fn block_hash(&self, number: U256) -> H256 {
+			self.0.block_hash(number)
+		}
+	}
+
+	impl RuntimeEnvironment for SignerEnv {
+		fn block_env(&self) -> BlockEnv {
+			self.0.clone()
+		}
+		fn recover_signer(
+			&self,
+			_message: H256,
+			_y_parity: bool,
+			r: H256,
+			_s: H256,
+		) -> Option<H160> {
+			if r == H256::zero() {
+				Some(AUTHORITY)
+			} else {
+				None
+			}
+		}
+	}
+
+	fn auth_and_call(authority: H160) -> Result<(H256, H256, H256), ExitError> {
+		// PUSH1 97 PUSH1 0 PUSH20 authority AUTH PUSH1 0 SSTORE
+		// PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0x04 GAS AUTHCALL
+		// PUSH1 1 SSTORE
+		let invoker_code = hex::decode(format!(
+			"6061600073{}f66000556000600060006000600060045af7600155",
+			hex::encode(authority)
+		))
+		.unwrap();
+		let mut backend = testing::backend_with_env(
+			SignerEnv(BlockEnv::default()),
+			[
+				(H160::repeat_byte(0x01), testing::account(&[])),
+				(
+					H160::repeat_byte(0x02),
+					WitnessAccount {
+						storage: testing::zero_slots(&[0, 1]),
+						..testing::account(&invoker_code)
+					},
+				),
+				(authority, testing::account(&[])),
+				// CALLER PUSH1 0 SSTORE
+				(
+					H160::from_low_u64_be(0x04),
+					WitnessAccount {
+						storage: testing::zero_slots(&[0]),
+						..testing::account(&hex::decode("33600055").unwrap())
+					},
+				),
+			],
+		);
+
+		let config = Config::builder().enable(Eip::Eip3074).build().unwrap();
+		let args = testing::call(H160::repeat_byte(0x01), H160::repeat_byte(0x02));
+		testing::transact_with(&config, args, &mut backend)?;
+
+		let storage = |address, slot| backend.storage(address, H256::from_low_u64_be(slot));
+		Ok((
+			storage(H160::repeat_byte(0x02), 0),
+			storage(H160::repeat_byte(0x02), 1),
+			storage(H160::from_low_u64_be(0x04), 0),
+		))
+	}
+
+	#[test]
+	fn authcall_uses_authorized_caller() {
+		assert_eq!(
+			auth_and_call(AUTHORITY),
+			Ok((
+				H256::from_low_u64_be(1),
+				H256::from_low_u64_be(1),
+				AUTHORITY.into()
+			))
+		);
+		assert_eq!(
+			auth_and_call(H160::repeat_byte(0x05)),
+			Err(ExitException::Unauthorized.into())
+		);
+	}
+
+	#[cfg(feature = "taint")]
+	#[test]
+	fn taint_flows_through_auth() {
+		use evm_interpreter::{
+			machine::Machine,
+			taint::{TaintEtable, TaintHook},
+		};
+
+		/// Marks `PUSH20` values, and the values computed from them, as
+		/// tainted.
+		struct AddressTaint;
+
+		impl<S> TaintHook<S> for AddressTaint {
+			type Tag = bool;
+
+			fn propagate(
+				&mut self,
+				_machine: &Machine<S>,
+				opcode: Opcode,
+				inputs: &[bool],
+			) -> bool {
+				opcode == Opcode::PUSH20 || inputs.iter().any(|tag| *tag)
+			}
+		}
+
+		// PUSH1 97 PUSH1 0 PUSH20 authority AUTH
+		// PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0x04 GAS AUTHCALL STOP
+		let code = hex::decode(format!(
+			"6061600073{}f66000600060006000600060045af700",
+			hex::encode(AUTHORITY)
+		))
+		.unwrap();
+		let mut backend = testing::backend_with_env(
+			SignerEnv(BlockEnv::default()),
+			[
+				(H160::repeat_byte(0x01), testing::account(&[])),
+				(H160::repeat_byte(0x02), testing::account(&code)),
+				(AUTHORITY, testing::account(&[])),
+				(H160::from_low_u64_be(0x04), testing::account(&[])),
+			],
+		);
+
+		let config = Config::builder().enable(Eip::Eip3074).build().unwrap();
+		let etable = TaintEtable::new(testing::etable(), AddressTaint);
+		let resolver = EtableResolver::new(&config, &(), &etable);
+		let invoker = Invoker::new(&config, &resolver);
+		let args = testing::call(H160::repeat_byte(0x01), H160::repeat_byte(0x02));
+		crate::transact(args, None, &mut backend, &invoker).unwrap();
+
+		// The result of `AUTH` comes from the authority, and the result of
+		// `AUTHCALL` is pushed after the call with the default tag.
+		assert_eq!(etable.tags(), vec![true, false]);
+	}
+
+	const DELEGATE: H160 = H160::repeat_byte(0x04);
+
+	fn set_code(
+		gas_limit: u64,
+	) -> (
+		Result<TransactValue, ExitError>,
+		OverlayedBackend<WitnessBackend<SignerEnv>>,
+	) {
+		let mut backend = testing::backend_with_env(
+			SignerEnv(BlockEnv::default()),
+			[
+				(H160::repeat_byte(0x01), testing::account(&[])),
+				(
+					AUTHORITY,
+					WitnessAccount {
+						storage: testing::zero_slots(&[0]),
+						..testing::account(&[])
+					},
+				),
+				// CALLER PUSH1 0 SSTORE
+				(
+					DELEGATE,
+					testing::account(&hex::decode("33600055").unwrap()),
+				),
+			],
+		);
+
+		let authorization = |address, r| Authorization {
+			chain_id: U256::zero(),
+			address,
+			nonce: 0,
+			y_parity: false,
+			r,
+			s: H256::zero(),
+		};
+		let mut args = testing::call(H160::repeat_byte(0x01), AUTHORITY);
+		if let TransactArgs::Call {
+			authorization_list,
+			gas_limit: limit,
+			..
+		} = &mut args
+		{
+			*authorization_list = vec![
+				// Signer cannot be recovered.
+				authorization(H160::repeat_byte(0x05), H256::repeat_byte(0x01)),
+				authorization(DELEGATE, H256::zero()),
+				// Nonce was already used by the previous authorization.
+				authorization(H160::repeat_byte(0x05), H256::zero()),
+			];
+			*limit = U256::from(gas_limit);
+		}
+		let config = Config::builder().enable(Eip::Eip7702).build().unwrap();
+		let result = testing::transact_with(&config, args, &mut backend);
+
+		(result, backend)
+	}
+
+	#[test]
+	fn set_code_delegates_authority() {
+		let (result, backend) = set_code(testing::GAS_LIMIT);
+		assert!(result.is_ok());
+
+		assert_eq!(
+			backend.code(AUTHORITY),
+			crate::standard::delegation_designator(DELEGATE)
+		);
+		assert_eq!(backend.nonce(AUTHORITY), U256::one());
+		assert_eq!(
+			backend.storage(AUTHORITY, H256::zero()),
+			H160::repeat_byte(0x01).into()
+		);
+	}
+
+	#[test]
+	fn invalid_set_code_transaction_keeps_authority() {
+		// Below the intrinsic gas of 21000 and 25000 for each authorization.
+		let (result, backend) = set_code(90_000);
+		assert_eq!(result.err(), Some(ExitException::OutOfGas.into()));
+
+		assert_eq!(backend.code(AUTHORITY), Vec::<u8>::new());
+		assert_eq!(backend.nonce(AUTHORITY), U256::zero());
+	}
+
+	#[test]
+	fn blob_count_follows_schedule() {
+		let transact = |config: &Config, blobs: usize| {
+			let caller = H160::repeat_byte(0x01);
+			let mut backend = testing::backend([(caller, testing::account(&[]))]);
+			let mut args = testing::call(caller, caller);
+			if let TransactArgs::Call { blob_hashes, .. } = &mut args {
+				*blob_hashes = vec![H256::zero(); blobs];
+			}
+			testing::transact_with(config, args, &mut backend).map(|_| ())
+		};
+		let invalid = Err(ExitException::InvalidBlobCount.into());
+
+		assert_eq!(transact(&Config::shanghai(), 1), invalid);
+		assert_eq!(transact(&Config::cancun(), 6), Ok(()));
+		assert_eq!(transact(&Config::cancun(), 7), invalid);
+		assert_eq!(transact(&Config::prague(), 9), Ok(()));
+		assert_eq!(transact(&Config::prague(), 10), invalid);
+	}
+
+	#[test]
+	fn coinbase_earns_priority_fee_of_used_gas() {
+		let caller = H160::repeat_byte(0x01);
+		let contract = H160::repeat_byte(0x02);
+		let env = BlockEnv {
+			base_fee_per_gas: U256::from(7),
+			..Default::default()
+		};
+		let coinbase = env.coinbase;
+		let mut backend = testing::backend_with_env(
+			env,
+			[
+				(
+					caller,
+					WitnessAccount {
+						balance: Some(U256::from(100_000_000)),
+						..testing::account(&[])
+					},
+				),
+				// PUSH1 0 PUSH1 0 SSTORE, clearing the slot for a refund.
+				(
+					contract,
+					WitnessAccount {
+						storage: [(H256::zero(), H256::from_low_u64_be(1))].into(),
+						..testing::account(&hex::decode("6000600055").unwrap())
+					},
+				),
+				(coinbase, testing::account(&[])),
+			],
+		);
+
+		let mut args = testing::normal(testing::call(caller, contract));
+		if let TransactArgs::Call { gas_price, .. } = &mut args {
+			*gas_price = U256::from(10);
+		}
+		let value = testing::transact_with(&Config::london(), args, &mut backend).unwrap();
+
+		// 21000 of intrinsic gas and 5006 of execution, less the refund of
+		// 4800 for clearing the slot.
+		assert_eq!(value.used_gas, U256::from(21206));
+		assert_eq!(
+			backend.balance(caller),
+			U256::from(100_000_000 - 21206 * 10)
+		);
+		// The base fee of 7 is burned, and the priority fee of 3 is paid only
+		// for the gas used.
+		assert_eq!(backend.balance(coinbase), U256::from(21206 * 3));
+	}
+
+	#[test]
+	fn recording_is_set_on_the_invoker() {
+		// PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0x04 GAS CALL POP
+		// PUSH1 0 PUSH1 0 REVERT
+		let code = hex::decode("6000600060006000600060045af15060006000fd").unwrap();
+		let config = Config::cancun();
+		let etable = testing::etable();
+		let resolver = EtableResolver::new(&config, &(), &etable);
+		let transact = |recording: bool| {
+			let invoker = Invoker::new(&config, &resolver)
+				.with_frame_gas_depth(if recording { 1 } else { 0 })
+				.with_call_tree(recording)
+				.with_revert_reason(recording);
+			let mut backend = testing::backend([
+				(H160::repeat_byte(0x01), testing::account(&[])),
+				(H160::repeat_byte(0x02), testing::account(&code)),
+				(H160::from_low_u64_be(0x04), testing::account(&[])),
+			]);
+			let args = testing::call(H160::repeat_byte(0x01), H160::repeat_byte(0x02));
+			crate::transact(args, None, &mut backend, &invoker).unwrap()
+		};
+
+		let value = transact(false);
+		assert!(matches!(
+			value.outcome,
+			TransactOutcome::Revert { reason: None, .. }
+		));
+		assert!(value.frames.is_empty());
+		assert!(value.calls.is_empty());
+		assert_eq!(value.err_on_revert().err(), Some(ExitError::Reverted));
+
+		let value = transact(true);
+		assert!(matches!(
+			value.outcome,
+			TransactOutcome::Revert {
+				reason: Some(RevertReason::Empty),
+				..
+			}
+		));
+		assert_eq!(
+			value
+				.frames
+				.iter()
+				.map(|frame| (frame.depth, frame.address))
+				.collect::<Vec<_>>(),
+			vec![(1, H160::from_low_u64_be(0x04))]
+		);
+		assert_eq!(
+			value
+				.calls
+				.iter()
+				.map(|call| (call.depth, call.callee))
+				.collect::<Vec<_>>(),
+			vec![(1, H160::from_low_u64_be(0x04))]
+		);
+	}
+
+	#[test]
+	fn create_warms_coinbase_and_access_list() {
+		let caller = H160::repeat_byte(0x01);
+		let target = H160::repeat_byte(0x05);
+		// COINBASE BALANCE POP PUSH20 target BALANCE POP STOP
+		let init_code = hex::decode(format!("41315073{}315000", hex::encode(target))).unwrap();
+		let created = testing::create2_address(caller, H256::zero(), &init_code);
+
+		let used_gas = |config: &Config, access_list: Vec<(H160, Vec<H256>)>| {
+			let mut backend = testing::backend(
+				[caller, target, created, BlockEnv::default().coinbase]
+					.map(|address| (address, testing::account(&[]))),
+			);
+			let mut args = testing::create2(caller, init_code.clone(), H256::zero());
+			if let TransactArgs::Create {
+				access_list: list, ..
+			} = &mut args
+			{
+				*list = access_list;
+			}
+			testing::transact_with(config, args, &mut backend)
+				.unwrap()
+				.used_gas
+				.as_u64()
+		};
+
+		let warm = Config::shanghai();
+		let cold = Config::builder()
+			.base(Fork::Shanghai)
+			.disable(Eip::Eip3651)
+			.build()
+			.unwrap();
+		assert_eq!(
+			used_gas(&cold, Vec::new()) - used_gas(&warm, Vec::new()),
+			2500
+		);
+		// The access list costs 2400 and saves the 2500 of a cold access.
+		assert_eq!(
+			used_gas(&warm, Vec::new()) - used_gas(&warm, vec![(target, Vec::new())]),
+			100
+		);
+	}
+
+	#[test]
+	fn create_transaction_initcode_limit() {
+		let caller = H160::repeat_byte(0x01);
+		let create = |len: usize| {
+			let init_code = vec![0; len];
+			let created = testing::create2_address(caller, H256::zero(), &init_code);
+			let mut backend =
+				testing::backend([caller, created].map(|address| (address, testing::account(&[]))));
+			let args = testing::create2(caller, init_code, H256::zero());
+			testing::transact_with(&Config::shanghai(), args, &mut backend)
+				.map(|value| value.used_gas)
+		};
+
+		assert_eq!(
+			create(0xC000),
+			Ok(U256::from(53000 + 4 * 0xC000 + 2 * 1536))
+		);
+		assert_eq!(
+			create(0xC001),
+			Err(ExitException::CreateContractLimit.into())
+		);
+	}
+}
@@ -0,0 +1,123 @@
+use alloc::{boxed::Box, collections::BTreeMap, vec::Vec};
+
+use evm_interpreter::error::ExitResult;
+use primitive_types::H160;
+
+use crate::standard::PrecompileSet;
+
+/// A single precompile, to be registered at an address of a
+/// [DynamicPrecompileSet]. It is implemented for closures, which is handy for
+/// mocks.
+pub trait Precompile<S, H> {
+	/// Execute the precompile.
+	fn execute(&self, input: &[u8], state: &mut S, handler: &mut H) -> (ExitResult, Vec<u8>);
+}
+
+impl<S, H, F> Precompile<S, H> for F
+where
+	F: Fn(&[u8], &mut S, &mut H) -> (ExitResult, Vec<u8>),
+{
+	fn execute(&self, input: &[u8], state: &mut S, handler: &mut H) -> (ExitResult, Vec<u8>) {
+		self(input, state, handler)
+	}
+}
+
+/// Precompile set combining precompiles registered at runtime with a static
+/// set, so that test harnesses and plugins can inject precompiles without
+/// changing the type of the static set. Registered precompiles take
+/// precedence over the static ones at the same address. Registered
+/// precompiles must be `Send + Sync`, so that the set can be shared by
+/// executions running on several threads.
+pub struct DynamicPrecompileSet<'a, S, H, P = ()> {
+	precompiles: P,
+	registered: BTreeMap<H160, Box<dyn Precompile<S, H> + Send + Sync + 'a>>,
+}
+
+impl<'a, S, H, P> DynamicPrecompileSet<'a, S, H, P> {
+	/// Create a registry on top of a static precompile set.
+	pub fn new(precompiles: P) -> Self {
+		Self {
+			precompiles,
+			registered: BTreeMap::new(),
+		}
+	}
+
+	/// Register a precompile at the address, returning the one that was
+	/// previously registered there.
+	pub fn register(
+		&mut self,
+		address: H160,
+		precompile: Box<dyn Precompile<S, H> + Send + Sync + 'a>,
+	) -> Option<Box<dyn Precompile<S, H> + Send + Sync + 'a>> {
+		self.registered.insert(address, precompile)
+	}
+
+	/// Register a precompile at the address.
+	pub fn with(
+		mut self,
+		address: H160,
+		precompile: Box<dyn Precompile<S, H> + Send + Sync + 'a>,
+	) -> Self {
+		self.register(address, precompile);
+		self
+	}
+
+	/// Remove the precompile registered at the address.
+	pub fn unregister(
+		&mut self,
+		address: H160,
+	) -> Option<Box<dyn Precompile<S, H> + Send + Sync + 'a>> {
+		self.registered.remove(&address)
+	}
+
+	/// Whether a precompile is registered at the address. Precompiles of the
+	/// static set are not considered.
+	pub fn is_registered(&self, address: H160) -> bool {
+		self.registered.contains_key(&address)
+	}
+}
+
+impl<'a, S, H, P: PrecompileSet<S, H>> PrecompileSet<S, H> for DynamicPrecompileSet<'a, S, H, P> {
+	fn execute(
+		&self,
+		code_address: H160,
+		input: &[u8],
+		state: &mut S,
+		handler: &mut H,
+	) -> Option<(ExitResult, Vec<u8>)> {
+		if let Some(precompile) = self.registered.get(&code_address) {
+			return Some(precompile.execute(input, state, handler));
+		}
+
+		self.precompiles
+			.execute(code_address, input, state, handler)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use evm_interpreter::error::ExitSucceed;
+
+	use super::*;
+
+	#[test]
+	fn registered_precompiles_take_precedence() {
+		let address = H160::repeat_byte(0x01);
+		let mut set = DynamicPrecompileSet::<(), (), ()>::new(()).with(
+			address,
+			Box::new(|input: &[u8], _: &mut (), _: &mut ()| {
+				(Ok(ExitSucceed::Returned), input.to_vec())
+			}),
+		);
+
+		let (result, retval) = set.execute(address, &[1, 2], &mut (), &mut ()).unwrap();
+		assert_eq!(result, Ok(ExitSucceed::Returned));
+		assert_eq!(retval, vec![1, 2]);
+		assert!(set
+			.execute(H160::repeat_byte(0x02), &[], &mut (), &mut ())
+			.is_none());
+
+		assert!(set.unregister(address).is_some());
+		assert!(set.execute(address, &[], &mut (), &mut ()).is_none());
+	}
+}
@@ -1,7 +1,10 @@
-use alloc::{rc::Rc, vec::Vec};
+use alloc::{sync::Arc, vec::Vec};
 
+#[cfg(feature = "arena")]
+use evm_interpreter::machine::FrameArena;
 use evm_interpreter::{
-	error::{ExitError, ExitResult},
+	eof::{self, EofLayout},
+	error::{ExitError, ExitException, ExitResult},
 	etable::EtableSet,
 	machine::Machine,
 	runtime::{RuntimeBackend, RuntimeState},
@@ -9,7 +12,10 @@ use evm_interpreter::{
 };
 use primitive_types::H160;
 
-use crate::{invoker::InvokerControl, standard::Config};
+use crate::{
+	invoker::InvokerControl,
+	standard::{delegation_target, Config},
+};
 
 /// A code resolver.
 ///
@@ -26,16 +32,17 @@ pub trait Resolver<H> {
 	fn resolve_call(
 		&self,
 		code_address: H160,
-		input: Vec<u8>,
+		input: Arc<Vec<u8>>,
 		state: Self::State,
 		handler: &mut H,
 	) -> Result<InvokerControl<Self::Interpreter, (ExitResult, (Self::State, Vec<u8>))>, ExitError>;
 
-	/// Resolve a create (with the init code).
+	/// Resolve a create (with the init code and, for `EOFCREATE`, its input).
 	#[allow(clippy::type_complexity)]
 	fn resolve_create(
 		&self,
 		init_code: Vec<u8>,
+		input: Arc<Vec<u8>>,
 		state: Self::State,
 		handler: &mut H,
 	) -> Result<InvokerControl<Self::Interpreter, (ExitResult, (Self::State, Vec<u8>))>, ExitError>;
@@ -72,6 +79,8 @@ pub struct EtableResolver<'config, 'precompile, 'etable, Pre, ES> {
 	config: &'config Config,
 	etable: &'etable ES,
 	precompiles: &'precompile Pre,
+	#[cfg(feature = "arena")]
+	arena: Option<FrameArena>,
 }
 
 impl<'config, 'precompile, 'etable, Pre, ES>
@@ -86,8 +95,53 @@ impl<'config, 'precompile, 'etable, Pre, ES>
 			config,
 			precompiles,
 			etable,
+			#[cfg(feature = "arena")]
+			arena: None,
 		}
 	}
+
+	/// Recycle memory buffers of call frames through the arena. Other
+	/// per-frame buffers are not pooled, see [FrameArena].
+	#[cfg(feature = "arena")]
+	pub fn with_arena(mut self, arena: FrameArena) -> Self {
+		self.arena = Some(arena);
+		self
+	}
+
+	fn machine(&self, code: Vec<u8>, data: Arc<Vec<u8>>, state: ES::State) -> Machine<ES::State>
+	where
+		ES: EtableSet,
+	{
+		let code = Arc::new(code);
+		let (stack_limit, memory_limit) = (self.config.stack_limit, self.config.memory_limit);
+		#[cfg(feature = "arena")]
+		let machine = match &self.arena {
+			Some(arena) => Machine::new_in(code, data, stack_limit, memory_limit, state, arena),
+			None => Machine::new(code, data, stack_limit, memory_limit, state),
+		};
+		#[cfg(not(feature = "arena"))]
+		let machine = Machine::new(code, data, stack_limit, memory_limit, state);
+		if self.config.has_prevrandao {
+			machine.with_prevrandao()
+		} else {
+			machine
+		}
+	}
+
+	/// Layout of `code` if it is an EOF container to execute as such. The
+	/// container is validated first, as code in the backend may not have been
+	/// validated on deployment, and the instructions rely on valid immediates.
+	fn eof_layout(&self, code: &[u8]) -> Option<Result<EofLayout, ExitError>> {
+		if !(self.config.eof_deployment && eof::is_eof(code)) {
+			return None;
+		}
+
+		Some(
+			eof::validate(code)
+				.and_then(|_| eof::layout(code))
+				.map_err(|_| ExitException::InvalidContainer.into()),
+		)
+	}
 }
 
 impl<'config, 'precompile, 'etable, H, Pre, ES> Resolver<H>
@@ -106,7 +160,7 @@ where
 	fn resolve_call(
 		&self,
 		code_address: H160,
-		input: Vec<u8>,
+		input: Arc<Vec<u8>>,
 		mut state: ES::State,
 		handler: &mut H,
 	) -> Result<InvokerControl<Self::Interpreter, (ExitResult, (ES::State, Vec<u8>))>, ExitError> {
@@ -117,36 +171,53 @@ where
 			return Ok(InvokerControl::DirectExit((r, (state, retval))));
 		}
 
-		let code = handler.code(code_address);
+		let mut code = handler.code(code_address);
+		if self.config.eip_7702_enabled {
+			if let Some(delegate) = delegation_target(&code) {
+				// Delegating to a precompile runs empty code (see EIP-7702).
+				code = handler.code(delegate);
+			}
+		}
 
-		let machine = Machine::<ES::State>::new(
-			Rc::new(code),
-			Rc::new(input),
-			self.config.stack_limit,
-			self.config.memory_limit,
-			state,
-		);
+		let eof = match self.eof_layout(&code) {
+			Some(Ok(layout)) => Some(layout),
+			Some(Err(err)) => {
+				return Ok(InvokerControl::DirectExit((Err(err), (state, Vec::new()))))
+			}
+			None => None,
+		};
+
+		let mut machine = self.machine(code, input, state);
+		if let Some(layout) = eof {
+			machine = machine.with_eof(layout);
+		}
 
 		let ret = InvokerControl::Enter(EtableInterpreter::new(machine, self.etable));
 
 		Ok(ret)
 	}
 
-	/// Resolve a create (with the init code).
+	/// Resolve a create (with the init code and, for `EOFCREATE`, its input).
 	#[allow(clippy::type_complexity)]
 	fn resolve_create(
 		&self,
 		init_code: Vec<u8>,
+		input: Arc<Vec<u8>>,
 		state: ES::State,
 		_handler: &mut H,
 	) -> Result<InvokerControl<Self::Interpreter, (ExitResult, (ES::State, Vec<u8>))>, ExitError> {
-		let machine = Machine::new(
-			Rc::new(init_code),
-			Rc::new(Vec::new()),
-			self.config.stack_limit,
-			self.config.memory_limit,
-			state,
-		);
+		let eof = match self.eof_layout(&init_code) {
+			Some(Ok(layout)) => Some(layout),
+			Some(Err(err)) => {
+				return Ok(InvokerControl::DirectExit((Err(err), (state, Vec::new()))))
+			}
+			None => None,
+		};
+
+		let mut machine = self.machine(init_code, input, state);
+		if let Some(layout) = eof {
+			machine = machine.with_eof(layout);
+		}
 
 		let ret = InvokerControl::Enter(EtableInterpreter::new(machine, self.etable));
 
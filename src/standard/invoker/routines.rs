@@ -1,7 +1,8 @@
-use alloc::vec::Vec;
+use alloc::{sync::Arc, vec::Vec};
 
 use evm_interpreter::{
-	error::{CallTrapData, CreateTrapData, ExitError, ExitException, ExitResult},
+	eof,
+	error::{CallTrapData, CreateScheme, CreateTrapData, ExitError, ExitException, ExitResult},
 	opcode::Opcode,
 	runtime::{RuntimeBackend, RuntimeEnvironment, RuntimeState, SetCodeOrigin, Transfer},
 };
@@ -16,10 +17,10 @@ use crate::{
 
 #[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn make_enter_call_machine<H, R>(
-	_config: &Config,
+	config: &Config,
 	resolver: &R,
 	code_address: H160,
-	input: Vec<u8>,
+	input: Arc<Vec<u8>>,
 	transfer: Option<Transfer>,
 	state: R::State,
 	handler: &mut H,
@@ -30,6 +31,9 @@ where
 	R: Resolver<H>,
 {
 	handler.mark_hot(state.as_ref().context.address, None);
+	if !config.eip_6780_enabled {
+		handler.mark_deletable(state.as_ref().context.address);
+	}
 
 	if let Some(transfer) = transfer {
 		handler.transfer(transfer)?;
@@ -44,7 +48,10 @@ pub fn make_enter_create_machine<H, R>(
 	resolver: &R,
 	caller: H160,
 	init_code: Vec<u8>,
+	input: Arc<Vec<u8>>,
+	eof_create: bool,
 	transfer: Transfer,
+	increase_caller_nonce: bool,
 	state: R::State,
 	handler: &mut H,
 ) -> Result<InvokerControl<R::Interpreter, (ExitResult, (R::State, Vec<u8>))>, ExitError>
@@ -58,6 +65,10 @@ where
 			return Err(ExitException::CreateContractLimit.into());
 		}
 	}
+	// EOF initcode can only be run by `EOFCREATE` (see EIP-7620).
+	if config.eof_deployment && !eof_create && eof::is_eof(&init_code) {
+		return Err(ExitException::InvalidContainer.into());
+	}
 
 	handler.mark_hot(caller, None);
 	handler.mark_hot(state.as_ref().context.address, None);
@@ -69,14 +80,17 @@ where
 	{
 		return Err(ExitException::CreateCollision.into());
 	}
-	handler.inc_nonce(caller)?;
+	if increase_caller_nonce {
+		handler.inc_nonce(caller)?;
+	}
 	if config.create_increase_nonce {
 		handler.inc_nonce(state.as_ref().context.address)?;
 	}
 
 	handler.reset_storage(state.as_ref().context.address);
+	handler.mark_deletable(state.as_ref().context.address);
 
-	resolver.resolve_create(init_code, state, handler)
+	resolver.resolve_create(init_code, input, state, handler)
 }
 
 #[allow(clippy::type_complexity, clippy::too_many_arguments)]
@@ -151,7 +165,9 @@ where
 			scheme,
 			value,
 			code: _,
+			input,
 		} = trap_data.clone();
+		let eof_create = matches!(scheme, CreateScheme::EofCreate { .. });
 
 		let caller = scheme.caller();
 		let address = scheme.address(handler);
@@ -163,7 +179,16 @@ where
 		};
 
 		let machine = make_enter_create_machine(
-			config, resolver, caller, code, transfer, state, handler,
+			config,
+			resolver,
+			caller,
+			code,
+			Arc::new(input),
+			eof_create,
+			transfer,
+			true,
+			state,
+			handler,
 		)?;
 
 		Ok((
@@ -186,7 +211,12 @@ where
 
 fn check_first_byte(config: &Config, code: &[u8]) -> Result<(), ExitError> {
 	if config.disallow_executable_format && Some(&Opcode::EOFMAGIC.as_u8()) == code.first() {
-		return Err(ExitException::InvalidOpcode(Opcode::EOFMAGIC).into());
+		if config.eof_deployment && eof::is_eof(code) {
+			return eof::validate(code)
+				.map(|_| ())
+				.map_err(|_| ExitException::InvalidContainer.into());
+		}
+		return Err(ExitException::CreateContractStartingWithEF.into());
 	}
 	Ok(())
 }
@@ -217,3 +247,130 @@ where
 
 	Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+	use alloc::collections::BTreeSet;
+
+	use primitive_types::H256;
+
+	use super::*;
+	use crate::{
+		backend::WitnessAccount,
+		standard::{testing, TransactArgs},
+	};
+
+	// PUSH1 0xff SELFDESTRUCT
+	const SELFDESTRUCT_CODE: &str = "60ffff";
+
+	fn created_address() -> H160 {
+		testing::create2_address(
+			H160::repeat_byte(0x01),
+			H256::zero(),
+			&hex::decode(SELFDESTRUCT_CODE).unwrap(),
+		)
+	}
+
+	fn selfdestructs(config: &Config, args: TransactArgs) -> BTreeSet<H160> {
+		let mut backend = testing::backend([
+			(H160::repeat_byte(0x01), testing::account(&[])),
+			(
+				H160::repeat_byte(0x02),
+				testing::account(&hex::decode(SELFDESTRUCT_CODE).unwrap()),
+			),
+			(H160::from_low_u64_be(0xff), testing::account(&[])),
+			(created_address(), testing::account(&[])),
+		]);
+
+		testing::transact_with(config, args, &mut backend).unwrap();
+
+		let (_, changeset) = backend.deconstruct();
+		changeset.deletes
+	}
+
+	#[test]
+	fn selfdestruct_deletes_only_created_accounts() {
+		let call = || testing::call(H160::repeat_byte(0x01), H160::repeat_byte(0x02));
+		let create = testing::create2(
+			H160::repeat_byte(0x01),
+			hex::decode(SELFDESTRUCT_CODE).unwrap(),
+			H256::zero(),
+		);
+
+		assert_eq!(
+			selfdestructs(&Config::shanghai(), call()),
+			[H160::repeat_byte(0x02)].into()
+		);
+		assert!(selfdestructs(&Config::cancun(), call()).is_empty());
+		assert_eq!(
+			selfdestructs(&Config::cancun(), create),
+			[created_address()].into()
+		);
+	}
+
+	#[test]
+	fn eofcreate_deploys_returned_container() {
+		let caller = H160::repeat_byte(0x01);
+		let factory = H160::repeat_byte(0x02);
+		// Container with a single STOP.
+		let runtime = hex::decode("ef00010100040200010001ff0000000080000000").unwrap();
+		// PUSH0 PUSH0 RETURNCONTRACT 0
+		let initcode = [
+			hex::decode("ef0001010004020001000403000100000014ff000000008000025f5fee00").unwrap(),
+			runtime.clone(),
+		]
+		.concat();
+		// PUSH0 PUSH0 PUSH0 PUSH0 EOFCREATE 0 PUSH0 SSTORE STOP
+		let code = [
+			hex::decode("ef0001010004020001000903000100000032ff000000008000045f5f5f5fec005f5500")
+				.unwrap(),
+			initcode,
+		]
+		.concat();
+
+		let created = CreateScheme::EofCreate {
+			caller: factory,
+			salt: H256::zero(),
+		}
+		.address(&testing::backend([]));
+		let account = |code: &[u8]| WitnessAccount {
+			storage: testing::zero_slots(&[0]),
+			..testing::account(code)
+		};
+		let mut backend = testing::backend([
+			(caller, account(&[])),
+			(factory, account(&code)),
+			(created, account(&[])),
+		]);
+
+		let mut config = Config::cancun();
+		config.eof_deployment = true;
+		testing::transact_with(&config, testing::call(caller, factory), &mut backend).unwrap();
+
+		let (_, changeset) = backend.deconstruct();
+		assert_eq!(changeset.codes.get(&created), Some(&runtime));
+		assert_eq!(
+			changeset.storages.get(&(factory, H256::zero())),
+			Some(&H256::from(created))
+		);
+	}
+
+	#[test]
+	fn invalid_container_is_not_executed() {
+		let caller = H160::repeat_byte(0x01);
+		let address = H160::repeat_byte(0x02);
+		// Container calling a missing code section: CALLF 1 STOP
+		let code = hex::decode("ef00010100040200010004ff00000000800000e3000100").unwrap();
+		let mut backend = testing::backend([
+			(caller, testing::account(&[])),
+			(address, testing::account(&code)),
+		]);
+
+		let mut config = Config::cancun();
+		config.eof_deployment = true;
+		assert_eq!(
+			testing::transact_with(&config, testing::call(caller, address), &mut backend).err(),
+			Some(ExitException::InvalidContainer.into())
+		);
+	}
+}
@@ -1,12 +1,15 @@
-use alloc::vec::Vec;
+use alloc::{collections::BTreeSet, vec::Vec};
 
 use evm_interpreter::{
-	error::ExitError,
+	error::{ExitError, ExitResult},
 	runtime::{GasState, RuntimeState},
 };
 use primitive_types::{H160, H256, U256};
 
-use crate::{standard::Config, MergeStrategy};
+use crate::{
+	standard::{Config, FrameGas, InternalCall, InternalCallScheme},
+	MergeStrategy,
+};
 
 pub trait InvokerState<'config>: GasState + Sized {
 	fn new_transact_call(
@@ -14,6 +17,7 @@ pub trait InvokerState<'config>: GasState + Sized {
 		gas_limit: U256,
 		data: &[u8],
 		access_list: &[(H160, Vec<H256>)],
+		authorization_len: usize,
 		config: &'config Config,
 	) -> Result<Self, ExitError>;
 	fn new_transact_create(
@@ -37,5 +41,43 @@ pub trait InvokerState<'config>: GasState + Sized {
 
 	fn is_static(&self) -> bool;
 	fn effective_gas(&self) -> U256;
+	/// Gas granted to the frame by a sponsor, on top of its gas limit. It is
+	/// included in [InvokerState::effective_gas].
+	fn sponsored_gas(&self) -> U256 {
+		U256::zero()
+	}
 	fn config(&self) -> &Config;
+	/// Set what the frame and its subframes record in the transaction result,
+	/// see [Invoker::with_frame_gas_depth](crate::standard::Invoker::with_frame_gas_depth)
+	/// and [Invoker::with_call_tree](crate::standard::Invoker::with_call_tree).
+	fn set_recording(&mut self, _frame_gas_depth: usize, _record_call_tree: bool) {}
+	/// Take the recorded gas usage of call frames.
+	fn take_frames(&mut self) -> Vec<FrameGas> {
+		Vec::new()
+	}
+	/// Record the call of the frame, once it exited. See
+	/// [Invoker::with_call_tree](crate::standard::Invoker::with_call_tree).
+	fn record_call(
+		&mut self,
+		_scheme: InternalCallScheme,
+		_callee: H160,
+		_value: U256,
+		_result: &ExitResult,
+	) {
+	}
+	/// Take the recorded internal calls.
+	fn take_calls(&mut self) -> Vec<InternalCall> {
+		Vec::new()
+	}
+	/// Record that the frame created a contract at the address.
+	fn record_created(&mut self, _address: H160) {}
+	/// Take the contracts created by the frame and its committed subframes,
+	/// in creation order.
+	fn take_created(&mut self) -> Vec<H160> {
+		Vec::new()
+	}
+	/// Take the accounts touched by the frame and its committed subframes.
+	fn take_touched(&mut self) -> BTreeSet<H160> {
+		BTreeSet::new()
+	}
 }
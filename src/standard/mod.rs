@@ -4,25 +4,50 @@
 //! Ethereum mainnet. Most of them can still be customized to add additional functionality, by
 //! wrapping them or replacing the generic parameters.
 
+mod blob;
 mod config;
+mod delegation;
 mod gasometer;
 mod invoker;
+mod replay;
+mod rules;
+mod system;
+#[cfg(test)]
+pub(crate) mod testing;
 
-use alloc::vec::Vec;
+use alloc::{collections::BTreeSet, vec::Vec};
 
 use evm_interpreter::{
-	error::{CallCreateTrap, ExitError},
+	error::{CallCreateTrap, ExitError, ExitResult},
 	etable, machine,
 	runtime::{GasState, RuntimeState},
 };
 use primitive_types::{H160, H256, U256};
 
 pub use self::{
-	config::Config,
-	gasometer::{eval as eval_gasometer, GasometerState},
+	blob::{BlobSchedule, GAS_PER_BLOB, MIN_BASE_FEE_PER_BLOB_GAS},
+	config::{Config, ConfigBuilder, ConfigChange, Eip, Fork, UnknownFork, UnmetEipDependency},
+	delegation::{
+		apply_authorizations, delegation_designator, delegation_target, Authorization,
+		DELEGATION_PREFIX, PER_AUTH_BASE_COST, PER_EMPTY_ACCOUNT_COST,
+	},
+	gasometer::{
+		eval as eval_gasometer, memory_expansion_cost, memory_gas, preview_opcode_cost,
+		GasometerState,
+	},
 	invoker::{
-		routines, EtableResolver, Invoker, InvokerState, PrecompileSet, Resolver, SubstackInvoke,
-		TransactArgs, TransactInvoke, TransactValue,
+		routines, DynamicPrecompileSet, EffectiveGasPrice, EtableResolver, FrameGas,
+		GuardedPrecompileSet, InternalCall, InternalCallScheme, Invoker, InvokerState,
+		MemoizingResolver, Precompile, PrecompileGuard, PrecompileGuards, PrecompileSet,
+		PureCallCache, Resolver, SubstackInvoke, TransactArgs, TransactInvoke, TransactKind,
+		TransactOutcome, TransactValue,
+	},
+	replay::ReplayBundle,
+	rules::{ChainRules, ChainRulesEtable, ChainRulesPrecompileSet},
+	system::{
+		process_withdrawals, system_call, update_beacon_root, update_block_hash_history,
+		Withdrawal, BEACON_ROOTS_ADDRESS, HISTORY_STORAGE_ADDRESS, SYSTEM_ADDRESS,
+		SYSTEM_CALL_GAS_LIMIT,
 	},
 };
 use crate::{gasometer::GasMutState, MergeStrategy};
@@ -40,6 +65,22 @@ pub type Etable<'config, H, F = Efn<'config, H>> =
 pub struct State<'config> {
 	pub runtime: RuntimeState,
 	pub gasometer: GasometerState<'config>,
+	/// Gas usage of merged subcall frames, relative to this frame.
+	pub frames: Vec<FrameGas>,
+	/// Internal calls of this frame and its subframes, in call order.
+	pub calls: Vec<InternalCall>,
+	/// Contracts created by this frame and its committed subframes, in
+	/// creation order.
+	pub created: Vec<H160>,
+	/// Accounts touched, in the EIP-161 sense, by this frame and its
+	/// committed subframes. The context address of each frame is touched, as
+	/// are the caller and the coinbase of the transaction.
+	pub touched: BTreeSet<H160>,
+	/// Number of call frame levels whose gas usage is recorded, see
+	/// [Invoker::with_frame_gas_depth].
+	pub frame_gas_depth: usize,
+	/// Whether internal calls are recorded, see [Invoker::with_call_tree].
+	pub record_call_tree: bool,
 }
 
 impl<'config> AsRef<RuntimeState> for State<'config> {
@@ -76,6 +117,14 @@ impl<'config> GasMutState for State<'config> {
 	fn record_gas(&mut self, gas: U256) -> Result<(), ExitError> {
 		self.gasometer.record_gas(gas)
 	}
+
+	fn refunded_gas(&self) -> u64 {
+		self.gasometer.refunded_gas()
+	}
+
+	fn record_refund(&mut self, refund: i64) {
+		self.gasometer.record_refund(refund)
+	}
 }
 
 impl<'config> InvokerState<'config> for State<'config> {
@@ -84,11 +133,25 @@ impl<'config> InvokerState<'config> for State<'config> {
 		gas_limit: U256,
 		data: &[u8],
 		access_list: &[(H160, Vec<H256>)],
+		authorization_len: usize,
 		config: &'config Config,
 	) -> Result<Self, ExitError> {
+		let touched = [runtime.context.caller, runtime.context.address].into();
 		Ok(Self {
 			runtime,
-			gasometer: GasometerState::new_transact_call(gas_limit, data, access_list, config)?,
+			gasometer: GasometerState::new_transact_call(
+				gas_limit,
+				data,
+				access_list,
+				authorization_len,
+				config,
+			)?,
+			frames: Vec::new(),
+			calls: Vec::new(),
+			created: Vec::new(),
+			touched,
+			frame_gas_depth: 0,
+			record_call_tree: false,
 		})
 	}
 	fn new_transact_create(
@@ -98,9 +161,16 @@ impl<'config> InvokerState<'config> for State<'config> {
 		access_list: &[(H160, Vec<H256>)],
 		config: &'config Config,
 	) -> Result<Self, ExitError> {
+		let touched = [runtime.context.caller, runtime.context.address].into();
 		Ok(Self {
 			runtime,
 			gasometer: GasometerState::new_transact_create(gas_limit, code, access_list, config)?,
+			frames: Vec::new(),
+			calls: Vec::new(),
+			created: Vec::new(),
+			touched,
+			frame_gas_depth: 0,
+			record_call_tree: false,
 		})
 	}
 
@@ -111,14 +181,63 @@ impl<'config> InvokerState<'config> for State<'config> {
 		is_static: bool,
 		call_has_value: bool,
 	) -> Result<Self, ExitError> {
+		let touched = [runtime.context.address].into();
 		Ok(Self {
 			runtime,
 			gasometer: self
 				.gasometer
 				.submeter(gas_limit, is_static, call_has_value)?,
+			frames: Vec::new(),
+			calls: Vec::new(),
+			created: Vec::new(),
+			touched,
+			frame_gas_depth: self.frame_gas_depth,
+			record_call_tree: self.record_call_tree,
 		})
 	}
 	fn merge(&mut self, substate: Self, strategy: MergeStrategy) {
+		// Depth here is relative to the current frame, which is never lower
+		// than the absolute depth, so frames can be dropped early.
+		let max_depth = self.frame_gas_depth;
+		if max_depth > 0 {
+			let gas_limit = substate.gasometer.gas_limit64();
+			let used_gas = match strategy {
+				MergeStrategy::Commit | MergeStrategy::Revert => {
+					gas_limit - substate.gasometer.gas64()
+				}
+				MergeStrategy::Discard => gas_limit,
+			};
+
+			self.frames.push(FrameGas {
+				depth: 1,
+				address: substate.runtime.context.address,
+				gas_limit,
+				used_gas,
+				strategy,
+			});
+			self.frames.extend(
+				substate
+					.frames
+					.into_iter()
+					.filter(|frame| frame.depth < max_depth)
+					.map(|frame| FrameGas {
+						depth: frame.depth + 1,
+						..frame
+					}),
+			);
+		}
+
+		self.calls
+			.extend(substate.calls.into_iter().map(|call| InternalCall {
+				depth: call.depth + 1,
+				..call
+			}));
+
+		if matches!(strategy, MergeStrategy::Commit) {
+			self.created.extend(substate.created);
+			self.touched.extend(substate.touched);
+		}
+
 		self.gasometer.merge(substate.gasometer, strategy)
 	}
 
@@ -132,7 +251,61 @@ impl<'config> InvokerState<'config> for State<'config> {
 	fn effective_gas(&self) -> U256 {
 		self.gasometer.effective_gas()
 	}
+	fn sponsored_gas(&self) -> U256 {
+		self.gasometer.sponsored_gas().into()
+	}
 	fn config(&self) -> &Config {
 		self.gasometer.config
 	}
+	fn set_recording(&mut self, frame_gas_depth: usize, record_call_tree: bool) {
+		self.frame_gas_depth = frame_gas_depth;
+		self.record_call_tree = record_call_tree;
+	}
+	fn take_frames(&mut self) -> Vec<FrameGas> {
+		core::mem::take(&mut self.frames)
+	}
+	fn record_call(
+		&mut self,
+		scheme: InternalCallScheme,
+		callee: H160,
+		value: U256,
+		result: &ExitResult,
+	) {
+		if !self.record_call_tree {
+			return;
+		}
+
+		let gas_limit = self.gasometer.gas_limit64();
+		let used_gas = match result {
+			Ok(_) | Err(ExitError::Reverted) => gas_limit - self.gasometer.gas64(),
+			Err(_) => gas_limit,
+		};
+		// As for created contracts, the call goes before its subcalls.
+		self.calls.insert(
+			0,
+			InternalCall {
+				depth: 0,
+				scheme,
+				callee,
+				value,
+				gas_limit,
+				used_gas,
+				result: result.clone(),
+			},
+		);
+	}
+	fn take_calls(&mut self) -> Vec<InternalCall> {
+		core::mem::take(&mut self.calls)
+	}
+	fn record_created(&mut self, address: H160) {
+		// Subframes exit before their parent, so the address goes before
+		// those they created.
+		self.created.insert(0, address);
+	}
+	fn take_created(&mut self) -> Vec<H160> {
+		core::mem::take(&mut self.created)
+	}
+	fn take_touched(&mut self) -> BTreeSet<H160> {
+		core::mem::take(&mut self.touched)
+	}
 }
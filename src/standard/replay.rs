@@ -0,0 +1,52 @@
+use evm_interpreter::runtime::{BlockEnv, RuntimeEnvironment};
+
+use crate::{
+	backend::{ExecutionWitness, WitnessBackend},
+	standard::{Config, TransactArgs},
+};
+
+/// Self-contained bundle reproducing a transaction execution, for bug
+/// reports.
+///
+/// To export a bundle, run the transaction on a backend wrapped in a
+/// [WitnessRecorder](crate::backend::WitnessRecorder), and build the bundle
+/// from the recorded witness, whatever the result of the execution. To
+/// reproduce it, run [ReplayBundle::transaction] with [ReplayBundle::config]
+/// on an [OverlayedBackend](crate::backend::OverlayedBackend) over
+/// [ReplayBundle::backend]. With the `serde` feature, the bundle can be
+/// exchanged in any serde format.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReplayBundle {
+	/// Block information. Block hashes read are part of the pre-state.
+	pub environment: BlockEnv,
+	/// State read by the transaction, before it was executed.
+	pub pre_state: ExecutionWitness,
+	/// The transaction.
+	pub transaction: TransactArgs,
+	/// Configuration used to execute the transaction.
+	pub config: Config,
+}
+
+impl ReplayBundle {
+	/// Create a new bundle from the environment of the execution and its
+	/// recorded pre-state.
+	pub fn new<E: RuntimeEnvironment>(
+		environment: &E,
+		pre_state: ExecutionWitness,
+		transaction: TransactArgs,
+		config: &Config,
+	) -> Self {
+		Self {
+			environment: environment.block_env(),
+			pre_state,
+			transaction,
+			config: config.clone(),
+		}
+	}
+
+	/// Backend serving the environment and pre-state of the bundle.
+	pub fn backend(&self) -> WitnessBackend<BlockEnv> {
+		WitnessBackend::new(self.environment.clone(), self.pre_state.clone())
+	}
+}
@@ -0,0 +1,211 @@
+use alloc::vec::Vec;
+
+use evm_interpreter::{
+	error::{ExitError, ExitException, ExitResult},
+	etable::{Control, EtableSet},
+	machine::Machine,
+	opcode::Opcode,
+	runtime::RuntimeBackend,
+};
+use primitive_types::{H160, U256};
+
+use crate::standard::{PrecompileSet, TransactArgs, TransactInvoke};
+
+/// Chain-specific rules, for L2s and other forks whose behavior diverges from
+/// Ethereum mainnet in ways [Config](crate::standard::Config) cannot express.
+///
+/// The invoker consults the rules for fees and the refund policy (see
+/// [Invoker::with_chain_rules](crate::standard::Invoker::with_chain_rules)).
+/// Opcodes and precompiles are filtered by wrapping the etable in
+/// [ChainRulesEtable] and the precompile set in [ChainRulesPrecompileSet].
+pub trait ChainRules<H> {
+	/// Whether the opcode is enabled.
+	fn opcode_enabled(&self, _opcode: Opcode) -> bool {
+		true
+	}
+
+	/// Whether the precompile at the address is enabled.
+	fn precompile_enabled(&self, _address: H160) -> bool {
+		true
+	}
+
+	/// Gas refunded to the caller at the end of the transaction, given the gas
+	/// left including the capped refund counter.
+	fn refunded_gas(&self, _invoke: &TransactInvoke, left_gas: U256) -> U256 {
+		left_gas
+	}
+
+	/// Recipient of the priority fee.
+	fn priority_fee_recipient(&self, coinbase: H160) -> H160 {
+		coinbase
+	}
+
+	/// Recipient of the base fee. If `None`, the base fee is burned.
+	fn base_fee_recipient(&self) -> Option<H160> {
+		None
+	}
+
+	/// Extra fee charged to the caller for the transaction, on top of the gas
+	/// fee, such as the L1 data availability cost of rollups. It is withdrawn
+	/// together with the gas fee, and is never refunded.
+	fn extra_fee(&self, _args: &TransactArgs, _handler: &H) -> U256 {
+		U256::zero()
+	}
+
+	/// Recipient of the extra fee. If `None`, the extra fee is burned.
+	fn extra_fee_recipient(&self, _handler: &H) -> Option<H160> {
+		None
+	}
+
+	/// Charge the fees of a transaction, the gas fee at the gas limit and the
+	/// extra fee, to the caller before execution. Fee abstraction chains
+	/// override it, with [ChainRules::refund_fee] and
+	/// [ChainRules::deposit_fee], to settle fees in a token instead of the
+	/// native balance.
+	fn withdraw_fee(&self, caller: H160, amount: U256, handler: &mut H) -> Result<(), ExitError>
+	where
+		H: RuntimeBackend,
+	{
+		handler.withdrawal(caller, amount)
+	}
+
+	/// Refund the fee of the gas left to the caller after execution.
+	fn refund_fee(&self, caller: H160, amount: U256, handler: &mut H)
+	where
+		H: RuntimeBackend,
+	{
+		handler.deposit(caller, amount)
+	}
+
+	/// Pay a part of the fees to a recipient, such as the coinbase.
+	fn deposit_fee(&self, recipient: H160, amount: U256, handler: &mut H)
+	where
+		H: RuntimeBackend,
+	{
+		handler.deposit(recipient, amount)
+	}
+}
+
+impl<H> ChainRules<H> for () {}
+
+impl<'rules, H, T: ChainRules<H>> ChainRules<H> for &'rules T {
+	fn opcode_enabled(&self, opcode: Opcode) -> bool {
+		(*self).opcode_enabled(opcode)
+	}
+
+	fn precompile_enabled(&self, address: H160) -> bool {
+		(*self).precompile_enabled(address)
+	}
+
+	fn refunded_gas(&self, invoke: &TransactInvoke, left_gas: U256) -> U256 {
+		(*self).refunded_gas(invoke, left_gas)
+	}
+
+	fn priority_fee_recipient(&self, coinbase: H160) -> H160 {
+		(*self).priority_fee_recipient(coinbase)
+	}
+
+	fn base_fee_recipient(&self) -> Option<H160> {
+		(*self).base_fee_recipient()
+	}
+
+	fn extra_fee(&self, args: &TransactArgs, handler: &H) -> U256 {
+		(*self).extra_fee(args, handler)
+	}
+
+	fn extra_fee_recipient(&self, handler: &H) -> Option<H160> {
+		(*self).extra_fee_recipient(handler)
+	}
+
+	fn withdraw_fee(&self, caller: H160, amount: U256, handler: &mut H) -> Result<(), ExitError>
+	where
+		H: RuntimeBackend,
+	{
+		(*self).withdraw_fee(caller, amount, handler)
+	}
+
+	fn refund_fee(&self, caller: H160, amount: U256, handler: &mut H)
+	where
+		H: RuntimeBackend,
+	{
+		(*self).refund_fee(caller, amount, handler)
+	}
+
+	fn deposit_fee(&self, recipient: H160, amount: U256, handler: &mut H)
+	where
+		H: RuntimeBackend,
+	{
+		(*self).deposit_fee(recipient, amount, handler)
+	}
+}
+
+/// Etable set rejecting opcodes disabled by the chain rules as invalid.
+pub struct ChainRulesEtable<'rules, C, ES> {
+	rules: &'rules C,
+	etable: ES,
+}
+
+impl<'rules, C, ES> ChainRulesEtable<'rules, C, ES> {
+	/// Wrap an etable set.
+	pub fn new(rules: &'rules C, etable: ES) -> Self {
+		Self { rules, etable }
+	}
+}
+
+impl<'rules, C, ES> EtableSet for ChainRulesEtable<'rules, C, ES>
+where
+	ES: EtableSet,
+	C: ChainRules<ES::Handle>,
+{
+	type State = ES::State;
+	type Handle = ES::Handle;
+	type Trap = ES::Trap;
+
+	fn eval(
+		&self,
+		machine: &mut Machine<Self::State>,
+		handle: &mut Self::Handle,
+		opcode: Opcode,
+		position: usize,
+	) -> Control<Self::Trap> {
+		if !self.rules.opcode_enabled(opcode) {
+			return Control::Exit(ExitException::InvalidOpcode(opcode).into());
+		}
+
+		self.etable.eval(machine, handle, opcode, position)
+	}
+}
+
+/// Precompile set hiding precompiles disabled by the chain rules.
+pub struct ChainRulesPrecompileSet<'rules, C, P> {
+	rules: &'rules C,
+	precompiles: P,
+}
+
+impl<'rules, C, P> ChainRulesPrecompileSet<'rules, C, P> {
+	/// Wrap a precompile set.
+	pub fn new(rules: &'rules C, precompiles: P) -> Self {
+		Self { rules, precompiles }
+	}
+}
+
+impl<'rules, S, H, C, P> PrecompileSet<S, H> for ChainRulesPrecompileSet<'rules, C, P>
+where
+	C: ChainRules<H>,
+	P: PrecompileSet<S, H>,
+{
+	fn execute(
+		&self,
+		code_address: H160,
+		input: &[u8],
+		state: &mut S,
+		handler: &mut H,
+	) -> Option<(ExitResult, Vec<u8>)> {
+		if !self.rules.precompile_enabled(code_address) {
+			return None;
+		}
+
+		self.precompiles
+			.execute(code_address, input, state, handler)
+	}
+}
@@ -0,0 +1,212 @@
+use alloc::vec::Vec;
+use core::convert::Infallible;
+
+use evm_interpreter::{
+	error::ExitError,
+	runtime::{RuntimeBackend, RuntimeBaseBackend},
+	RunInterpreter,
+};
+use primitive_types::{H160, H256, U256};
+
+use crate::{
+	standard::{TransactArgs, TransactKind},
+	transact, Invoker as InvokerT,
+};
+
+/// Sender of system calls.
+pub const SYSTEM_ADDRESS: H160 = H160([
+	0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+	0xff, 0xff, 0xff, 0xfe,
+]);
+
+/// Gas limit of system calls. It does not count against the block gas limit.
+pub const SYSTEM_CALL_GAS_LIMIT: u64 = 30_000_000;
+
+/// Beacon block root contract of
+/// [EIP-4788](https://eips.ethereum.org/EIPS/eip-4788).
+pub const BEACON_ROOTS_ADDRESS: H160 = H160([
+	0x00, 0x0f, 0x3d, 0xf6, 0xd7, 0x32, 0x80, 0x7e, 0xf1, 0x31, 0x9f, 0xb7, 0xb8, 0xbb, 0x85, 0x22,
+	0xd0, 0xbe, 0xac, 0x02,
+]);
+
+/// Block hash history contract of
+/// [EIP-2935](https://eips.ethereum.org/EIPS/eip-2935).
+pub const HISTORY_STORAGE_ADDRESS: H160 = H160([
+	0x00, 0x00, 0xf9, 0x08, 0x27, 0xf1, 0xc5, 0x3a, 0x10, 0xcb, 0x7a, 0x02, 0x33, 0x5b, 0x17, 0x53,
+	0x20, 0x00, 0x29, 0x35,
+]);
+
+/// Validator withdrawal of [EIP-4895](https://eips.ethereum.org/EIPS/eip-4895).
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Withdrawal {
+	/// Index of the withdrawal.
+	pub index: u64,
+	/// Index of the withdrawing validator.
+	pub validator_index: u64,
+	/// Recipient.
+	pub address: H160,
+	/// Amount, in Gwei.
+	pub amount: u64,
+}
+
+impl TransactArgs {
+	/// Arguments of a system call into `address`, sent by [SYSTEM_ADDRESS]
+	/// with [SYSTEM_CALL_GAS_LIMIT] and no fee.
+	pub fn system_call(address: H160, data: Vec<u8>) -> Self {
+		TransactArgs::Call {
+			caller: SYSTEM_ADDRESS,
+			address,
+			value: U256::zero(),
+			data,
+			gas_limit: U256::from(SYSTEM_CALL_GAS_LIMIT),
+			gas_price: U256::zero(),
+			access_list: Vec::new(),
+			authorization_list: Vec::new(),
+			blob_hashes: Vec::new(),
+			kind: TransactKind::System,
+		}
+	}
+}
+
+/// Run a system call into `address`, see [TransactArgs::system_call]. As
+/// specified by the system call EIPs, nothing is run if `address` has no
+/// code, in which case `None` is returned.
+pub fn system_call<H, Tr, I>(
+	address: H160,
+	data: Vec<u8>,
+	backend: &mut H,
+	invoker: &I,
+) -> Result<Option<I::TransactValue>, ExitError>
+where
+	H: RuntimeBaseBackend,
+	I: InvokerT<H, Tr, TransactArgs = TransactArgs, Interrupt = Infallible>,
+	I::Interpreter: RunInterpreter<H, Tr>,
+{
+	if backend.code_size(address).is_zero() {
+		return Ok(None);
+	}
+
+	transact(
+		TransactArgs::system_call(address, data),
+		None,
+		backend,
+		invoker,
+	)
+	.map(Some)
+}
+
+/// Store the parent beacon block root at the start of a block, as in
+/// EIP-4788.
+pub fn update_beacon_root<H, Tr, I>(
+	parent_beacon_block_root: H256,
+	backend: &mut H,
+	invoker: &I,
+) -> Result<Option<I::TransactValue>, ExitError>
+where
+	H: RuntimeBaseBackend,
+	I: InvokerT<H, Tr, TransactArgs = TransactArgs, Interrupt = Infallible>,
+	I::Interpreter: RunInterpreter<H, Tr>,
+{
+	system_call(
+		BEACON_ROOTS_ADDRESS,
+		parent_beacon_block_root.as_bytes().to_vec(),
+		backend,
+		invoker,
+	)
+}
+
+/// Store the parent block hash at the start of a block, as in EIP-2935.
+pub fn update_block_hash_history<H, Tr, I>(
+	parent_hash: H256,
+	backend: &mut H,
+	invoker: &I,
+) -> Result<Option<I::TransactValue>, ExitError>
+where
+	H: RuntimeBaseBackend,
+	I: InvokerT<H, Tr, TransactArgs = TransactArgs, Interrupt = Infallible>,
+	I::Interpreter: RunInterpreter<H, Tr>,
+{
+	system_call(
+		HISTORY_STORAGE_ADDRESS,
+		parent_hash.as_bytes().to_vec(),
+		backend,
+		invoker,
+	)
+}
+
+/// Credit withdrawals at the end of a block. Withdrawals are not EVM calls:
+/// the balances are increased without running any code and without fee.
+pub fn process_withdrawals<'a, H, I>(withdrawals: I, backend: &mut H)
+where
+	H: RuntimeBackend,
+	I: IntoIterator<Item = &'a Withdrawal>,
+{
+	for withdrawal in withdrawals {
+		let amount = U256::from(withdrawal.amount) * U256::from(1_000_000_000u64);
+		backend.deposit(withdrawal.address, amount);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		backend::WitnessAccount,
+		standard::{testing, Config, EtableResolver, Invoker},
+	};
+
+	#[test]
+	fn block_prelude_and_withdrawals() {
+		let recipient = H160::repeat_byte(0x01);
+		let account = |code: &str| WitnessAccount {
+			exists: Some(false),
+			storage: testing::zero_slots(&[0]),
+			..testing::account(&hex::decode(code).unwrap())
+		};
+		let mut backend = testing::backend([
+			(SYSTEM_ADDRESS, account("")),
+			(recipient, account("")),
+			// PUSH1 0 CALLDATALOAD PUSH1 0 SSTORE
+			(BEACON_ROOTS_ADDRESS, account("600035600055")),
+			(HISTORY_STORAGE_ADDRESS, account("")),
+		]);
+
+		let config = Config::cancun();
+		let etable = testing::etable();
+		let resolver = EtableResolver::new(&config, &(), &etable);
+		let invoker = Invoker::new(&config, &resolver);
+
+		let root = H256::repeat_byte(0xbe);
+		assert!(update_beacon_root(root, &mut backend, &invoker)
+			.unwrap()
+			.is_some());
+		assert!(
+			update_block_hash_history(H256::repeat_byte(0xaa), &mut backend, &invoker)
+				.unwrap()
+				.is_none()
+		);
+		process_withdrawals(
+			&[Withdrawal {
+				index: 0,
+				validator_index: 0,
+				address: recipient,
+				amount: 2,
+			}],
+			&mut backend,
+		);
+
+		let (_, changeset) = backend.deconstruct();
+		assert_eq!(
+			changeset
+				.storages
+				.get(&(BEACON_ROOTS_ADDRESS, H256::zero())),
+			Some(&root)
+		);
+		assert_eq!(changeset.nonces.get(&SYSTEM_ADDRESS), None);
+		assert_eq!(
+			changeset.balances.get(&recipient),
+			Some(&U256::from(2_000_000_000u64))
+		);
+	}
+}
@@ -0,0 +1,140 @@
+//! Fixtures shared by the tests of the crate.
+
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use evm_interpreter::{
+	error::{CallCreateTrap, CreateScheme, ExitError},
+	runtime::{BlockEnv, RuntimeBackend, RuntimeEnvironment},
+};
+use primitive_types::{H160, H256, U256};
+use sha3::{Digest, Keccak256};
+
+use crate::{
+	backend::{
+		ExecutionWitness, OverlayedBackend, TransactionalBackend, WitnessAccount, WitnessBackend,
+	},
+	standard::{
+		self, Config, Etable, EtableResolver, Invoker, State, TransactArgs, TransactKind,
+		TransactValue,
+	},
+	transact,
+};
+
+/// Gas limit of the transactions built here.
+pub const GAS_LIMIT: u64 = 1_000_000;
+
+/// Existing account with the given code and nothing else.
+pub fn account(code: &[u8]) -> WitnessAccount {
+	WitnessAccount {
+		balance: Some(U256::zero()),
+		nonce: Some(U256::zero()),
+		code: Some(code.to_vec()),
+		exists: Some(true),
+		storage: Default::default(),
+	}
+}
+
+/// Zero storage slots at `indices`, for accounts whose storage is read.
+pub fn zero_slots(indices: &[u64]) -> BTreeMap<H256, H256> {
+	indices
+		.iter()
+		.map(|index| (H256::from_low_u64_be(*index), H256::zero()))
+		.collect()
+}
+
+/// Backend holding exactly `accounts`, in the default block environment.
+pub fn backend<I>(accounts: I) -> OverlayedBackend<WitnessBackend<BlockEnv>>
+where
+	I: IntoIterator<Item = (H160, WitnessAccount)>,
+{
+	backend_with_env(BlockEnv::default(), accounts)
+}
+
+/// Backend holding exactly `accounts`, in the environment `env`.
+pub fn backend_with_env<E, I>(env: E, accounts: I) -> OverlayedBackend<WitnessBackend<E>>
+where
+	I: IntoIterator<Item = (H160, WitnessAccount)>,
+{
+	let witness = ExecutionWitness {
+		accounts: accounts.into_iter().collect(),
+		..Default::default()
+	};
+	OverlayedBackend::new(WitnessBackend::new(env, witness), Default::default())
+}
+
+/// Gasometer and runtime etables of the standard machine.
+pub fn etable<'config, H>() -> (Etable<'config, H>, Etable<'config, H>)
+where
+	H: RuntimeEnvironment + RuntimeBackend,
+{
+	(
+		Etable::single(standard::eval_gasometer::<State<'config>, H, CallCreateTrap>),
+		Etable::runtime(),
+	)
+}
+
+/// Execute `args` with the standard invoker, without precompiles.
+pub fn transact_with<H>(
+	config: &Config,
+	args: TransactArgs,
+	backend: &mut H,
+) -> Result<TransactValue, ExitError>
+where
+	H: RuntimeEnvironment + RuntimeBackend + TransactionalBackend,
+{
+	let etable = etable();
+	let resolver = EtableResolver::new(config, &(), &etable);
+	let invoker = Invoker::new(config, &resolver);
+	transact(args, None, backend, &invoker)
+}
+
+/// System call transaction without value or data.
+pub fn call(caller: H160, address: H160) -> TransactArgs {
+	TransactArgs::Call {
+		caller,
+		address,
+		value: U256::zero(),
+		data: Vec::new(),
+		gas_limit: U256::from(GAS_LIMIT),
+		gas_price: U256::zero(),
+		access_list: Vec::new(),
+		authorization_list: Vec::new(),
+		blob_hashes: Vec::new(),
+		kind: TransactKind::System,
+	}
+}
+
+/// System `CREATE2` transaction without value.
+pub fn create2(caller: H160, init_code: Vec<u8>, salt: H256) -> TransactArgs {
+	TransactArgs::Create {
+		caller,
+		value: U256::zero(),
+		init_code,
+		salt: Some(salt),
+		gas_limit: U256::from(GAS_LIMIT),
+		gas_price: U256::zero(),
+		access_list: Vec::new(),
+		kind: TransactKind::System,
+	}
+}
+
+/// Turn a system transaction into a normal one, paying fees and increasing
+/// the nonce of its caller.
+pub fn normal(mut args: TransactArgs) -> TransactArgs {
+	match &mut args {
+		TransactArgs::Call { kind, .. } | TransactArgs::Create { kind, .. } => {
+			*kind = TransactKind::Normal
+		}
+	}
+	args
+}
+
+/// Address created by `CREATE2`, which does not depend on the state.
+pub fn create2_address(caller: H160, salt: H256, init_code: &[u8]) -> H160 {
+	let scheme = CreateScheme::Create2 {
+		caller,
+		code_hash: H256::from_slice(Keccak256::digest(init_code).as_slice()),
+		salt,
+	};
+	scheme.address(&backend([]))
+}
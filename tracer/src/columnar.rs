@@ -0,0 +1,86 @@
+use evm::{
+	interpreter::{opcode::Opcode, runtime::GasState},
+	standard::Machine,
+};
+use primitive_types::{H256, U256};
+
+use crate::{
+	memory::{memory_accesses, MemoryAccessKind},
+	standard::EvalTracer,
+};
+
+/// Execution trace in a columnar layout, with one row per step, suitable for
+/// feeding zkEVM provers or constraint-debugging tools.
+///
+/// Memory accesses are kept in a separate table, whose rows refer to the step
+/// they belong to.
+#[derive(Clone, Debug, Default)]
+pub struct ColumnarTrace {
+	/// Number of stack items recorded from the top for each step.
+	pub stack_width: usize,
+	/// Program counter of each step.
+	pub pc: Vec<usize>,
+	/// Opcode of each step.
+	pub opcode: Vec<u8>,
+	/// Gas left before each step.
+	pub gas: Vec<U256>,
+	/// Stack length before each step.
+	pub stack_len: Vec<usize>,
+	/// Top stack items before each step, `stack_width` items per step starting
+	/// from the top, padded with zeros.
+	pub stack_top: Vec<H256>,
+	/// Memory size before each step.
+	pub memory_size: Vec<usize>,
+	/// Step of each memory access.
+	pub memory_step: Vec<usize>,
+	/// Whether each memory access is a write.
+	pub memory_write: Vec<bool>,
+	/// Offset of each memory access.
+	pub memory_offset: Vec<U256>,
+	/// Length of each memory access.
+	pub memory_len: Vec<U256>,
+}
+
+impl ColumnarTrace {
+	/// Create an empty trace recording the given number of stack items.
+	pub fn new(stack_width: usize) -> Self {
+		Self {
+			stack_width,
+			..Default::default()
+		}
+	}
+
+	/// Number of steps recorded.
+	pub fn len(&self) -> usize {
+		self.pc.len()
+	}
+
+	/// Whether no step has been recorded.
+	pub fn is_empty(&self) -> bool {
+		self.pc.is_empty()
+	}
+}
+
+impl<H> EvalTracer<H> for ColumnarTrace {
+	fn on_eval(&mut self, machine: &Machine, _handle: &H, opcode: Opcode, position: usize) {
+		let step = self.len();
+
+		self.pc.push(position);
+		self.opcode.push(opcode.0);
+		self.gas.push(machine.state.gas());
+		self.stack_len.push(machine.stack.len());
+		for i in 0..self.stack_width {
+			self.stack_top
+				.push(machine.stack.peek(i).unwrap_or_default());
+		}
+		self.memory_size.push(machine.memory.len());
+
+		for access in memory_accesses(opcode, &machine.stack) {
+			self.memory_step.push(step);
+			self.memory_write
+				.push(access.kind == MemoryAccessKind::Write);
+			self.memory_offset.push(access.offset);
+			self.memory_len.push(access.len);
+		}
+	}
+}
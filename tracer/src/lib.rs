@@ -1,7 +1,48 @@
 pub mod standard;
 
+mod columnar;
+mod memory;
+mod moonbeam;
+mod nonce;
+mod stats;
+mod storage;
+#[cfg(test)]
+mod testing;
+
+#[cfg(feature = "spans")]
+pub mod spans;
+#[cfg(feature = "timing")]
+pub mod timing;
+
 use evm::interpreter::{machine::Machine, opcode::Opcode};
 
+pub use crate::{
+	columnar::ColumnarTrace,
+	memory::{memory_accesses, MemoryAccess, MemoryAccessKind, MemoryEvent, MemoryTracer},
+	moonbeam::{Event, EventTracer, EvmEvent, RuntimeEvent},
+	nonce::{NonceChange, NonceChangeRecorder},
+	stats::ExecutionStats,
+	storage::{StorageChange, StorageChangeRecorder},
+};
+
 pub trait EvalTracer<S, H> {
 	fn on_eval(&mut self, machine: &Machine<S>, handle: &H, opcode: Opcode, position: usize);
 }
+
+// Tracers are moved along with the executions they observe, which may run on
+// any thread.
+const _: fn() = || {
+	fn assert_send_sync<T: Send + Sync>() {}
+	assert_send_sync::<ColumnarTrace>();
+	assert_send_sync::<EventTracer>();
+	assert_send_sync::<ExecutionStats>();
+	assert_send_sync::<MemoryTracer>();
+	assert_send_sync::<NonceChangeRecorder<()>>();
+	assert_send_sync::<StorageChangeRecorder<()>>();
+	#[cfg(feature = "timing")]
+	{
+		fn assert_send<T: Send>() {}
+		assert_send_sync::<timing::OpcodeTimer>();
+		assert_send::<timing::TimedPrecompileSet<()>>();
+	}
+};
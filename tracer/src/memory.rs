@@ -0,0 +1,189 @@
+use evm::{
+	interpreter::{machine::Stack, opcode::Opcode, utils::h256_to_u256},
+	standard::Machine,
+};
+use primitive_types::U256;
+
+use crate::standard::EvalTracer;
+
+/// Kind of a memory access.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MemoryAccessKind {
+	/// Memory is read.
+	Read,
+	/// Memory is written.
+	Write,
+}
+
+/// A memory access about to be performed by an opcode.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MemoryAccess {
+	/// Kind of the access.
+	pub kind: MemoryAccessKind,
+	/// Memory offset.
+	pub offset: U256,
+	/// Length of the access.
+	pub len: U256,
+}
+
+/// Memory accesses that the given opcode is about to perform, derived from
+/// the stack before its execution. Zero-length accesses are omitted, and
+/// accesses are empty if the stack does not have enough items.
+pub fn memory_accesses(opcode: Opcode, stack: &Stack) -> Vec<MemoryAccess> {
+	use MemoryAccessKind::{Read, Write};
+
+	let fixed = |kind, offset: usize, len: u64| -> Option<(MemoryAccessKind, U256, U256)> {
+		Some((
+			kind,
+			h256_to_u256(stack.peek(offset).ok()?),
+			U256::from(len),
+		))
+	};
+	let ranged = |kind, offset: usize, len: usize| -> Option<(MemoryAccessKind, U256, U256)> {
+		Some((
+			kind,
+			h256_to_u256(stack.peek(offset).ok()?),
+			h256_to_u256(stack.peek(len).ok()?),
+		))
+	};
+
+	let accesses = match opcode {
+		Opcode::MLOAD => vec![fixed(Read, 0, 32)],
+		Opcode::MSTORE => vec![fixed(Write, 0, 32)],
+		Opcode::MSTORE8 => vec![fixed(Write, 0, 1)],
+		Opcode::CALLDATACOPY | Opcode::CODECOPY | Opcode::RETURNDATACOPY => {
+			vec![ranged(Write, 0, 2)]
+		}
+		Opcode::EXTCODECOPY => vec![ranged(Write, 1, 3)],
+		Opcode::MCOPY => vec![ranged(Read, 1, 2), ranged(Write, 0, 2)],
+		Opcode::SHA3
+		| Opcode::RETURN
+		| Opcode::REVERT
+		| Opcode::LOG0
+		| Opcode::LOG1
+		| Opcode::LOG2
+		| Opcode::LOG3
+		| Opcode::LOG4 => vec![ranged(Read, 0, 1)],
+		Opcode::CREATE | Opcode::CREATE2 => vec![ranged(Read, 1, 2)],
+		Opcode::CALL | Opcode::CALLCODE => vec![ranged(Read, 3, 4), ranged(Write, 5, 6)],
+		Opcode::DELEGATECALL | Opcode::STATICCALL => {
+			vec![ranged(Read, 2, 3), ranged(Write, 4, 5)]
+		}
+		_ => Vec::new(),
+	};
+
+	accesses
+		.into_iter()
+		.flatten()
+		.filter(|(_, _, len)| !len.is_zero())
+		.map(|(kind, offset, len)| MemoryAccess { kind, offset, len })
+		.collect()
+}
+
+/// A memory access of an opcode, recorded by a [MemoryTracer].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MemoryEvent {
+	/// Opcode performing the access.
+	pub opcode: Opcode,
+	/// Program counter of the opcode.
+	pub position: usize,
+	/// Call depth of the frame. The top-level frame is at depth 0.
+	pub depth: u32,
+	/// The access.
+	pub access: MemoryAccess,
+}
+
+/// Eval tracer recording the memory reads and writes of each opcode, without
+/// snapshotting the memory, for memory-dependency analysis.
+///
+/// Like [memory_accesses], accesses are derived from the stack before the
+/// opcode is evaluated, so they are recorded even if the opcode then fails.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryTracer {
+	/// Events collected so far, in execution order.
+	pub events: Vec<MemoryEvent>,
+}
+
+impl MemoryTracer {
+	/// Create a new tracer.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Take the collected events.
+	pub fn take_events(&mut self) -> Vec<MemoryEvent> {
+		core::mem::take(&mut self.events)
+	}
+}
+
+impl<H> EvalTracer<H> for MemoryTracer {
+	fn on_eval(&mut self, machine: &Machine, _handle: &H, opcode: Opcode, position: usize) {
+		let depth = machine.state.gasometer.depth();
+		self.events.extend(
+			memory_accesses(opcode, &machine.stack)
+				.into_iter()
+				.map(|access| MemoryEvent {
+					opcode,
+					position,
+					depth,
+					access,
+				}),
+		);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use evm::standard::Config;
+	use primitive_types::H160;
+
+	use super::*;
+	use crate::testing;
+
+	#[test]
+	fn memory_events_follow_execution_order() {
+		let caller = H160::repeat_byte(0x01);
+		let contract = H160::repeat_byte(0x02);
+		let mut backend = testing::backend([
+			(caller, testing::account("")),
+			// PUSH1 1 PUSH1 0 MSTORE PUSH1 32 PUSH1 0 PUSH1 32 MCOPY
+			// PUSH1 32 PUSH1 64 PUSH1 32 PUSH1 0 PUSH1 0 PUSH1 0x03 GAS CALL STOP
+			(
+				contract,
+				testing::account("60016000526020600060205e6020604060206000600060035af100"),
+			),
+			// PUSH1 0 MLOAD STOP
+			(H160::from_low_u64_be(0x03), testing::account("60005100")),
+		]);
+
+		let mut tracer = MemoryTracer::new();
+		let config = Config::cancun();
+		let args = testing::call(caller, contract);
+		testing::transact_traced(&config, &mut tracer, args, &mut backend).unwrap();
+
+		let event = |opcode, depth, kind, offset: u64| {
+			(opcode, depth, kind, U256::from(offset), U256::from(32))
+		};
+		assert_eq!(
+			tracer
+				.take_events()
+				.into_iter()
+				.map(|event| (
+					event.opcode,
+					event.depth,
+					event.access.kind,
+					event.access.offset,
+					event.access.len
+				))
+				.collect::<Vec<_>>(),
+			vec![
+				event(Opcode::MSTORE, 0, MemoryAccessKind::Write, 0),
+				event(Opcode::MCOPY, 0, MemoryAccessKind::Read, 0),
+				event(Opcode::MCOPY, 0, MemoryAccessKind::Write, 32),
+				event(Opcode::CALL, 0, MemoryAccessKind::Read, 0),
+				event(Opcode::CALL, 0, MemoryAccessKind::Write, 64),
+				event(Opcode::MLOAD, 1, MemoryAccessKind::Read, 0),
+			]
+		);
+	}
+}
@@ -0,0 +1,288 @@
+//! Events following the layout of the Moonbeam/Frontier `evm-tracing` event
+//! schema, so that runtimes consuming those events can be fed from this
+//! interpreter.
+//!
+//! Events are derived from the machine before each opcode is evaluated. Exit
+//! events of the schema are not observable from an eval tracer and are not
+//! emitted.
+
+use evm::{
+	interpreter::{
+		error::CreateScheme,
+		opcode::Opcode,
+		runtime::{Context, RuntimeBackend, Transfer},
+		utils::h256_to_u256,
+	},
+	standard::Machine,
+};
+use primitive_types::{H160, H256, U256};
+use sha3::{Digest, Keccak256};
+
+use crate::standard::EvalTracer;
+
+/// Largest memory range read into an event. Ranges this large cannot be paid
+/// for, so the opcode fails anyway.
+const MAX_MEMORY_READ: usize = 1 << 20;
+
+/// Call frame event.
+#[derive(Clone, Debug)]
+pub enum EvmEvent {
+	/// A `CALL`, `CALLCODE`, `DELEGATECALL` or `STATICCALL` is about to run.
+	Call {
+		/// Address whose code is executed.
+		code_address: H160,
+		/// Value transfer of the call.
+		transfer: Option<Transfer>,
+		/// Call input.
+		input: Vec<u8>,
+		/// Gas requested by the caller.
+		target_gas: Option<u64>,
+		/// Whether the call is static.
+		is_static: bool,
+		/// Context of the callee.
+		context: Context,
+	},
+	/// A `CREATE` or `CREATE2` is about to run.
+	Create {
+		/// Creator.
+		caller: H160,
+		/// Address of the created contract.
+		address: H160,
+		/// Create scheme.
+		scheme: CreateScheme,
+		/// Endowment.
+		value: U256,
+		/// Init code.
+		init_code: Vec<u8>,
+		/// Gas requested by the caller, which is always the maximum for
+		/// creates.
+		target_gas: Option<u64>,
+	},
+	/// A `SELFDESTRUCT` is about to run.
+	Suicide {
+		/// Destructed account.
+		address: H160,
+		/// Beneficiary.
+		target: H160,
+		/// Balance transferred to the beneficiary.
+		balance: U256,
+	},
+}
+
+/// Opcode-level event.
+#[derive(Clone, Debug)]
+pub enum RuntimeEvent {
+	/// Snapshot before an opcode is evaluated.
+	Step {
+		/// Context of the frame.
+		context: Context,
+		/// Opcode.
+		opcode: Opcode,
+		/// Program counter.
+		position: usize,
+		/// Stack, from the bottom, if recorded.
+		stack: Option<Vec<H256>>,
+		/// Memory, if recorded.
+		memory: Option<Vec<u8>>,
+	},
+	/// An `SLOAD` is about to run.
+	SLoad {
+		/// Address of the account.
+		address: H160,
+		/// Storage key.
+		index: H256,
+		/// Value read.
+		value: H256,
+	},
+	/// An `SSTORE` is about to run.
+	SStore {
+		/// Address of the account.
+		address: H160,
+		/// Storage key.
+		index: H256,
+		/// Value written.
+		value: H256,
+	},
+}
+
+/// Tracing event.
+#[derive(Clone, Debug)]
+pub enum Event {
+	/// Call frame event.
+	Evm(EvmEvent),
+	/// Opcode-level event.
+	Runtime(RuntimeEvent),
+}
+
+/// Eval tracer collecting [Event]s.
+#[derive(Clone, Debug, Default)]
+pub struct EventTracer {
+	/// Whether to record the stack in step snapshots.
+	pub record_stack: bool,
+	/// Whether to record the memory in step snapshots.
+	pub record_memory: bool,
+	/// Events collected so far, in execution order.
+	pub events: Vec<Event>,
+}
+
+impl EventTracer {
+	/// Create a new tracer. Step snapshots are always emitted, with the stack
+	/// and memory only if requested.
+	pub fn new(record_stack: bool, record_memory: bool) -> Self {
+		Self {
+			record_stack,
+			record_memory,
+			events: Vec::new(),
+		}
+	}
+
+	/// Take the collected events.
+	pub fn take_events(&mut self) -> Vec<Event> {
+		core::mem::take(&mut self.events)
+	}
+}
+
+impl<H: RuntimeBackend> EvalTracer<H> for EventTracer {
+	fn on_eval(&mut self, machine: &Machine, handle: &H, opcode: Opcode, position: usize) {
+		let context = &machine.state.runtime.context;
+
+		self.events.push(Event::Runtime(RuntimeEvent::Step {
+			context: context.clone(),
+			opcode,
+			position,
+			stack: self.record_stack.then(|| machine.stack.data().clone()),
+			memory: self
+				.record_memory
+				.then(|| machine.memory.get(0, machine.memory.len())),
+		}));
+
+		if let Some(event) = frame_event(machine, handle, opcode) {
+			self.events.push(event);
+		}
+	}
+}
+
+fn frame_event<H: RuntimeBackend>(machine: &Machine, handle: &H, opcode: Opcode) -> Option<Event> {
+	let stack = &machine.stack;
+	let context = &machine.state.runtime.context;
+	let is_static = machine.state.gasometer.is_static;
+	let word = |n: usize| stack.peek(n).ok();
+	let number = |n: usize| word(n).map(h256_to_u256);
+	let address = |n: usize| word(n).map(H160::from);
+
+	let event = match opcode {
+		Opcode::SLOAD => {
+			let index = word(0)?;
+			Event::Runtime(RuntimeEvent::SLoad {
+				address: context.address,
+				index,
+				value: handle.storage(context.address, index),
+			})
+		}
+		Opcode::SSTORE => Event::Runtime(RuntimeEvent::SStore {
+			address: context.address,
+			index: word(0)?,
+			value: word(1)?,
+		}),
+		Opcode::CALL | Opcode::CALLCODE | Opcode::DELEGATECALL | Opcode::STATICCALL => {
+			let gas = number(0)?;
+			let target = address(1)?;
+			let has_value = matches!(opcode, Opcode::CALL | Opcode::CALLCODE);
+			let value = if has_value { number(2)? } else { U256::zero() };
+			let input_start = if has_value { 3 } else { 2 };
+			let input = read_memory(machine, number(input_start)?, number(input_start + 1)?)?;
+
+			let (transfer, context) = match opcode {
+				Opcode::CALL => (
+					Some(Transfer {
+						source: context.address,
+						target,
+						value,
+					}),
+					Context {
+						address: target,
+						caller: context.address,
+						apparent_value: value,
+						authorized: None,
+					},
+				),
+				Opcode::CALLCODE => (
+					Some(Transfer {
+						source: context.address,
+						target: context.address,
+						value,
+					}),
+					Context {
+						address: context.address,
+						caller: context.address,
+						apparent_value: value,
+						authorized: None,
+					},
+				),
+				Opcode::DELEGATECALL => (None, context.clone()),
+				_ => (
+					None,
+					Context {
+						address: target,
+						caller: context.address,
+						apparent_value: U256::zero(),
+						authorized: None,
+					},
+				),
+			};
+
+			Event::Evm(EvmEvent::Call {
+				code_address: target,
+				transfer,
+				input,
+				target_gas: Some(gas.min(U256::from(u64::MAX)).as_u64()),
+				is_static: is_static || opcode == Opcode::STATICCALL,
+				context,
+			})
+		}
+		Opcode::CREATE | Opcode::CREATE2 => {
+			let value = number(0)?;
+			let init_code = read_memory(machine, number(1)?, number(2)?)?;
+			let scheme = if opcode == Opcode::CREATE2 {
+				CreateScheme::Create2 {
+					caller: context.address,
+					code_hash: H256::from_slice(Keccak256::digest(&init_code).as_slice()),
+					salt: word(3)?,
+				}
+			} else {
+				CreateScheme::Legacy {
+					caller: context.address,
+				}
+			};
+
+			Event::Evm(EvmEvent::Create {
+				caller: context.address,
+				address: scheme.address(handle),
+				scheme,
+				value,
+				init_code,
+				target_gas: None,
+			})
+		}
+		Opcode::SUICIDE => Event::Evm(EvmEvent::Suicide {
+			address: context.address,
+			target: address(0)?,
+			balance: handle.balance(context.address),
+		}),
+		_ => return None,
+	};
+
+	Some(event)
+}
+
+fn read_memory(machine: &Machine, offset: U256, len: U256) -> Option<Vec<u8>> {
+	if len.is_zero() {
+		return Some(Vec::new());
+	}
+
+	if offset > U256::from(usize::MAX) || len > U256::from(MAX_MEMORY_READ) {
+		return None;
+	}
+
+	Some(machine.memory.get(offset.as_usize(), len.as_usize()))
+}
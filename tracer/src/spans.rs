@@ -0,0 +1,271 @@
+//! Spans for transactions and call frames, for correlating execution latency
+//! with the rest of a node's request tracing.
+//!
+//! Wrap the invoker with [SpanInvoker] and implement [SpanSink] on top of the
+//! tracing library of the node. An OpenTelemetry sink starts a span in
+//! [SpanSink::start] and sets the attributes of [SpanStart] and [SpanEnd]
+//! on it, named after the `ATTRIBUTE_*` constants.
+
+use core::cell::RefCell;
+
+use evm::{
+	interpreter::{
+		error::{Capture, ExitError, ExitResult},
+		runtime::GasState,
+		Interpreter,
+	},
+	Invoker, InvokerControl,
+};
+use primitive_types::U256;
+
+/// Attribute name of the call depth.
+pub const ATTRIBUTE_DEPTH: &str = "evm.depth";
+/// Attribute name of the gas limit.
+pub const ATTRIBUTE_GAS_LIMIT: &str = "evm.gas_limit";
+/// Attribute name of the gas used.
+pub const ATTRIBUTE_GAS_USED: &str = "evm.gas_used";
+/// Attribute name of the outcome, one of `succeed`, `revert`, `error` and
+/// `fatal`.
+pub const ATTRIBUTE_OUTCOME: &str = "evm.outcome";
+
+/// Kind of span.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SpanKind {
+	/// Whole transaction.
+	Transaction,
+	/// Call frame, including the top-level one.
+	Frame,
+}
+
+/// Attributes known when a span starts.
+#[derive(Clone, Debug)]
+pub struct SpanStart {
+	/// Kind of span.
+	pub kind: SpanKind,
+	/// Call depth. The top-level frame and the transaction are at depth 0.
+	pub depth: usize,
+	/// Gas given to the frame. Unknown for transactions, whose gas is only
+	/// known once the top-level frame is created.
+	pub gas_limit: Option<U256>,
+}
+
+/// Attributes known when a span ends.
+#[derive(Clone, Debug)]
+pub struct SpanEnd {
+	/// Gas used by the frame. Unknown for transactions.
+	pub gas_used: Option<U256>,
+	/// Value of the [ATTRIBUTE_OUTCOME] attribute.
+	pub outcome: &'static str,
+}
+
+/// Receiver of spans.
+pub trait SpanSink {
+	/// Handle of a started span.
+	type Span;
+
+	/// Start a span.
+	fn start(&self, start: SpanStart) -> Self::Span;
+	/// End a span.
+	fn end(&self, span: Self::Span, end: SpanEnd);
+}
+
+/// Invoker wrapper starting a span for each transaction and call frame.
+pub struct SpanInvoker<I, S> {
+	invoker: I,
+	sink: S,
+}
+
+impl<I, S> SpanInvoker<I, S> {
+	/// Wrap an invoker.
+	pub fn new(invoker: I, sink: S) -> Self {
+		Self { invoker, sink }
+	}
+
+	/// Deconstruct into the inner invoker and the sink.
+	pub fn deconstruct(self) -> (I, S) {
+		(self.invoker, self.sink)
+	}
+}
+
+/// Invoke of a transaction, with its spans.
+pub struct SpanTransactInvoke<T, P> {
+	invoke: T,
+	transaction: RefCell<Option<P>>,
+	frame: RefCell<Option<(P, U256)>>,
+}
+
+fn outcome(result: &ExitResult) -> &'static str {
+	match result {
+		Ok(_) => "succeed",
+		Err(ExitError::Reverted) => "revert",
+		Err(ExitError::Exception(_)) => "error",
+		Err(ExitError::Fatal(_)) => "fatal",
+	}
+}
+
+impl<I, S: SpanSink> SpanInvoker<I, S> {
+	fn start_frame<St: GasState, V>(
+		&self,
+		depth: usize,
+		control: &InvokerControl<V, (ExitResult, (St, Vec<u8>))>,
+	) -> (S::Span, U256)
+	where
+		V: Interpreter<State = St>,
+	{
+		let gas_limit = match control {
+			InvokerControl::Enter(interpreter) => interpreter.machine().state.gas(),
+			InvokerControl::DirectExit((_, (state, _))) => state.gas(),
+		};
+		let span = self.sink.start(SpanStart {
+			kind: SpanKind::Frame,
+			depth,
+			gas_limit: Some(gas_limit),
+		});
+		(span, gas_limit)
+	}
+
+	fn end_frame<St: GasState>(
+		&self,
+		span: S::Span,
+		gas_limit: U256,
+		result: &ExitResult,
+		state: &St,
+	) {
+		self.sink.end(
+			span,
+			SpanEnd {
+				gas_used: Some(gas_limit.saturating_sub(state.gas())),
+				outcome: outcome(result),
+			},
+		);
+	}
+}
+
+impl<H, Tr, I, S> Invoker<H, Tr> for SpanInvoker<I, S>
+where
+	I: Invoker<H, Tr>,
+	I::State: GasState,
+	S: SpanSink,
+{
+	type State = I::State;
+	type Interpreter = I::Interpreter;
+	type Interrupt = I::Interrupt;
+	type TransactArgs = I::TransactArgs;
+	type TransactInvoke = SpanTransactInvoke<I::TransactInvoke, S::Span>;
+	type TransactValue = I::TransactValue;
+	type SubstackInvoke = (I::SubstackInvoke, S::Span, U256);
+
+	fn new_transact(
+		&self,
+		args: Self::TransactArgs,
+		handler: &mut H,
+	) -> Result<
+		(
+			Self::TransactInvoke,
+			InvokerControl<Self::Interpreter, (ExitResult, (Self::State, Vec<u8>))>,
+		),
+		ExitError,
+	> {
+		let transaction = self.sink.start(SpanStart {
+			kind: SpanKind::Transaction,
+			depth: 0,
+			gas_limit: None,
+		});
+
+		match self.invoker.new_transact(args, handler) {
+			Ok((invoke, control)) => {
+				let frame = self.start_frame(0, &control);
+				Ok((
+					SpanTransactInvoke {
+						invoke,
+						transaction: RefCell::new(Some(transaction)),
+						frame: RefCell::new(Some(frame)),
+					},
+					control,
+				))
+			}
+			Err(err) => {
+				self.sink.end(
+					transaction,
+					SpanEnd {
+						gas_used: None,
+						outcome: outcome(&Err(err.clone())),
+					},
+				);
+				Err(err)
+			}
+		}
+	}
+
+	fn finalize_transact(
+		&self,
+		invoke: &Self::TransactInvoke,
+		exit: ExitResult,
+		machine: (Self::State, Vec<u8>),
+		handler: &mut H,
+	) -> Result<Self::TransactValue, ExitError> {
+		if let Some((span, gas_limit)) = invoke.frame.borrow_mut().take() {
+			self.end_frame(span, gas_limit, &exit, &machine.0);
+		}
+
+		let ret = self
+			.invoker
+			.finalize_transact(&invoke.invoke, exit.clone(), machine, handler);
+
+		if let Some(span) = invoke.transaction.borrow_mut().take() {
+			let result = match &ret {
+				Ok(_) => exit,
+				Err(err) => Err(err.clone()),
+			};
+			self.sink.end(
+				span,
+				SpanEnd {
+					gas_used: None,
+					outcome: outcome(&result),
+				},
+			);
+		}
+
+		ret
+	}
+
+	fn enter_substack(
+		&self,
+		trap: Tr,
+		machine: &mut Self::Interpreter,
+		handler: &mut H,
+		depth: usize,
+	) -> Capture<
+		Result<
+			(
+				Self::SubstackInvoke,
+				InvokerControl<Self::Interpreter, (ExitResult, (Self::State, Vec<u8>))>,
+			),
+			ExitError,
+		>,
+		Self::Interrupt,
+	> {
+		match self.invoker.enter_substack(trap, machine, handler, depth) {
+			Capture::Exit(Ok((invoke, control))) => {
+				let (span, gas_limit) = self.start_frame(depth, &control);
+				Capture::Exit(Ok(((invoke, span, gas_limit), control)))
+			}
+			Capture::Exit(Err(err)) => Capture::Exit(Err(err)),
+			Capture::Trap(interrupt) => Capture::Trap(interrupt),
+		}
+	}
+
+	fn exit_substack(
+		&self,
+		result: ExitResult,
+		child: (Self::State, Vec<u8>),
+		trap_data: Self::SubstackInvoke,
+		parent: &mut Self::Interpreter,
+		handler: &mut H,
+	) -> Result<(), ExitError> {
+		let (invoke, span, gas_limit) = trap_data;
+		self.end_frame(span, gas_limit, &result, &child.0);
+		self.invoker
+			.exit_substack(result, child, invoke, parent, handler)
+	}
+}
@@ -0,0 +1,109 @@
+use evm::{interpreter::opcode::Opcode, standard::Machine};
+use primitive_types::U256;
+
+use crate::standard::EvalTracer;
+
+/// Execution statistics of a transaction, for capacity planning and anomaly
+/// detection.
+///
+/// Statistics are collected before each opcode is evaluated, so opcodes
+/// failing afterwards, such as for lack of gas, are still counted.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ExecutionStats {
+	/// Number of evaluated opcodes.
+	pub steps: u64,
+	/// Maximum call depth reached. The top-level frame is at depth 0.
+	pub max_depth: u32,
+	/// Largest memory size of a frame, in bytes.
+	pub memory_high_water: usize,
+	/// Number of `CALL`, `CALLCODE`, `DELEGATECALL` and `STATICCALL`.
+	pub calls: u64,
+	/// Number of `CREATE` and `CREATE2`.
+	pub creates: u64,
+	/// Number of `SLOAD`.
+	pub sloads: u64,
+	/// Number of `SSTORE`.
+	pub sstores: u64,
+	/// Largest length of data returned by `RETURN` or `REVERT`.
+	pub max_return_data_len: U256,
+	/// Total length of data returned by `RETURN` and `REVERT`.
+	pub total_return_data_len: U256,
+}
+
+impl<H> EvalTracer<H> for ExecutionStats {
+	fn on_eval(&mut self, machine: &Machine, _handle: &H, opcode: Opcode, _position: usize) {
+		self.steps += 1;
+		self.max_depth = self.max_depth.max(machine.state.gasometer.depth());
+
+		let memory_len = machine.memory.effective_len();
+		let memory_len = if memory_len > U256::from(usize::MAX) {
+			usize::MAX
+		} else {
+			memory_len.as_usize()
+		};
+		self.memory_high_water = self.memory_high_water.max(memory_len);
+
+		match opcode {
+			Opcode::CALL | Opcode::CALLCODE | Opcode::DELEGATECALL | Opcode::STATICCALL => {
+				self.calls += 1
+			}
+			Opcode::CREATE | Opcode::CREATE2 => self.creates += 1,
+			Opcode::SLOAD => self.sloads += 1,
+			Opcode::SSTORE => self.sstores += 1,
+			Opcode::RETURN | Opcode::REVERT => {
+				if let Ok(len) = machine.stack.peek(1) {
+					let len = U256::from_big_endian(&len[..]);
+					self.max_return_data_len = self.max_return_data_len.max(len);
+					self.total_return_data_len = self.total_return_data_len.saturating_add(len);
+				}
+			}
+			_ => (),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use evm::standard::Config;
+	use primitive_types::H160;
+
+	use super::*;
+	use crate::testing;
+
+	#[test]
+	fn stats_cover_subcalls() {
+		let caller = H160::repeat_byte(0x01);
+		let contract = H160::repeat_byte(0x02);
+		let mut backend = testing::backend([
+			(caller, testing::account("")),
+			// PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0x03 GAS CALL STOP
+			(contract, testing::account("6000600060006000600060035af100")),
+			// PUSH1 0 SLOAD PUSH1 64 PUSH1 0 RETURN
+			(
+				H160::from_low_u64_be(0x03),
+				testing::account("60005460406000f3"),
+			),
+		]);
+
+		let mut stats = ExecutionStats::default();
+		let config = Config::cancun();
+		let args = testing::call(caller, contract);
+		testing::transact_traced(&config, &mut stats, args, &mut backend).unwrap();
+
+		assert_eq!(
+			stats,
+			ExecutionStats {
+				steps: 14,
+				max_depth: 1,
+				// Memory is only expanded by the final `RETURN`, after the
+				// statistics of the opcode are collected.
+				memory_high_water: 0,
+				calls: 1,
+				sloads: 1,
+				max_return_data_len: U256::from(64),
+				total_return_data_len: U256::from(64),
+				..Default::default()
+			}
+		);
+	}
+}
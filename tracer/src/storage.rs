@@ -0,0 +1,315 @@
+use evm::{
+	backend::TransactionalBackend,
+	interpreter::{
+		error::ExitError,
+		runtime::{
+			Basic, BlockEnv, Log, RuntimeBackend, RuntimeBaseBackend, RuntimeBlockHashes,
+			RuntimeEnvironment, SelfDestruct, SetCodeOrigin, Transfer,
+		},
+	},
+	MergeStrategy,
+};
+use primitive_types::{H160, H256, U256};
+
+/// A storage change event emitted by `SSTORE`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StorageChange {
+	/// Address of the account.
+	pub address: H160,
+	/// Storage key.
+	pub index: H256,
+	/// Value before the change.
+	pub old: H256,
+	/// Value after the change.
+	pub new: H256,
+	/// Whether the change was later reverted, by either the frame it happened
+	/// in or one of its parents.
+	pub reverted: bool,
+}
+
+/// Backend wrapper recording all storage changes, for storage-level indexers.
+pub struct StorageChangeRecorder<B> {
+	backend: B,
+	changes: Vec<StorageChange>,
+	substates: Vec<usize>,
+}
+
+impl<B> StorageChangeRecorder<B> {
+	/// Wrap a backend.
+	pub fn new(backend: B) -> Self {
+		Self {
+			backend,
+			changes: Vec::new(),
+			substates: Vec::new(),
+		}
+	}
+
+	/// Storage changes recorded so far, in execution order.
+	pub fn changes(&self) -> &[StorageChange] {
+		&self.changes
+	}
+
+	/// Take the recorded storage changes.
+	pub fn take_changes(&mut self) -> Vec<StorageChange> {
+		core::mem::take(&mut self.changes)
+	}
+
+	/// Inner backend.
+	pub fn inner(&self) -> &B {
+		&self.backend
+	}
+
+	/// Mutable inner backend.
+	pub fn inner_mut(&mut self) -> &mut B {
+		&mut self.backend
+	}
+
+	/// Deconstruct into the inner backend and the recorded storage changes.
+	pub fn deconstruct(self) -> (B, Vec<StorageChange>) {
+		(self.backend, self.changes)
+	}
+}
+
+impl<B: RuntimeBlockHashes> RuntimeBlockHashes for StorageChangeRecorder<B> {
+	fn block_hash(&self, number: U256) -> H256 {
+		self.backend.block_hash(number)
+	}
+}
+
+impl<B: RuntimeEnvironment> RuntimeEnvironment for StorageChangeRecorder<B> {
+	fn block_env(&self) -> BlockEnv {
+		self.backend.block_env()
+	}
+
+	fn blob_hash(&self, index: U256) -> H256 {
+		self.backend.blob_hash(index)
+	}
+
+	fn recover_signer(&self, message: H256, y_parity: bool, r: H256, s: H256) -> Option<H160> {
+		self.backend.recover_signer(message, y_parity, r, s)
+	}
+}
+
+impl<B: RuntimeBaseBackend> RuntimeBaseBackend for StorageChangeRecorder<B> {
+	fn balance(&self, address: H160) -> U256 {
+		self.backend.balance(address)
+	}
+
+	fn code_size(&self, address: H160) -> U256 {
+		self.backend.code_size(address)
+	}
+
+	fn code_hash(&self, address: H160) -> H256 {
+		self.backend.code_hash(address)
+	}
+
+	fn code(&self, address: H160) -> Vec<u8> {
+		self.backend.code(address)
+	}
+
+	fn code_slice(&self, address: H160, offset: usize, len: usize) -> Vec<u8> {
+		self.backend.code_slice(address, offset, len)
+	}
+
+	fn storage(&self, address: H160, index: H256) -> H256 {
+		self.backend.storage(address, index)
+	}
+
+	fn transient_storage(&self, address: H160, index: H256) -> H256 {
+		self.backend.transient_storage(address, index)
+	}
+
+	fn exists(&self, address: H160) -> bool {
+		self.backend.exists(address)
+	}
+
+	fn nonce(&self, address: H160) -> U256 {
+		self.backend.nonce(address)
+	}
+
+	fn basic(&self, address: H160) -> Basic {
+		self.backend.basic(address)
+	}
+}
+
+impl<B: RuntimeBackend> RuntimeBackend for StorageChangeRecorder<B> {
+	fn original_storage(&self, address: H160, index: H256) -> H256 {
+		self.backend.original_storage(address, index)
+	}
+
+	fn deleted(&self, address: H160) -> bool {
+		self.backend.deleted(address)
+	}
+
+	fn is_cold(&self, address: H160, index: Option<H256>) -> bool {
+		self.backend.is_cold(address, index)
+	}
+
+	fn is_hot(&self, address: H160, index: Option<H256>) -> bool {
+		self.backend.is_hot(address, index)
+	}
+
+	fn is_cold_batch(&self, accesses: &[(H160, Option<H256>)]) -> Vec<bool> {
+		self.backend.is_cold_batch(accesses)
+	}
+
+	fn mark_hot(&mut self, address: H160, index: Option<H256>) {
+		self.backend.mark_hot(address, index)
+	}
+
+	fn mark_hot_batch(&mut self, accesses: &[(H160, Option<H256>)]) {
+		self.backend.mark_hot_batch(accesses)
+	}
+
+	fn set_storage(&mut self, address: H160, index: H256, value: H256) -> Result<(), ExitError> {
+		let old = self.backend.storage(address, index);
+		self.backend.set_storage(address, index, value)?;
+		self.changes.push(StorageChange {
+			address,
+			index,
+			old,
+			new: value,
+			reverted: false,
+		});
+		Ok(())
+	}
+
+	fn set_transient_storage(
+		&mut self,
+		address: H160,
+		index: H256,
+		value: H256,
+	) -> Result<(), ExitError> {
+		self.backend.set_transient_storage(address, index, value)
+	}
+
+	fn log(&mut self, log: Log) -> Result<(), ExitError> {
+		self.backend.log(log)
+	}
+
+	fn mark_delete(&mut self, address: H160) {
+		self.backend.mark_delete(address)
+	}
+
+	fn record_selfdestruct(&mut self, selfdestruct: SelfDestruct) {
+		self.backend.record_selfdestruct(selfdestruct)
+	}
+
+	fn mark_deletable(&mut self, address: H160) {
+		self.backend.mark_deletable(address)
+	}
+
+	fn deletable(&self, address: H160) -> bool {
+		self.backend.deletable(address)
+	}
+
+	fn reset_storage(&mut self, address: H160) {
+		self.backend.reset_storage(address)
+	}
+
+	fn set_code(
+		&mut self,
+		address: H160,
+		code: Vec<u8>,
+		origin: SetCodeOrigin,
+	) -> Result<(), ExitError> {
+		self.backend.set_code(address, code, origin)
+	}
+
+	fn reset_balance(&mut self, address: H160) {
+		self.backend.reset_balance(address)
+	}
+
+	fn deposit(&mut self, target: H160, value: U256) {
+		self.backend.deposit(target, value)
+	}
+
+	fn withdrawal(&mut self, source: H160, value: U256) -> Result<(), ExitError> {
+		self.backend.withdrawal(source, value)
+	}
+
+	fn transfer(&mut self, transfer: Transfer) -> Result<(), ExitError> {
+		self.backend.transfer(transfer)
+	}
+
+	fn inc_nonce(&mut self, address: H160) -> Result<(), ExitError> {
+		self.backend.inc_nonce(address)
+	}
+}
+
+impl<B: TransactionalBackend> TransactionalBackend for StorageChangeRecorder<B> {
+	fn push_substate(&mut self) {
+		self.substates.push(self.changes.len());
+		self.backend.push_substate()
+	}
+
+	fn pop_substate(&mut self, strategy: MergeStrategy) {
+		let start = self.substates.pop().expect("uneven substate pop");
+		match strategy {
+			MergeStrategy::Commit => (),
+			MergeStrategy::Revert | MergeStrategy::Discard => {
+				for change in &mut self.changes[start..] {
+					change.reverted = true;
+				}
+			}
+		}
+		self.backend.pop_substate(strategy)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use evm::standard::Config;
+
+	use super::*;
+	use crate::testing;
+
+	const CALLER: H160 = H160::repeat_byte(0x01);
+	const CONTRACT: H160 = H160::repeat_byte(0x02);
+
+	fn changes(code: &str) -> Vec<StorageChange> {
+		let mut backend = StorageChangeRecorder::new(testing::backend([
+			(CALLER, testing::account("")),
+			(CONTRACT, testing::account(code)),
+			// PUSH1 2 PUSH1 0 SSTORE PUSH1 0 PUSH1 0 REVERT
+			(
+				H160::from_low_u64_be(0x03),
+				testing::account("600260005560006000fd"),
+			),
+		]));
+
+		let config = Config::cancun();
+		let args = testing::call(CALLER, CONTRACT);
+		let _ = testing::transact_with(&config, &testing::etable(), args, &mut backend);
+		backend.take_changes()
+	}
+
+	fn change(address: H160, new: u64, reverted: bool) -> StorageChange {
+		StorageChange {
+			address,
+			index: H256::zero(),
+			old: H256::zero(),
+			new: H256::from_low_u64_be(new),
+			reverted,
+		}
+	}
+
+	#[test]
+	fn reverted_frames_mark_their_changes() {
+		// PUSH1 1 PUSH1 0 SSTORE
+		// PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0x03 GAS CALL STOP
+		assert_eq!(
+			changes("60016000556000600060006000600060035af100"),
+			vec![
+				change(CONTRACT, 1, false),
+				change(H160::from_low_u64_be(0x03), 2, true)
+			]
+		);
+
+		// PUSH1 1 PUSH1 0 SSTORE PUSH1 0 PUSH1 0 REVERT
+		assert_eq!(
+			changes("600160005560006000fd"),
+			vec![change(CONTRACT, 1, true)]
+		);
+	}
+}
@@ -0,0 +1,131 @@
+//! Fixtures shared by the tests of the crate.
+
+use std::cell::RefCell;
+
+use evm::{
+	backend::{
+		ExecutionWitness, OverlayedBackend, TransactionalBackend, WitnessAccount, WitnessBackend,
+	},
+	interpreter::{
+		error::{CallCreateTrap, CreateScheme, ExitError},
+		etable::EtableSet,
+		runtime::{BlockEnv, RuntimeBackend, RuntimeEnvironment},
+	},
+	standard::{
+		self, Config, Etable, EtableResolver, Invoker, State, TransactArgs, TransactKind,
+		TransactValue,
+	},
+	transact,
+};
+use primitive_types::{H160, H256, U256};
+use sha3::{Digest, Keccak256};
+
+use crate::standard::EvalTracer;
+
+/// Gas limit of the transactions built here.
+pub const GAS_LIMIT: u64 = 1_000_000;
+
+/// Existing account with the given code and zero storage slot 0.
+pub fn account(code: &str) -> WitnessAccount {
+	WitnessAccount {
+		balance: Some(U256::zero()),
+		nonce: Some(U256::zero()),
+		code: Some(hex::decode(code).unwrap()),
+		exists: Some(true),
+		storage: [(H256::zero(), H256::zero())].into(),
+	}
+}
+
+/// Backend holding exactly `accounts`, in the default block environment.
+pub fn backend<I>(accounts: I) -> OverlayedBackend<WitnessBackend<BlockEnv>>
+where
+	I: IntoIterator<Item = (H160, WitnessAccount)>,
+{
+	let witness = ExecutionWitness {
+		accounts: accounts.into_iter().collect(),
+		..Default::default()
+	};
+	OverlayedBackend::new(
+		WitnessBackend::new(BlockEnv::default(), witness),
+		Default::default(),
+	)
+}
+
+/// Gasometer and runtime etables of the standard machine.
+pub fn etable<'config, H>() -> (Etable<'config, H>, Etable<'config, H>)
+where
+	H: RuntimeEnvironment + RuntimeBackend,
+{
+	(
+		Etable::single(standard::eval_gasometer::<State<'config>, H, CallCreateTrap>),
+		Etable::runtime(),
+	)
+}
+
+/// Execute `args` with the given etables, without precompiles.
+pub fn transact_with<'config, ES, H>(
+	config: &'config Config,
+	etable: &ES,
+	args: TransactArgs,
+	backend: &mut H,
+) -> Result<TransactValue, ExitError>
+where
+	ES: EtableSet<State = State<'config>, Handle = H, Trap = CallCreateTrap>,
+	H: RuntimeEnvironment + RuntimeBackend + TransactionalBackend,
+{
+	let resolver = EtableResolver::new(config, &(), etable);
+	let invoker = Invoker::new(config, &resolver);
+	transact(args, None, backend, &invoker)
+}
+
+/// Execute `args` with the standard etables, calling `tracer` before each
+/// opcode.
+pub fn transact_traced<T, H>(
+	config: &Config,
+	tracer: &mut T,
+	args: TransactArgs,
+	backend: &mut H,
+) -> Result<TransactValue, ExitError>
+where
+	T: EvalTracer<H>,
+	H: RuntimeEnvironment + RuntimeBackend + TransactionalBackend,
+{
+	let tracer = RefCell::new(tracer);
+	let (gasometer, runtime) = etable::<H>();
+	let gasometer = gasometer.wrap(|f, _| {
+		let tracer = &tracer;
+		move |machine: &mut standard::Machine, handle: &mut H, opcode, position| {
+			tracer
+				.borrow_mut()
+				.on_eval(machine, handle, opcode, position);
+			f(machine, handle, opcode, position)
+		}
+	});
+	transact_with(config, &(gasometer, runtime), args, backend)
+}
+
+/// Normal call transaction without value or data.
+pub fn call(caller: H160, address: H160) -> TransactArgs {
+	TransactArgs::Call {
+		caller,
+		address,
+		value: U256::zero(),
+		data: Vec::new(),
+		gas_limit: U256::from(GAS_LIMIT),
+		gas_price: U256::zero(),
+		access_list: Vec::new(),
+		authorization_list: Vec::new(),
+		blob_hashes: Vec::new(),
+		kind: TransactKind::Normal,
+	}
+}
+
+/// Address created by `CREATE2`.
+pub fn create2_address(caller: H160, salt: H256, init_code: &[u8]) -> H160 {
+	let scheme = CreateScheme::Create2 {
+		caller,
+		code_hash: H256::from_slice(Keccak256::digest(init_code).as_slice()),
+		salt,
+	};
+	scheme.address(&backend([]))
+}
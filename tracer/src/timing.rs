@@ -0,0 +1,209 @@
+//! Wall-clock timings of opcodes and precompiles, for validating that gas
+//! prices reflect the actual execution cost on a given machine.
+//!
+//! Opcodes are timed by [OpcodeTimer], an eval tracer attributing the time
+//! between two evaluations to the first opcode. The time of calls and creates
+//! therefore only covers entering the new frame, and the time of the last
+//! opcode of a frame covers exiting it. Precompiles are timed by wrapping the
+//! precompile set in [TimedPrecompileSet].
+
+use std::{
+	cell::RefCell,
+	collections::BTreeMap,
+	time::{Duration, Instant},
+};
+
+use evm::{
+	interpreter::{error::ExitResult, opcode::Opcode},
+	standard::{Machine, PrecompileSet},
+};
+use primitive_types::H160;
+
+use crate::standard::EvalTracer;
+
+/// Aggregate timing of an opcode, opcode class or precompile.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Timing {
+	/// Number of executions.
+	pub count: u64,
+	/// Total time of all executions.
+	pub total: Duration,
+}
+
+impl Timing {
+	fn record(&mut self, elapsed: Duration) {
+		self.count += 1;
+		self.total += elapsed;
+	}
+
+	fn merge(&mut self, other: &Timing) {
+		self.count += other.count;
+		self.total += other.total;
+	}
+
+	/// Average time of an execution.
+	pub fn average(&self) -> Duration {
+		if self.count == 0 {
+			Duration::ZERO
+		} else {
+			Duration::from_nanos((self.total.as_nanos() / u128::from(self.count)) as u64)
+		}
+	}
+}
+
+/// Class of opcodes with a similar cost profile.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum OpcodeClass {
+	/// `STOP` and arithmetic.
+	Arithmetic,
+	/// Comparison and bitwise logic.
+	Bitwise,
+	/// `SHA3`.
+	Hashing,
+	/// Call and account information, including `BALANCE` and `EXTCODE*`.
+	Environment,
+	/// Block information.
+	Block,
+	/// Persistent and transient storage.
+	Storage,
+	/// Memory.
+	Memory,
+	/// Jumps and `PC`.
+	Flow,
+	/// `POP`, `PUSH*`, `DUP*` and `SWAP*`.
+	Stack,
+	/// `LOG*`.
+	Log,
+	/// Calls, creates, returns and `SELFDESTRUCT`.
+	System,
+	/// Undefined opcodes.
+	Other,
+}
+
+impl OpcodeClass {
+	/// Class of an opcode.
+	pub fn of(opcode: Opcode) -> Self {
+		match opcode.0 {
+			0x00..=0x0b => Self::Arithmetic,
+			0x10..=0x1d => Self::Bitwise,
+			0x20 => Self::Hashing,
+			0x30..=0x3f => Self::Environment,
+			0x40..=0x4a => Self::Block,
+			0x54 | 0x55 | 0x5c | 0x5d => Self::Storage,
+			0x51..=0x53 | 0x59 | 0x5e => Self::Memory,
+			0x56..=0x58 | 0x5b => Self::Flow,
+			0x50 | 0x5a | 0x5f..=0x9f => Self::Stack,
+			0xa0..=0xa4 => Self::Log,
+			0xf0..=0xf5 | 0xfa | 0xfd | 0xff => Self::System,
+			_ => Self::Other,
+		}
+	}
+}
+
+/// Eval tracer timing each evaluated opcode.
+pub struct OpcodeTimer {
+	timings: Vec<Timing>,
+	pending: Option<(Opcode, Instant)>,
+}
+
+impl Default for OpcodeTimer {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl OpcodeTimer {
+	/// Create a new timer.
+	pub fn new() -> Self {
+		Self {
+			timings: vec![Timing::default(); 256],
+			pending: None,
+		}
+	}
+
+	/// Attribute the time since the last evaluation to its opcode. Must be
+	/// called once the execution finishes.
+	pub fn finish(&mut self) {
+		if let Some((opcode, started)) = self.pending.take() {
+			self.timings[opcode.as_usize()].record(started.elapsed());
+		}
+	}
+
+	/// Timing of an opcode.
+	pub fn opcode(&self, opcode: Opcode) -> Timing {
+		self.timings[opcode.as_usize()]
+	}
+
+	/// Timings of opcodes that were executed at least once, in opcode order.
+	pub fn by_opcode(&self) -> Vec<(Opcode, Timing)> {
+		self.timings
+			.iter()
+			.enumerate()
+			.filter(|(_, timing)| timing.count > 0)
+			.map(|(opcode, timing)| (Opcode(opcode as u8), *timing))
+			.collect()
+	}
+
+	/// Timings aggregated by opcode class.
+	pub fn by_class(&self) -> BTreeMap<OpcodeClass, Timing> {
+		let mut classes = BTreeMap::<OpcodeClass, Timing>::new();
+		for (opcode, timing) in self.by_opcode() {
+			classes
+				.entry(OpcodeClass::of(opcode))
+				.or_default()
+				.merge(&timing);
+		}
+		classes
+	}
+}
+
+impl<H> EvalTracer<H> for OpcodeTimer {
+	fn on_eval(&mut self, _machine: &Machine, _handle: &H, opcode: Opcode, _position: usize) {
+		let now = Instant::now();
+		if let Some((previous, started)) = self.pending.replace((opcode, now)) {
+			self.timings[previous.as_usize()].record(now - started);
+		}
+	}
+}
+
+/// Precompile set wrapper timing each precompile execution.
+pub struct TimedPrecompileSet<P> {
+	precompiles: P,
+	timings: RefCell<BTreeMap<H160, Timing>>,
+}
+
+impl<P> TimedPrecompileSet<P> {
+	/// Wrap a precompile set.
+	pub fn new(precompiles: P) -> Self {
+		Self {
+			precompiles,
+			timings: RefCell::new(BTreeMap::new()),
+		}
+	}
+
+	/// Timings of executed precompiles, by address.
+	pub fn timings(&self) -> BTreeMap<H160, Timing> {
+		self.timings.borrow().clone()
+	}
+}
+
+impl<S, H, P: PrecompileSet<S, H>> PrecompileSet<S, H> for TimedPrecompileSet<P> {
+	fn execute(
+		&self,
+		code_address: H160,
+		input: &[u8],
+		state: &mut S,
+		handler: &mut H,
+	) -> Option<(ExitResult, Vec<u8>)> {
+		let started = Instant::now();
+		let ret = self
+			.precompiles
+			.execute(code_address, input, state, handler)?;
+		self.timings
+			.borrow_mut()
+			.entry(code_address)
+			.or_default()
+			.record(started.elapsed());
+		Some(ret)
+	}
+}